@@ -2,16 +2,26 @@
 //! Animation data structure definition.
 //!
 
+use ::alloc::alloc::{self, Layout};
+use ::alloc::string::String;
+use ::alloc::vec::Vec;
+use core::simd::prelude::*;
+use core::{mem, slice};
 use glam::{Quat, Vec3, Vec4};
-use std::alloc::{self, Layout};
+#[cfg(feature = "std")]
 use std::io::Read;
-use std::simd::prelude::*;
-use std::simd::*;
-use std::{mem, slice};
 
+use crate::animation_builder::AnimationBuilder;
+#[cfg(feature = "std")]
 use crate::archive::{Archive, ArchiveRead};
+#[cfg(feature = "std")]
+use crate::base::SKELETON_MAX_JOINTS;
 use crate::base::{align_ptr, align_usize, OzzError};
-use crate::math::{f16_to_f32, fx4, ix4, simd_f16_to_f32, SoaQuat, SoaVec3, ONE, ZERO};
+use crate::math::{
+    f16_to_f32, f32_clamp_or_max, f32_to_f16, simd_f16_to_f32, SoaQuat, SoaTransform, SoaVec3, StdFloat, ONE, ZERO,
+};
+#[cfg(feature = "std")]
+use crate::skeleton::Skeleton;
 
 /// Float3 key for `Animation` track.
 #[repr(C)]
@@ -25,19 +35,57 @@ impl Float3Key {
         Float3Key(value)
     }
 
+    /// Compresses a `Vec3` to half precision float key.
+    #[inline]
+    pub fn from_f32(value: Vec3) -> Float3Key {
+        Float3Key([f32_to_f16(value.x), f32_to_f16(value.y), f32_to_f16(value.z)])
+    }
+
     #[inline]
     pub fn decompress(&self) -> Vec3 {
         Vec3::new(f16_to_f32(self.0[0]), f16_to_f32(self.0[1]), f16_to_f32(self.0[2]))
     }
 
+    /// Compares quantized values of two keys.
+    ///
+    /// Keys don't hold their ratio (stored apart by `Animation`), so it's the same as `==`, but explicit about
+    /// what's compared.
+    #[inline]
+    pub fn value_eq(&self, other: &Float3Key) -> bool {
+        self.0 == other.0
+    }
+
     #[inline]
     pub fn simd_decompress(k0: &Float3Key, k1: &Float3Key, k2: &Float3Key, k3: &Float3Key, soa: &mut SoaVec3) {
         soa.x = simd_f16_to_f32([k0.0[0], k1.0[0], k2.0[0], k3.0[0]]);
         soa.y = simd_f16_to_f32([k0.0[1], k1.0[1], k2.0[1], k3.0[1]]);
         soa.z = simd_f16_to_f32([k0.0[2], k1.0[2], k2.0[2], k3.0[2]]);
+
+        #[cfg(debug_assertions)]
+        {
+            let mut scalar = SoaVec3::default();
+            Float3Key::decompress_scalar4(k0, k1, k2, k3, &mut scalar);
+            debug_assert!(
+                lanes_approx_eq(soa.x, scalar.x)
+                    && lanes_approx_eq(soa.y, scalar.y)
+                    && lanes_approx_eq(soa.z, scalar.z),
+                "Float3Key::simd_decompress() mismatches the scalar path"
+            );
+        }
+    }
+
+    /// Decompresses 4 keys into a `SoaVec3` one key at a time, without SIMD arithmetic.
+    ///
+    /// Produces the same result as `simd_decompress`, it's used to verify the SIMD path.
+    pub fn decompress_scalar4(k0: &Float3Key, k1: &Float3Key, k2: &Float3Key, k3: &Float3Key, soa: &mut SoaVec3) {
+        let v = [k0.decompress(), k1.decompress(), k2.decompress(), k3.decompress()];
+        soa.x = f32x4::from_array([v[0].x, v[1].x, v[2].x, v[3].x]);
+        soa.y = f32x4::from_array([v[0].y, v[1].y, v[2].y, v[3].y]);
+        soa.z = f32x4::from_array([v[0].z, v[1].z, v[2].z, v[3].z]);
     }
 }
 
+#[cfg(feature = "std")]
 impl ArchiveRead<Float3Key> for Float3Key {
     #[inline]
     fn read<R: Read>(archive: &mut Archive<R>) -> Result<Float3Key, OzzError> {
@@ -58,6 +106,180 @@ impl QuaternionKey {
         QuaternionKey(value)
     }
 
+    /// Compresses a normalized `Quat` to quaternion key.
+    ///
+    /// The largest component is dropped and restored from the 3 others at decompression time.
+    /// Only its index and sign are stored, the 3 smallest components are quantized on 15 bits.
+    pub fn from_quat(value: Quat) -> QuaternionKey {
+        const SCALE: f32 = 32767.0 / core::f32::consts::SQRT_2;
+        const OFFSET: f32 = core::f32::consts::SQRT_2 / 2.0;
+
+        let cpnt = value.to_array();
+        let mut largest = 0;
+        for idx in 1..4 {
+            if cpnt[idx].abs() > cpnt[largest].abs() {
+                largest = idx;
+            }
+        }
+        let sign = (cpnt[largest] < 0.0) as u16;
+
+        let mut value = [0u32; 3];
+        let mut idx = 0;
+        for (i, &c) in cpnt.iter().enumerate() {
+            if i != largest {
+                value[idx] = ((c + OFFSET) * SCALE + 0.5).clamp(0.0, 32767.0) as u32;
+                idx += 1;
+            }
+        }
+
+        QuaternionKey([
+            (largest as u16) | (sign << 2) | ((value[0] << 3) as u16),
+            ((value[0] >> 13) | (value[1] << 2)) as u16,
+            ((value[1] >> 14) | (value[2] << 1)) as u16,
+        ])
+    }
+
+    /// Worst case error of a quantized component, half of the 15 bits quantization step.
+    ///
+    /// Components are quantized in range [-sqrt(2)/2, sqrt(2)/2], so the step is `sqrt(2) / 32767`.
+    #[inline]
+    pub const fn max_component_error() -> f32 {
+        1.0 / (32767.0 * core::f32::consts::SQRT_2)
+    }
+
+    /// Compresses then decompresses `original`, and returns the angle (in radians) between both rotations.
+    ///
+    /// `original` is normalized first. The error stays below `2e-4` radians.
+    pub fn quantization_error(original: Quat) -> f32 {
+        let original = original.normalize();
+        let diff = QuaternionKey::from_quat(original).decompress().inverse() * original;
+        2.0 * libm::atan2f(diff.xyz().length(), diff.w.abs())
+    }
+
+    /// Minimum number of bits per smallest component supported by `pack_bits()`.
+    pub const MIN_BITS: u32 = 8;
+    /// Maximum number of bits per smallest component, the default quantization.
+    pub const MAX_BITS: u32 = 15;
+
+    fn check_bits(bits: u32) -> Result<(), OzzError> {
+        if !(Self::MIN_BITS..=Self::MAX_BITS).contains(&bits) {
+            return Err(OzzError::InvalidData {
+                field: "bits",
+                value: bits as i64,
+            });
+        }
+        Ok(())
+    }
+
+    /// Recompresses the key with `bits` per smallest component, in range `MIN_BITS..=MAX_BITS`.
+    ///
+    /// Returns the largest component index (2 bits), sign (1 bit) and the 3 smallest components (`bits` each),
+    /// packed in the low `3 + 3 * bits` bits. Components step grows by `2^(15 - bits)`, and so does the angular
+    /// error, which stays below 2e-3 radians at 12 bits. Keys packed with 15 bits are lossless.
+    /// Returns `OzzError::InvalidData` if `bits` isn't supported.
+    pub fn pack_bits(&self, bits: u32) -> Result<u64, OzzError> {
+        Self::check_bits(bits)?;
+        let max = ((1u32 << bits) - 1) as u64;
+        let (largest, sign, value) = self.unpack();
+        let mut packed = (largest as u64) | ((sign as u64) << 2);
+        for (idx, &v) in value.iter().enumerate() {
+            let v = (v as u64 * max + 32767 / 2) / 32767;
+            packed |= v << (3 + idx as u32 * bits);
+        }
+        Ok(packed)
+    }
+
+    /// Decompresses a key packed by `pack_bits()` with the same `bits`, back to the default 15 bits quantization.
+    /// Returns `OzzError::InvalidData` if `bits` isn't supported.
+    pub fn unpack_bits(packed: u64, bits: u32) -> Result<QuaternionKey, OzzError> {
+        Self::check_bits(bits)?;
+        let max = ((1u32 << bits) - 1) as u64;
+        let mut value = [0u32; 3];
+        for (idx, v) in value.iter_mut().enumerate() {
+            let packed = (packed >> (3 + idx as u32 * bits)) & max;
+            *v = ((packed * 32767 + max / 2) / max) as u32;
+        }
+        let (largest, sign) = ((packed & 0x3) as u16, ((packed >> 2) & 0x1) as u16);
+        Ok(QuaternionKey::pack(largest, sign, value))
+    }
+
+    /// Writes keys recompressed with `bits` per smallest component as a bit stream, see `pack_bits()`.
+    ///
+    /// Each key takes `3 + 3 * bits` bits, instead of 48 bits for `QuaternionKey`. Bits are written from the
+    /// lowest of each byte, the last byte is padded with zeros. Returns `OzzError::InvalidData` if `bits` isn't
+    /// supported.
+    pub fn write_packed(keys: &[QuaternionKey], bits: u32) -> Result<Vec<u8>, OzzError> {
+        Self::check_bits(bits)?;
+        let key_bits = 3 + 3 * bits as usize;
+        let mut bytes = ::alloc::vec![0u8; (keys.len() * key_bits).div_ceil(8)];
+        for (idx, key) in keys.iter().enumerate() {
+            let packed = key.pack_bits(bits)?;
+            for bit in 0..key_bits {
+                let pos = idx * key_bits + bit;
+                bytes[pos / 8] |= (((packed >> bit) & 0x1) as u8) << (pos % 8);
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Reads `count` keys written by `write_packed()` with the same `bits`.
+    ///
+    /// Keys are decompressed back to the default 15 bits quantization, so they can be sampled as any other key.
+    /// Returns `OzzError::InvalidData` if `bits` isn't supported or `bytes` is too short.
+    pub fn read_packed(bytes: &[u8], count: usize, bits: u32) -> Result<Vec<QuaternionKey>, OzzError> {
+        Self::check_bits(bits)?;
+        let key_bits = 3 + 3 * bits as usize;
+        if bytes.len() < (count * key_bits).div_ceil(8) {
+            return Err(OzzError::InvalidData {
+                field: "packed_quaternion_keys",
+                value: bytes.len() as i64,
+            });
+        }
+        (0..count)
+            .map(|idx| {
+                let mut packed = 0u64;
+                for bit in 0..key_bits {
+                    let pos = idx * key_bits + bit;
+                    packed |= (((bytes[pos / 8] >> (pos % 8)) & 0x1) as u64) << bit;
+                }
+                QuaternionKey::unpack_bits(packed, bits)
+            })
+            .collect()
+    }
+
+    /// Compares quantized values (largest component index, sign and 3 smallest components) of two keys.
+    ///
+    /// See `Float3Key::value_eq()`.
+    #[inline]
+    pub fn value_eq(&self, other: &QuaternionKey) -> bool {
+        self.0 == other.0
+    }
+
+    /// Gets the index (0 for x, 1 for y, 2 for z, 3 for w) of the largest component, which isn't stored but
+    /// restored from the 3 smallest components.
+    #[inline]
+    pub fn largest(&self) -> usize {
+        (self.0[0] & 0x3) as usize
+    }
+
+    /// Gets the key of the opposite quaternion, which represents the same rotation.
+    ///
+    /// Flips the largest component sign and mirrors the 3 smallest components around zero, it's lossless.
+    #[inline]
+    pub fn negated(&self) -> QuaternionKey {
+        let (largest, sign, value) = self.unpack();
+        QuaternionKey::pack(largest, sign ^ 1, value.map(|v| 0x7fff - v))
+    }
+
+    #[inline]
+    fn pack(largest: u16, sign: u16, value: [u32; 3]) -> QuaternionKey {
+        QuaternionKey([
+            largest | (sign << 2) | ((value[0] << 3) as u16),
+            ((value[0] >> 13) | (value[1] << 2)) as u16,
+            ((value[1] >> 14) | (value[2] << 1)) as u16,
+        ])
+    }
+
     #[inline]
     fn unpack(&self) -> (u16, u16, [u32; 3]) {
         let packed: u32 = ((self.0[0] as u32) >> 3) | ((self.0[1] as u32) << 13) | ((self.0[2] as u32) << 29);
@@ -102,10 +324,10 @@ impl QuaternionKey {
         k3: &QuaternionKey,
         soa: &mut SoaQuat,
     ) {
-        const MASK_F000:i32x4 = i32x4::from_array([-1i32, 0, 0, 0]);
-        const MASK_0F00:i32x4 = i32x4::from_array([0, -1i32, 0, 0]);
-        const MASK_00F0:i32x4 = i32x4::from_array([0, 0, -1i32, 0]);
-        const MASK_000F:i32x4 = i32x4::from_array([0, 0, 0, -1i32]);
+        const MASK_F000:u32x4 = u32x4::from_array([u32::MAX, 0, 0, 0]);
+        const MASK_0F00:u32x4 = u32x4::from_array([0, u32::MAX, 0, 0]);
+        const MASK_00F0:u32x4 = u32x4::from_array([0, 0, u32::MAX, 0]);
+        const MASK_000F:u32x4 = u32x4::from_array([0, 0, 0, u32::MAX]);
 
         const MAPPING: [[usize; 4]; 4] = [[0, 0, 1, 2], [0, 0, 1, 2], [0, 1, 0, 2], [0, 1, 2, 0]];
 
@@ -123,11 +345,11 @@ impl QuaternionKey {
         let m3 = &MAPPING[largest3 as usize];
 
         let cmp_keys: [f32x4; 4] = [
-            f32x4::from_array([ value0[m0[0]] as f32, value1[m1[0]] as f32, value2[m2[0]] as f32, value3[m3[0]] as f32 ]),
-            f32x4::from_array([ value0[m0[1]] as f32, value1[m1[1]] as f32, value2[m2[1]] as f32, value3[m3[1]] as f32 ]),
-            f32x4::from_array([ value0[m0[2]] as f32, value1[m1[2]] as f32, value2[m2[2]] as f32, value3[m3[2]] as f32 ]),
-            f32x4::from_array([ value0[m0[3]] as f32, value1[m1[3]] as f32, value2[m2[3]] as f32, value3[m3[3]] as f32 ]),
-        ]; // TODO: simd int to float
+            u32x4::from_array([ value0[m0[0]], value1[m1[0]], value2[m2[0]], value3[m3[0]] ]).cast(),
+            u32x4::from_array([ value0[m0[1]], value1[m1[1]], value2[m2[1]], value3[m3[1]] ]).cast(),
+            u32x4::from_array([ value0[m0[2]], value1[m1[2]], value2[m2[2]], value3[m3[2]] ]).cast(),
+            u32x4::from_array([ value0[m0[3]], value1[m1[3]], value2[m2[3]], value3[m3[3]] ]).cast(),
+        ];
 
         let mut cpnt = [
             SCALE * cmp_keys[0] + OFFSET,
@@ -135,29 +357,58 @@ impl QuaternionKey {
             SCALE * cmp_keys[2] + OFFSET,
             SCALE * cmp_keys[3] + OFFSET,
         ];
-        cpnt[largest0 as usize] = fx4(ix4(cpnt[largest0 as usize]) & !MASK_F000);
-        cpnt[largest1 as usize] = fx4(ix4(cpnt[largest1 as usize]) & !MASK_0F00);
-        cpnt[largest2 as usize] = fx4(ix4(cpnt[largest2 as usize]) & !MASK_00F0);
-        cpnt[largest3 as usize] = fx4(ix4(cpnt[largest3 as usize]) & !MASK_000F);
+        cpnt[largest0 as usize] = f32x4::from_bits(cpnt[largest0 as usize].to_bits() & !MASK_F000);
+        cpnt[largest1 as usize] = f32x4::from_bits(cpnt[largest1 as usize].to_bits() & !MASK_0F00);
+        cpnt[largest2 as usize] = f32x4::from_bits(cpnt[largest2 as usize].to_bits() & !MASK_00F0);
+        cpnt[largest3 as usize] = f32x4::from_bits(cpnt[largest3 as usize].to_bits() & !MASK_000F);
 
         let dot = cpnt[0] * cpnt[0] + cpnt[1] * cpnt[1] + cpnt[2] * cpnt[2] + cpnt[3] * cpnt[3];
         let ww0 =  f32x4::simd_max(ZERO, ONE - dot); // prevent NaN, different from C++ code
         let w0 = ww0.sqrt();
-        let sign = i32x4::from_array([sign0 as i32, sign1 as i32, sign2 as i32, sign3 as i32]) << 31;
-        let restored = ix4(w0) | sign;
+        let sign = u32x4::from_array([sign0 as u32, sign1 as u32, sign2 as u32, sign3 as u32]) << 31;
+        let restored = w0.to_bits() | sign;
 
-        cpnt[largest0 as usize] = fx4(ix4(cpnt[largest0 as usize]) | (restored & MASK_F000));
-        cpnt[largest1 as usize] = fx4(ix4(cpnt[largest1 as usize]) | (restored & MASK_0F00));
-        cpnt[largest2 as usize] = fx4(ix4(cpnt[largest2 as usize]) | (restored & MASK_00F0));
-        cpnt[largest3 as usize] = fx4(ix4(cpnt[largest3 as usize]) | (restored & MASK_000F));
+        cpnt[largest0 as usize] = f32x4::from_bits(cpnt[largest0 as usize].to_bits() | (restored & MASK_F000));
+        cpnt[largest1 as usize] = f32x4::from_bits(cpnt[largest1 as usize].to_bits() | (restored & MASK_0F00));
+        cpnt[largest2 as usize] = f32x4::from_bits(cpnt[largest2 as usize].to_bits() | (restored & MASK_00F0));
+        cpnt[largest3 as usize] = f32x4::from_bits(cpnt[largest3 as usize].to_bits() | (restored & MASK_000F));
 
         soa.x = cpnt[0];
         soa.y = cpnt[1];
         soa.z = cpnt[2];
         soa.w = cpnt[3];
+
+        #[cfg(debug_assertions)]
+        {
+            let mut scalar = SoaQuat::default();
+            QuaternionKey::decompress_scalar4(k0, k1, k2, k3, &mut scalar);
+            debug_assert!(
+                lanes_approx_eq(soa.x, scalar.x) && lanes_approx_eq(soa.y, scalar.y) &&
+                lanes_approx_eq(soa.z, scalar.z) && lanes_approx_eq(soa.w, scalar.w),
+                "QuaternionKey::simd_decompress() mismatches the scalar path"
+            );
+        }
+    }
+
+    /// Decompresses 4 keys into a `SoaQuat` one key at a time, without SIMD arithmetic.
+    ///
+    /// Produces the same result as `simd_decompress`, it's used to verify the SIMD path.
+    pub fn decompress_scalar4(
+        k0: &QuaternionKey,
+        k1: &QuaternionKey,
+        k2: &QuaternionKey,
+        k3: &QuaternionKey,
+        soa: &mut SoaQuat,
+    ) {
+        let q = [k0.decompress(), k1.decompress(), k2.decompress(), k3.decompress()];
+        soa.x = f32x4::from_array([q[0].x, q[1].x, q[2].x, q[3].x]);
+        soa.y = f32x4::from_array([q[0].y, q[1].y, q[2].y, q[3].y]);
+        soa.z = f32x4::from_array([q[0].z, q[1].z, q[2].z, q[3].z]);
+        soa.w = f32x4::from_array([q[0].w, q[1].w, q[2].w, q[3].w]);
     }
 }
 
+#[cfg(feature = "std")]
 impl ArchiveRead<QuaternionKey> for QuaternionKey {
     #[inline]
     fn read<R: Read>(archive: &mut Archive<R>) -> Result<QuaternionKey, OzzError> {
@@ -166,6 +417,256 @@ impl ArchiveRead<QuaternionKey> for QuaternionKey {
     }
 }
 
+/// Compares the SIMD and scalar decompression results, NaN lanes are equal to NaN lanes.
+#[cfg(any(debug_assertions, test))]
+fn lanes_approx_eq(a: f32x4, b: f32x4) -> bool {
+    const EPSILON: f32x4 = f32x4::from_array([1e-6; 4]);
+    (a.simd_eq(b) | (a - b).abs().simd_le(EPSILON) | (a.is_nan() & b.is_nan())).all()
+}
+
+/// Uncompressed float3 key, for building an `Animation` at runtime.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Float3KeyF32([f32; 3]);
+
+impl Float3KeyF32 {
+    pub const fn new(value: [f32; 3]) -> Float3KeyF32 {
+        Float3KeyF32(value)
+    }
+
+    #[inline]
+    pub fn value(&self) -> Vec3 {
+        Vec3::from_array(self.0)
+    }
+
+    /// Compresses to `Float3Key`.
+    #[inline]
+    pub fn compress(&self) -> Float3Key {
+        Float3Key::from_f32(self.value())
+    }
+}
+
+impl From<Vec3> for Float3KeyF32 {
+    #[inline]
+    fn from(value: Vec3) -> Float3KeyF32 {
+        Float3KeyF32(value.to_array())
+    }
+}
+
+impl From<Float3KeyF32> for Float3Key {
+    #[inline]
+    fn from(key: Float3KeyF32) -> Float3Key {
+        key.compress()
+    }
+}
+
+/// Uncompressed quaternion key, for building an `Animation` at runtime.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuaternionKeyF32([f32; 4]);
+
+impl QuaternionKeyF32 {
+    pub const fn new(value: [f32; 4]) -> QuaternionKeyF32 {
+        QuaternionKeyF32(value)
+    }
+
+    #[inline]
+    pub fn value(&self) -> Quat {
+        Quat::from_array(self.0)
+    }
+
+    /// Compresses to `QuaternionKey`, `value` should be normalized.
+    #[inline]
+    pub fn compress(&self) -> QuaternionKey {
+        QuaternionKey::from_quat(self.value())
+    }
+}
+
+impl From<Quat> for QuaternionKeyF32 {
+    #[inline]
+    fn from(value: Quat) -> QuaternionKeyF32 {
+        QuaternionKeyF32(value.to_array())
+    }
+}
+
+impl From<QuaternionKeyF32> for QuaternionKey {
+    #[inline]
+    fn from(key: QuaternionKeyF32) -> QuaternionKey {
+        key.compress()
+    }
+}
+
+/// Rotation keys of a track, encoded as deltas from the previous key.
+///
+/// An alternative to absolute `QuaternionKey`s for tracks that barely rotate, like twist correctives: each
+/// delta stores the 3 imaginary components of the rotation from the previous key, quantized to 8 bits over the
+/// track `range`. It takes 3 bytes per key instead of 6. Deltas are computed from the reconstructed previous key,
+/// so quantization errors don't accumulate along the track. Built by `Animation::encode_rotation_deltas()`,
+/// and decoded back to absolute keys by `Animation::with_rotation_deltas()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeltaRotationTrack {
+    /// Ratios of the keys, the first key included.
+    pub ratios: Vec<f32>,
+    /// First key of the track, absolute.
+    pub first: QuaternionKey,
+    /// Largest imaginary component of the deltas, which maps to `i8::MAX`.
+    pub range: f32,
+    /// Deltas of the keys following the first one.
+    pub deltas: Vec<[i8; 3]>,
+}
+
+impl DeltaRotationTrack {
+    /// Encodes keyframes `(ratio, key)` of a track, sorted by ratio.
+    pub fn encode(keyframes: &[(f32, QuaternionKey)]) -> DeltaRotationTrack {
+        let Some((_, first)) = keyframes.first() else {
+            return DeltaRotationTrack {
+                ratios: Vec::new(),
+                first: QuaternionKey::from_quat(Quat::IDENTITY),
+                range: f32::EPSILON,
+                deltas: Vec::new(),
+            };
+        };
+
+        let rotations: Vec<Quat> = keyframes.iter().map(|(_, key)| key.decompress()).collect();
+        let range = rotations
+            .windows(2)
+            .map(|pair| Self::delta(pair[0], pair[1]).abs().max_element())
+            .fold(0.0, f32::max);
+        // Margin for deltas from reconstructed keys, which differ slightly from the decompressed ones.
+        let range = (range * 1.01).max(f32::EPSILON);
+
+        let mut track = DeltaRotationTrack {
+            ratios: keyframes.iter().map(|(ratio, _)| *ratio).collect(),
+            first: *first,
+            range,
+            deltas: Vec::with_capacity(rotations.len() - 1),
+        };
+        let mut current = rotations[0];
+        for rotation in &rotations[1..] {
+            let delta = Self::delta(current, *rotation) * (i8::MAX as f32 / range);
+            let delta = delta
+                .to_array()
+                .map(|v| libm::roundf(v).clamp(-(i8::MAX as f32), i8::MAX as f32) as i8);
+            current = track.apply(current, delta);
+            track.deltas.push(delta);
+        }
+        track
+    }
+
+    /// Decodes the keyframes `(ratio, key)` of the track, `ratios` and `deltas` must match.
+    pub fn decode(&self) -> Vec<(f32, QuaternionKey)> {
+        let mut current = self.first.decompress();
+        let mut keyframes = Vec::with_capacity(self.ratios.len());
+        keyframes.extend(self.ratios.first().map(|ratio| (*ratio, self.first)));
+        for (ratio, delta) in self.ratios.iter().skip(1).zip(self.deltas.iter()) {
+            current = self.apply(current, *delta);
+            keyframes.push((*ratio, QuaternionKey::from_quat(current)));
+        }
+        keyframes
+    }
+
+    // Imaginary components of the rotation from `from` to `to`, with a positive real component.
+    fn delta(from: Quat, to: Quat) -> Vec3 {
+        let delta = from.conjugate() * to;
+        match delta.w < 0.0 {
+            true => -delta.xyz(),
+            false => delta.xyz(),
+        }
+    }
+
+    fn apply(&self, current: Quat, delta: [i8; 3]) -> Quat {
+        let xyz = Vec3::from_array(delta.map(|v| v as f32)) * (self.range / i8::MAX as f32);
+        let w = f32::max(0.0, 1.0 - xyz.length_squared()).sqrt();
+        (current * Quat::from_xyzw(xyz.x, xyz.y, xyz.z, w)).normalize()
+    }
+}
+
+/// Time ratio in an animation, in the unit interval 0.0-1.0 (0 is the beginning, 1 is the end).
+///
+/// Clamped on construction, it distinguishes ratios from times in seconds (see `Seconds`) at API boundaries.
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Ratio(f32);
+
+impl Ratio {
+    /// Creates a `Ratio`, clamped in range 0.0-1.0.
+    #[inline]
+    pub fn new(ratio: f32) -> Ratio {
+        Ratio(f32_clamp_or_max(ratio, 0.0, 1.0))
+    }
+
+    /// Gets the ratio value, in range 0.0-1.0.
+    #[inline]
+    pub fn value(&self) -> f32 {
+        self.0
+    }
+
+    /// Converts to a time in seconds, for an animation of `duration` seconds.
+    #[inline]
+    pub fn to_seconds(self, duration: f32) -> Seconds {
+        Seconds(self.0 * duration)
+    }
+}
+
+impl From<f32> for Ratio {
+    #[inline]
+    fn from(ratio: f32) -> Ratio {
+        Ratio::new(ratio)
+    }
+}
+
+impl From<Ratio> for f32 {
+    #[inline]
+    fn from(ratio: Ratio) -> f32 {
+        ratio.0
+    }
+}
+
+/// Time in seconds in an animation, see `Ratio`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Seconds(f32);
+
+impl Seconds {
+    /// Creates a `Seconds`.
+    #[inline]
+    pub fn new(seconds: f32) -> Seconds {
+        Seconds(seconds)
+    }
+
+    /// Gets the time value, in seconds.
+    #[inline]
+    pub fn value(&self) -> f32 {
+        self.0
+    }
+
+    /// Converts to a time ratio, for an animation of `duration` seconds. Like `Animation::ratio_at_time()`,
+    /// the ratio is clamped, and it's 0 for zero duration animations.
+    #[inline]
+    pub fn to_ratio(self, duration: f32) -> Ratio {
+        if duration <= 0.0 {
+            return Ratio(0.0);
+        }
+        Ratio::new(self.0 / duration)
+    }
+}
+
+impl From<f32> for Seconds {
+    #[inline]
+    fn from(seconds: f32) -> Seconds {
+        Seconds(seconds)
+    }
+}
+
+impl From<Seconds> for f32 {
+    #[inline]
+    fn from(seconds: Seconds) -> f32 {
+        seconds.0
+    }
+}
+
 ///
 /// Defines a runtime skeletal animation clip.
 ///
@@ -228,26 +729,44 @@ impl Drop for Animation {
                 let layout = Layout::from_size_align_unchecked(self.size, mem::size_of::<f32>());
                 alloc::dealloc(self.timepoints as *mut u8, layout);
             }
-            self.timepoints = std::ptr::null_mut();
-            self.translations = std::ptr::null_mut();
-            self.t_ratios = std::ptr::null_mut();
-            self.t_previouses = std::ptr::null_mut();
-            self.t_iframe_entries = std::ptr::null_mut();
-            self.t_iframe_desc = std::ptr::null_mut();
-            self.rotations = std::ptr::null_mut();
-            self.r_ratios = std::ptr::null_mut();
-            self.r_previouses = std::ptr::null_mut();
-            self.r_iframe_entries = std::ptr::null_mut();
-            self.r_iframe_desc = std::ptr::null_mut();
-            self.scales = std::ptr::null_mut();
-            self.s_ratios = std::ptr::null_mut();
-            self.s_previouses = std::ptr::null_mut();
-            self.s_iframe_entries = std::ptr::null_mut();
-            self.s_iframe_desc = std::ptr::null_mut();
+            self.timepoints = core::ptr::null_mut();
+            self.translations = core::ptr::null_mut();
+            self.t_ratios = core::ptr::null_mut();
+            self.t_previouses = core::ptr::null_mut();
+            self.t_iframe_entries = core::ptr::null_mut();
+            self.t_iframe_desc = core::ptr::null_mut();
+            self.rotations = core::ptr::null_mut();
+            self.r_ratios = core::ptr::null_mut();
+            self.r_previouses = core::ptr::null_mut();
+            self.r_iframe_entries = core::ptr::null_mut();
+            self.r_iframe_desc = core::ptr::null_mut();
+            self.scales = core::ptr::null_mut();
+            self.s_ratios = core::ptr::null_mut();
+            self.s_previouses = core::ptr::null_mut();
+            self.s_iframe_entries = core::ptr::null_mut();
+            self.s_iframe_desc = core::ptr::null_mut();
         }
     }
 }
 
+/// Prints a one line summary of `Animation`: name, duration, number of tracks and keys count of each kind.
+///
+/// Keys aren't printed, which makes it suited for logs.
+impl core::fmt::Display for Animation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Animation {{ name: {:?}, duration: {}s, num_tracks: {}, translations: {}, rotations: {}, scales: {} }}",
+            self.name(),
+            self.duration(),
+            self.num_tracks(),
+            self.translations().len(),
+            self.rotations().len(),
+            self.scales().len()
+        )
+    }
+}
+
 /// Animation meta in `Archive`.
 #[derive(Debug, Default, Clone)]
 pub struct AnimationMeta {
@@ -267,7 +786,7 @@ pub struct AnimationMeta {
     pub s_iframe_desc_count: u32,
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct AnimationRaw {
     pub duration: f32,
@@ -297,6 +816,22 @@ pub(crate) struct AnimationRaw {
     pub s_iframe_desc: Vec<u32>,
 }
 
+/// Maximum length of an animation name read from an `Archive`.
+#[cfg(feature = "std")]
+pub(crate) const ANIMATION_MAX_NAME_LEN: usize = u16::MAX as usize;
+
+/// Checks an int32 count read from an `Archive`, which must be non-negative and not greater than `max`.
+#[cfg(feature = "std")]
+pub(crate) fn check_count(field: &'static str, value: u32, max: usize) -> Result<u32, OzzError> {
+    if (value as i32) < 0 || value as usize > max {
+        return Err(OzzError::InvalidData {
+            field,
+            value: value as i32 as i64,
+        });
+    }
+    Ok(value)
+}
+
 impl Animation {
     /// `Animation` resource file tag for `Archive`.
     #[inline]
@@ -310,6 +845,7 @@ impl Animation {
         7
     }
 
+    #[cfg(feature = "std")]
     /// Reads an `AnimationMeta` from an `Archive`.
     pub fn read_meta(archive: &mut Archive<impl Read>) -> Result<AnimationMeta, OzzError> {
         if archive.tag() != Self::tag() {
@@ -333,6 +869,25 @@ impl Animation {
         let s_iframe_entries_count: u32 = archive.read()?;
         let s_iframe_desc_count: u32 = archive.read()?;
 
+        // Counts are int32 in ozz archives, validates them before allocating anything.
+        let num_tracks = check_count("num_tracks", num_tracks, SKELETON_MAX_JOINTS as usize)?;
+        let name_len = check_count("name_len", name_len, ANIMATION_MAX_NAME_LEN)?;
+        let timepoints_count = check_count("timepoints_count", timepoints_count, u16::MAX as usize + 1)?;
+        // A track has at most one key per timepoint.
+        let max_keys = (num_tracks as usize).div_ceil(4) * 4 * timepoints_count as usize;
+        let translations_count = check_count("translations_count", translations_count, max_keys)?;
+        let rotations_count = check_count("rotations_count", rotations_count, max_keys)?;
+        let scales_count = check_count("scales_count", scales_count, max_keys)?;
+        // Iframe entries are varint encoded key indices, and each iframe has 2 descriptors.
+        let max_entries = max_keys * 5;
+        let max_desc = timepoints_count as usize * 2;
+        let t_iframe_entries_count = check_count("t_iframe_entries_count", t_iframe_entries_count, max_entries)?;
+        let t_iframe_desc_count = check_count("t_iframe_desc_count", t_iframe_desc_count, max_desc)?;
+        let r_iframe_entries_count = check_count("r_iframe_entries_count", r_iframe_entries_count, max_entries)?;
+        let r_iframe_desc_count = check_count("r_iframe_desc_count", r_iframe_desc_count, max_desc)?;
+        let s_iframe_entries_count = check_count("s_iframe_entries_count", s_iframe_entries_count, max_entries)?;
+        let s_iframe_desc_count = check_count("s_iframe_desc_count", s_iframe_desc_count, max_desc)?;
+
         let mut name = String::new();
         if name_len != 0 {
             let buf = archive.read_vec(name_len as usize)?;
@@ -357,9 +912,36 @@ impl Animation {
         })
     }
 
+    #[cfg(feature = "std")]
     /// Reads an `Animation` from an `Archive`.
     pub fn from_archive(archive: &mut Archive<impl Read>) -> Result<Animation, OzzError> {
         let meta = Animation::read_meta(archive)?;
+
+        // Checks the content size implied by the counts before allocating it all at once.
+        let ratio_size = if meta.timepoints_count as usize <= (u8::MAX as usize) {
+            1
+        } else {
+            2
+        };
+        let key_size = ratio_size + mem::size_of::<u16>() + mem::size_of::<Float3Key>();
+        let channel_size = |keys: u32, entries: u32, desc: u32| {
+            keys as u64 * key_size as u64 + entries as u64 + desc as u64 * 4 + mem::size_of::<f32>() as u64
+        };
+        archive.check_remaining(
+            meta.timepoints_count as u64 * 4
+                + channel_size(
+                    meta.translations_count,
+                    meta.t_iframe_entries_count,
+                    meta.t_iframe_desc_count,
+                )
+                + channel_size(
+                    meta.rotations_count,
+                    meta.r_iframe_entries_count,
+                    meta.r_iframe_desc_count,
+                )
+                + channel_size(meta.scales_count, meta.s_iframe_entries_count, meta.s_iframe_desc_count),
+        )?;
+
         let mut animation = Animation::new(meta);
 
         archive.read_slice(animation.timepoints_mut())?;
@@ -407,7 +989,7 @@ impl Animation {
     }
 
     /// Reads an `Animation` from a file path.
-    #[cfg(not(feature = "wasm"))]
+    #[cfg(all(feature = "std", not(feature = "wasm")))]
     pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Animation, OzzError> {
         let mut archive = Archive::from_path(path)?;
         Animation::from_archive(&mut archive)
@@ -463,6 +1045,7 @@ impl Animation {
         animation
     }
 
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
     pub(crate) fn to_raw(&self) -> AnimationRaw {
         AnimationRaw {
             duration: self.duration,
@@ -499,37 +1082,37 @@ impl Animation {
             duration: meta.duration,
             num_tracks: meta.num_tracks,
             name: meta.name,
-            timepoints: std::ptr::null_mut(),
+            timepoints: core::ptr::null_mut(),
             timepoints_count: meta.timepoints_count,
 
-            translations: std::ptr::null_mut(),
+            translations: core::ptr::null_mut(),
             translations_count: meta.translations_count,
-            t_ratios: std::ptr::null_mut(),
-            t_previouses: std::ptr::null_mut(),
+            t_ratios: core::ptr::null_mut(),
+            t_previouses: core::ptr::null_mut(),
             t_iframe_interval: 0.0,
-            t_iframe_entries: std::ptr::null_mut(),
+            t_iframe_entries: core::ptr::null_mut(),
             t_iframe_entries_count: meta.t_iframe_entries_count,
-            t_iframe_desc: std::ptr::null_mut(),
+            t_iframe_desc: core::ptr::null_mut(),
             t_iframe_desc_count: meta.t_iframe_desc_count,
 
-            rotations: std::ptr::null_mut(),
+            rotations: core::ptr::null_mut(),
             rotations_count: meta.rotations_count,
-            r_ratios: std::ptr::null_mut(),
-            r_previouses: std::ptr::null_mut(),
+            r_ratios: core::ptr::null_mut(),
+            r_previouses: core::ptr::null_mut(),
             r_iframe_interval: 0.0,
-            r_iframe_entries: std::ptr::null_mut(),
+            r_iframe_entries: core::ptr::null_mut(),
             r_iframe_entries_count: meta.r_iframe_entries_count,
-            r_iframe_desc: std::ptr::null_mut(),
+            r_iframe_desc: core::ptr::null_mut(),
             r_iframe_desc_count: meta.r_iframe_desc_count,
 
-            scales: std::ptr::null_mut(),
+            scales: core::ptr::null_mut(),
             scales_count: meta.scales_count,
-            s_ratios: std::ptr::null_mut(),
-            s_previouses: std::ptr::null_mut(),
+            s_ratios: core::ptr::null_mut(),
+            s_previouses: core::ptr::null_mut(),
             s_iframe_interval: 0.0,
-            s_iframe_entries: std::ptr::null_mut(),
+            s_iframe_entries: core::ptr::null_mut(),
             s_iframe_entries_count: meta.s_iframe_entries_count,
-            s_iframe_desc: std::ptr::null_mut(),
+            s_iframe_desc: core::ptr::null_mut(),
             s_iframe_desc_count: meta.s_iframe_desc_count,
         };
 
@@ -606,77 +1189,961 @@ impl Animation {
     }
 }
 
-/// Animation keyframes control structure.
-#[derive(Debug, Default)]
-pub struct KeyframesCtrl<'t> {
-    pub ratios: &'t [u16],
-    pub previouses: &'t [u16],
-    pub iframe_entries: &'t [u8],
-    pub iframe_desc: &'t [u32],
-    pub iframe_interval: f32,
-}
-
+#[cfg(feature = "std")]
 impl Animation {
-    /// Gets the animation clip duration.
-    #[inline]
-    pub fn duration(&self) -> f32 {
-        self.duration
+    /// Validates that `Animation` can be sampled against the given `Skeleton`.
+    ///
+    /// Checks that the number of tracks matches the number of joints, and that every keyframe references
+    /// an existing time point and a previous keyframe of the same track. Keyframes are not decompressed,
+    /// so this is cheap enough to be used as a guard before binding into a `SamplingJob`.
+    pub fn validate_against(&self, skeleton: &Skeleton) -> Result<(), OzzError> {
+        if self.num_tracks() != skeleton.num_joints() {
+            return Err(OzzError::TrackMismatch {
+                animation: self.num_tracks(),
+                skeleton: skeleton.num_joints(),
+            });
+        }
+        self.validate_keyframes(&self.translations_ctrl())?;
+        self.validate_keyframes(&self.rotations_ctrl())?;
+        self.validate_keyframes(&self.scales_ctrl())?;
+        Ok(())
     }
 
-    /// Gets the number of animated tracks.
-    #[inline]
-    pub fn num_tracks(&self) -> usize {
-        self.num_tracks as usize
+    /// Remaps `Animation` tracks from `source` skeleton joints to `target` skeleton joints, matched by name.
+    ///
+    /// Tracks must match `source` joints. The returned animation has a track for each `target` joint, holding
+    /// the keys of the `source` joint with the same name. Keys are copied as is, without any re-quantization.
+    /// Target joints without a matching source joint are left at their rest pose.
+    pub fn remap_to(&self, source: &Skeleton, target: &Skeleton) -> Result<Animation, OzzError> {
+        self.remap(source, target, false)
     }
 
-    /// Gets the number of animated tracks (aligned to 4 * SoA).
-    #[inline]
-    pub fn num_aligned_tracks(&self) -> usize {
-        ((self.num_tracks as usize) + 3) & !0x3
+    /// Same as `remap_to()`, but fails with `OzzError::UnmatchedJoints` listing the names of the target joints
+    /// without a matching source joint.
+    pub fn remap_to_strict(&self, source: &Skeleton, target: &Skeleton) -> Result<Animation, OzzError> {
+        self.remap(source, target, true)
     }
 
-    /// Gets the number of SoA elements matching the number of tracks of `Animation`.
-    /// This value is useful to allocate SoA runtime data structures.
-    #[inline]
-    pub fn num_soa_tracks(&self) -> usize {
-        (self.num_tracks as usize).div_ceil(4)
-    }
+    fn remap(&self, source: &Skeleton, target: &Skeleton, strict: bool) -> Result<Animation, OzzError> {
+        if self.num_tracks() != source.num_joints() {
+            return Err(OzzError::TrackMismatch {
+                animation: self.num_tracks(),
+                skeleton: source.num_joints(),
+            });
+        }
 
-    /// Gets animation name.
-    #[inline]
-    pub fn name(&self) -> &str {
-        &self.name
-    }
+        let mut tracks: Vec<Option<usize>> = Vec::with_capacity(target.num_joints());
+        let mut unmatched = Vec::new();
+        for joint in 0..target.num_joints() {
+            let name = target.name_by_joint(joint as i16).unwrap_or_default();
+            let track = source.joint_by_name(name).map(|track| track as usize);
+            if track.is_none() {
+                unmatched.push(String::from(name));
+            }
+            tracks.push(track);
+        }
+        if strict && !unmatched.is_empty() {
+            return Err(OzzError::UnmatchedJoints(unmatched));
+        }
 
-    /// Gets the buffer of time points.
-    #[inline]
-    pub fn timepoints(&self) -> &[f32] {
-        unsafe { slice::from_raw_parts(self.timepoints, self.timepoints_count as usize) }
+        let mut builder = AnimationBuilder::new(self.duration(), target.num_joints());
+        builder.set_name(self.name());
+        for (joint, track) in tracks.into_iter().enumerate() {
+            let Some(track) = track else {
+                let rest_pose = &target.joint_rest_poses()[joint / 4];
+                for ratio in [0.0, 1.0] {
+                    builder.push_translation(
+                        joint,
+                        ratio,
+                        Float3KeyF32::from(rest_pose.translation.vec3(joint & 3)),
+                    )?;
+                    builder.push_rotation(joint, ratio, QuaternionKeyF32::from(rest_pose.rotation.quat(joint & 3)))?;
+                    builder.push_scale(joint, ratio, Float3KeyF32::from(rest_pose.scale.vec3(joint & 3)))?;
+                }
+                continue;
+            };
+
+            for (ratio, key) in self.track_keyframes(self.translations(), &self.translations_ctrl(), track) {
+                builder.push_translation(joint, ratio, key)?;
+            }
+            for (ratio, key) in self.track_keyframes(self.rotations(), &self.rotations_ctrl(), track) {
+                builder.push_rotation(joint, ratio, key)?;
+            }
+            for (ratio, key) in self.track_keyframes(self.scales(), &self.scales_ctrl(), track) {
+                builder.push_scale(joint, ratio, key)?;
+            }
+        }
+        builder.build()
     }
 
-    #[inline]
-    fn timepoints_mut(&mut self) -> &mut [f32] {
-        unsafe { slice::from_raw_parts_mut(self.timepoints, self.timepoints_count as usize) }
+    fn validate_keyframes(&self, ctrl: &KeyframesCtrl<'_>) -> Result<(), OzzError> {
+        if self.num_tracks == 0 {
+            return Ok(());
+        }
+
+        // The first 2 keyframes of each track are stored in track order.
+        let num_aligned_tracks = self.num_aligned_tracks();
+        if ctrl.ratios.len() < num_aligned_tracks * 2 {
+            return Err(OzzError::InvalidIndex);
+        }
+
+        let timepoints_count = self.timepoints_count as usize;
+        for (idx, (&ratio, &previous)) in ctrl.ratios.iter().zip(ctrl.previouses.iter()).enumerate() {
+            if ratio as usize >= timepoints_count {
+                return Err(OzzError::InvalidIndex);
+            }
+            if idx >= num_aligned_tracks && (previous == 0 || previous as usize > idx) {
+                return Err(OzzError::InvalidIndex);
+            }
+        }
+        Ok(())
     }
+}
 
-    /// Gets the buffer of translation keys.
-    #[inline]
-    pub fn translations(&self) -> &[Float3Key] {
-        unsafe { slice::from_raw_parts(self.translations, self.translations_count as usize) }
+impl Animation {
+    /// Gets the two translation keys bracketing `ratio` for `track`, and the interpolation coefficient between them.
+    ///
+    /// `ratio` is clamped in range 0.0-1.0, so the first two keys are returned before the beginning of the track,
+    /// and the last two keys after its end. Keys are not decompressed.
+    pub fn bracket_translation(&self, track: usize, ratio: f32) -> Result<(&Float3Key, &Float3Key, f32), OzzError> {
+        self.bracket(self.translations(), &self.translations_ctrl(), track, ratio)
     }
 
-    #[inline]
-    fn translations_mut(&mut self) -> &mut [Float3Key] {
-        unsafe { slice::from_raw_parts_mut(self.translations, self.translations_count as usize) }
+    /// Gets the two rotation keys bracketing `ratio` for `track`, and the interpolation coefficient between them.
+    ///
+    /// See `bracket_translation()` for details.
+    pub fn bracket_rotation(
+        &self,
+        track: usize,
+        ratio: f32,
+    ) -> Result<(&QuaternionKey, &QuaternionKey, f32), OzzError> {
+        self.bracket(self.rotations(), &self.rotations_ctrl(), track, ratio)
     }
 
-    #[inline]
-    fn t_ratios(&self) -> &[u16] {
-        unsafe { slice::from_raw_parts(self.t_ratios, self.translations_count as usize) }
+    /// Gets the two scale keys bracketing `ratio` for `track`, and the interpolation coefficient between them.
+    ///
+    /// See `bracket_translation()` for details.
+    pub fn bracket_scale(&self, track: usize, ratio: f32) -> Result<(&Float3Key, &Float3Key, f32), OzzError> {
+        self.bracket(self.scales(), &self.scales_ctrl(), track, ratio)
     }
 
-    #[inline]
-    fn t_ratios_mut(&mut self) -> &mut [u16] {
+    fn bracket<'t, K>(
+        &self,
+        keys: &'t [K],
+        ctrl: &KeyframesCtrl<'_>,
+        track: usize,
+        ratio: f32,
+    ) -> Result<(&'t K, &'t K, f32), OzzError> {
+        if track >= self.num_tracks() {
+            return Err(OzzError::InvalidIndex);
+        }
+        if ctrl.ratios.len() < self.num_aligned_tracks() * 2 {
+            return Err(OzzError::InvalidIndex);
+        }
+
+        // Walks the track keys only, until the second key of the pair is at or after ratio.
+        let timepoints = self.timepoints();
+        let time = |key: usize| timepoints[ctrl.ratios[key] as usize];
+        let ratio = ratio.clamp(0.0, 1.0);
+        let mut indices = self.track_key_indices(ctrl.ratios.len(), ctrl.previouses, track);
+        let (mut k0, mut k1) = match (indices.next(), indices.next()) {
+            (Some(k0), Some(k1)) => (k0, k1),
+            _ => return Err(OzzError::InvalidIndex),
+        };
+        while time(k1) < ratio {
+            let Some(next) = indices.next() else {
+                break;
+            };
+            (k0, k1) = (k1, next);
+        }
+
+        let (t0, t1) = (time(k0), time(k1));
+        let coeff = if t1 > t0 {
+            ((ratio - t0) / (t1 - t0)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        Ok((&keys[k0], &keys[k1], coeff))
+    }
+
+    /// Iterates over the translation keys of `track`, in ratio order.
+    ///
+    /// Yields nothing if `track` is out of range. Keys are not decompressed.
+    pub fn track_translations(&self, track: usize) -> impl Iterator<Item = &Float3Key> {
+        self.track_keys(self.translations(), self.translations_ctrl().previouses, track)
+    }
+
+    /// Iterates over the rotation keys of `track`, in ratio order.
+    ///
+    /// See `track_translations()` for details.
+    pub fn track_rotations(&self, track: usize) -> impl Iterator<Item = &QuaternionKey> {
+        self.track_keys(self.rotations(), self.rotations_ctrl().previouses, track)
+    }
+
+    /// Iterates over the scale keys of `track`, in ratio order.
+    ///
+    /// See `track_translations()` for details.
+    pub fn track_scales(&self, track: usize) -> impl Iterator<Item = &Float3Key> {
+        self.track_keys(self.scales(), self.scales_ctrl().previouses, track)
+    }
+
+    /// Counts the keys of each track, as `(track, translations, rotations, scales)`.
+    ///
+    /// Helps finding over-sampled tracks. Keys of the padding tracks (up to `num_aligned_tracks()`) aren't
+    /// reported.
+    pub fn track_key_counts(&self) -> Vec<(usize, usize, usize, usize)> {
+        let translations = self.key_counts(self.translations_ctrl().previouses);
+        let rotations = self.key_counts(self.rotations_ctrl().previouses);
+        let scales = self.key_counts(self.scales_ctrl().previouses);
+        (0..self.num_tracks())
+            .map(|track| (track, translations[track], rotations[track], scales[track]))
+            .collect()
+    }
+
+    /// Counts rotation keys by the index of their largest component (see `QuaternionKey::largest()`).
+    ///
+    /// Helps debugging quaternion compression: a clip whose keys mostly use a largest component other than w
+    /// may have tracks that should have been pre-rotated. Keys of the padding tracks are counted too, so the
+    /// histogram sums to `rotations().len()`.
+    pub fn rotation_largest_histogram(&self) -> [usize; 4] {
+        let mut histogram = [0; 4];
+        for key in self.rotations() {
+            histogram[key.largest()] += 1;
+        }
+        histogram
+    }
+
+    /// Decompresses every key, and checks that the results are finite.
+    ///
+    /// A one-time integrity check for untrusted assets, as non-finite values would silently flow through
+    /// sampling. Returns `OzzError::InvalidKey` for the first failing key, with `field` telling the kind of key
+    /// (`translation_key`, `rotation_key` or `scale_key`), `track` the track it belongs to, and `key` its index in
+    /// `translations()`, `rotations()` or `scales()`.
+    pub fn validate_keys(&self) -> Result<(), OzzError> {
+        fn check<K>(
+            animation: &Animation,
+            field: &'static str,
+            keys: &[K],
+            previouses: &[u16],
+            is_finite: impl Fn(&K) -> bool,
+        ) -> Result<(), OzzError> {
+            match keys.iter().position(|key| !is_finite(key)) {
+                Some(key) => Err(OzzError::InvalidKey {
+                    field,
+                    track: animation.key_tracks(previouses)[key],
+                    key,
+                }),
+                None => Ok(()),
+            }
+        }
+        check(
+            self,
+            "translation_key",
+            self.translations(),
+            self.translations_ctrl().previouses,
+            |key| key.decompress().is_finite(),
+        )?;
+        check(
+            self,
+            "rotation_key",
+            self.rotations(),
+            self.rotations_ctrl().previouses,
+            |key| key.decompress().is_finite(),
+        )?;
+        check(self, "scale_key", self.scales(), self.scales_ctrl().previouses, |key| {
+            key.decompress().is_finite()
+        })?;
+        Ok(())
+    }
+
+    // Tallies keys by track in a single pass, a key belongs to the track of its previous key.
+    fn key_counts(&self, previouses: &[u16]) -> Vec<usize> {
+        let mut counts = ::alloc::vec![0; self.num_aligned_tracks()];
+        for track in self.key_tracks(previouses) {
+            counts[track] += 1;
+        }
+        counts
+    }
+
+    // Track of each key, in storage order.
+    fn key_tracks(&self, previouses: &[u16]) -> Vec<usize> {
+        let num_aligned_tracks = self.num_aligned_tracks();
+        let mut tracks = Vec::with_capacity(previouses.len());
+        for (idx, previous) in previouses.iter().enumerate() {
+            let track = if idx < num_aligned_tracks * 2 {
+                idx % num_aligned_tracks
+            } else {
+                tracks[idx - *previous as usize]
+            };
+            tracks.push(track);
+        }
+        tracks
+    }
+
+    // Follows the keys of a track: the first two keys are stored at `track` and `num_aligned_tracks + track`,
+    // then each key is stored after its previous key, and references it through `previouses`.
+    fn track_keys<'t, K>(
+        &self,
+        keys: &'t [K],
+        previouses: &'t [u16],
+        track: usize,
+    ) -> impl Iterator<Item = &'t K> + 't {
+        self.track_key_indices(keys.len(), previouses, track)
+            .map(move |idx| &keys[idx])
+    }
+
+    // Indices of the keys of a track, see `track_keys()`.
+    fn track_key_indices<'t>(
+        &self,
+        num_keys: usize,
+        previouses: &'t [u16],
+        track: usize,
+    ) -> impl Iterator<Item = usize> + 't {
+        let num_aligned_tracks = self.num_aligned_tracks();
+        let valid = track < self.num_tracks() && num_keys >= num_aligned_tracks * 2;
+        let mut last: Option<usize> = None;
+        core::iter::from_fn(move || {
+            if !valid {
+                return None;
+            }
+            let idx = match last {
+                None => track,
+                Some(last) if last == track => num_aligned_tracks + track,
+                Some(last) => (usize::max(last + 1, num_aligned_tracks * 2)..num_keys)
+                    .find(|&idx| previouses[idx] as usize == idx - last)?,
+            };
+            last = Some(idx);
+            Some(idx)
+        })
+    }
+
+    // Keys of a track with their ratios, in ratio order.
+    fn track_keyframes<K: Copy>(&self, keys: &[K], ctrl: &KeyframesCtrl<'_>, track: usize) -> Vec<(f32, K)> {
+        let timepoints = self.timepoints();
+        self.track_key_indices(keys.len(), ctrl.previouses, track)
+            .map(|idx| (timepoints[ctrl.ratios[idx] as usize], keys[idx]))
+            .collect()
+    }
+
+    /// Flips rotation keys so that successive keys of each track are in the same hemisphere.
+    ///
+    /// Keys are walked in ratio order, a key is negated if its dot product with the previous key is negative,
+    /// so that interpolation takes the shortest path. Negating a key is lossless. Returns the number of flipped
+    /// keys.
+    pub fn enforce_rotation_continuity(&mut self) -> usize {
+        let previouses = self.rotations_ctrl().previouses.to_vec();
+        let mut flipped = 0;
+        for track in 0..self.num_tracks() {
+            let indices: Vec<usize> = self.track_key_indices(previouses.len(), &previouses, track).collect();
+            let rotations = self.rotations_mut();
+            for pair in indices.windows(2) {
+                let (prev, key) = (rotations[pair[0]], rotations[pair[1]]);
+                if prev.decompress().dot(key.decompress()) < 0.0 {
+                    rotations[pair[1]] = key.negated();
+                    flipped += 1;
+                }
+            }
+        }
+        flipped
+    }
+
+    /// Builds a copy of `Animation` without redundant keys.
+    ///
+    /// A key is removed if its quantized value equals both its previous and next keys values on the same track,
+    /// as linear interpolation between the remaining keys yields the same value. First and last keys of each
+    /// track are always kept. Keys are copied as is, without any re-quantization.
+    pub fn dedup_consecutive(&self) -> Result<Animation, OzzError> {
+        fn dedup<K: Copy>(keyframes: Vec<(f32, K)>, eq: impl Fn(&K, &K) -> bool) -> Vec<(f32, K)> {
+            let last = keyframes.len().saturating_sub(1);
+            (0..keyframes.len())
+                .filter(|&idx| {
+                    idx == 0
+                        || idx == last
+                        || !(eq(&keyframes[idx].1, &keyframes[idx - 1].1)
+                            && eq(&keyframes[idx].1, &keyframes[idx + 1].1))
+                })
+                .map(|idx| keyframes[idx])
+                .collect()
+        }
+
+        let mut builder = AnimationBuilder::new(self.duration(), self.num_tracks());
+        builder.set_name(self.name());
+        for track in 0..self.num_tracks() {
+            let keyframes = self.track_keyframes(self.translations(), &self.translations_ctrl(), track);
+            for (ratio, key) in dedup(keyframes, Float3Key::value_eq) {
+                builder.push_translation(track, ratio, key)?;
+            }
+            let keyframes = self.track_keyframes(self.rotations(), &self.rotations_ctrl(), track);
+            for (ratio, key) in dedup(keyframes, QuaternionKey::value_eq) {
+                builder.push_rotation(track, ratio, key)?;
+            }
+            let keyframes = self.track_keyframes(self.scales(), &self.scales_ctrl(), track);
+            for (ratio, key) in dedup(keyframes, Float3Key::value_eq) {
+                builder.push_scale(track, ratio, key)?;
+            }
+        }
+        builder.build()
+    }
+
+    /// Encodes rotation keys of `track` as deltas from the previous key, see `DeltaRotationTrack`.
+    ///
+    /// Returns `OzzError::InvalidIndex` if `track` is out of range.
+    pub fn encode_rotation_deltas(&self, track: usize) -> Result<DeltaRotationTrack, OzzError> {
+        if track >= self.num_tracks() {
+            return Err(OzzError::InvalidIndex);
+        }
+        let keyframes = self.track_keyframes(self.rotations(), &self.rotations_ctrl(), track);
+        Ok(DeltaRotationTrack::encode(&keyframes))
+    }
+
+    /// Builds a copy of `Animation`, with rotations of some tracks decoded from `DeltaRotationTrack`s.
+    ///
+    /// `deltas` are `(track, encoded keys)` pairs, other tracks keep their keys as is. Decoded keys are
+    /// re-quantized. Returns `OzzError::InvalidIndex` if a track is out of range, and `OzzError::InvalidJob` if
+    /// encoded keys don't start at ratio 0 and end at ratio 1.
+    pub fn with_rotation_deltas(&self, deltas: &[(usize, DeltaRotationTrack)]) -> Result<Animation, OzzError> {
+        if deltas.iter().any(|(track, _)| *track >= self.num_tracks()) {
+            return Err(OzzError::InvalidIndex);
+        }
+
+        let mut builder = AnimationBuilder::new(self.duration(), self.num_tracks());
+        builder.set_name(self.name());
+        for track in 0..self.num_tracks() {
+            for (ratio, key) in self.track_keyframes(self.translations(), &self.translations_ctrl(), track) {
+                builder.push_translation(track, ratio, key)?;
+            }
+            let keyframes = match deltas.iter().rev().find(|(idx, _)| *idx == track) {
+                Some((_, encoded)) => encoded.decode(),
+                None => self.track_keyframes(self.rotations(), &self.rotations_ctrl(), track),
+            };
+            for (ratio, key) in keyframes {
+                builder.push_rotation(track, ratio, key)?;
+            }
+            for (ratio, key) in self.track_keyframes(self.scales(), &self.scales_ctrl(), track) {
+                builder.push_scale(track, ratio, key)?;
+            }
+        }
+        builder.build()
+    }
+
+    /// Builds a copy of `Animation` without the keys that interpolation can rebuild within tolerances.
+    ///
+    /// Per track, interior keys are removed if their value is predicted by the linear interpolation of the kept
+    /// neighbor keys within the tolerance, using a Ramer-Douglas-Peucker like decimation as ozz offline tools do.
+    /// Translation and scale errors are distances, rotation error is an angle in radians. First and last keys
+    /// of each track are always kept. Keys are copied as is, without any re-quantization. Returns
+    /// `OzzError::InvalidJob` if any tolerance is negative or NaN.
+    pub fn decimate(&self, translation_eps: f32, rotation_eps: f32, scale_eps: f32) -> Result<Animation, OzzError> {
+        fn decimate<K: Copy>(
+            keyframes: Vec<(f32, K)>,
+            error: impl Fn(&K, &K, &K, f32) -> f32,
+            eps: f32,
+        ) -> Vec<(f32, K)> {
+            if keyframes.len() < 3 {
+                return keyframes;
+            }
+            let mut kept = ::alloc::vec![false; keyframes.len()];
+            let mut segments = ::alloc::vec![(0, keyframes.len() - 1)];
+            while let Some((left, right)) = segments.pop() {
+                kept[left] = true;
+                kept[right] = true;
+                let (r0, k0) = keyframes[left];
+                let (r1, k1) = keyframes[right];
+                let mut worst: Option<(usize, f32)> = None;
+                for (idx, (ratio, key)) in keyframes.iter().enumerate().take(right).skip(left + 1) {
+                    let err = error(&k0, &k1, key, (ratio - r0) / (r1 - r0));
+                    if err > eps && worst.map_or(true, |(_, worst)| err > worst) {
+                        worst = Some((idx, err));
+                    }
+                }
+                if let Some((idx, _)) = worst {
+                    segments.push((left, idx));
+                    segments.push((idx, right));
+                }
+            }
+            keyframes
+                .into_iter()
+                .zip(kept)
+                .filter(|(_, kept)| *kept)
+                .map(|(keyframe, _)| keyframe)
+                .collect()
+        }
+
+        fn vec3_error(k0: &Float3Key, k1: &Float3Key, key: &Float3Key, alpha: f32) -> f32 {
+            k0.decompress().lerp(k1.decompress(), alpha).distance(key.decompress())
+        }
+
+        // Predicted the way sampling does, without taking the shortest path.
+        fn quat_error(k0: &QuaternionKey, k1: &QuaternionKey, key: &QuaternionKey, alpha: f32) -> f32 {
+            let lerp = Vec4::from(k0.decompress()).lerp(Vec4::from(k1.decompress()), alpha);
+            Quat::from_vec4(lerp.normalize()).angle_between(key.decompress())
+        }
+
+        if ![translation_eps, rotation_eps, scale_eps].iter().all(|eps| *eps >= 0.0) {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let mut builder = AnimationBuilder::new(self.duration(), self.num_tracks());
+        builder.set_name(self.name());
+        for track in 0..self.num_tracks() {
+            let keyframes = self.track_keyframes(self.translations(), &self.translations_ctrl(), track);
+            for (ratio, key) in decimate(keyframes, vec3_error, translation_eps) {
+                builder.push_translation(track, ratio, key)?;
+            }
+            let keyframes = self.track_keyframes(self.rotations(), &self.rotations_ctrl(), track);
+            for (ratio, key) in decimate(keyframes, quat_error, rotation_eps) {
+                builder.push_rotation(track, ratio, key)?;
+            }
+            let keyframes = self.track_keyframes(self.scales(), &self.scales_ctrl(), track);
+            for (ratio, key) in decimate(keyframes, vec3_error, scale_eps) {
+                builder.push_scale(track, ratio, key)?;
+            }
+        }
+        builder.build()
+    }
+
+    /// Builds a sub-clip of `Animation`, from `start_ratio` to `end_ratio`.
+    ///
+    /// Ratios are clamped in range 0.0-1.0, `end_ratio` must be greater than `start_ratio`, otherwise
+    /// `OzzError::InvalidJob` is returned. Keys inside the window are copied as is, and their ratios rescaled
+    /// to 0.0-1.0. Keys at the new boundaries are interpolated like `SamplingJob` does, so they're re-quantized,
+    /// unless a key already lies exactly at the boundary. Duration is the windowed length.
+    pub fn slice(&self, start_ratio: f32, end_ratio: f32) -> Result<Animation, OzzError> {
+        // Interpolated key at `ratio`, keys are sorted by ratio and bracket the whole animation.
+        fn key_at<K: Copy>(keyframes: &[(f32, K)], ratio: f32, interp: impl Fn(&K, &K, f32) -> K) -> K {
+            let next = keyframes
+                .iter()
+                .position(|(r, _)| *r >= ratio)
+                .unwrap_or(keyframes.len() - 1);
+            let (r1, k1) = keyframes[next];
+            if next == 0 || r1 == ratio {
+                return k1;
+            }
+            let (r0, k0) = keyframes[next - 1];
+            interp(&k0, &k1, (ratio - r0) / (r1 - r0))
+        }
+
+        fn window<K: Copy>(
+            keyframes: Vec<(f32, K)>,
+            start: f32,
+            end: f32,
+            interp: impl Fn(&K, &K, f32) -> K,
+        ) -> Vec<(f32, K)> {
+            let mut windowed = Vec::with_capacity(keyframes.len() + 2);
+            windowed.push((0.0, key_at(&keyframes, start, &interp)));
+            windowed.extend(
+                keyframes
+                    .iter()
+                    .map(|(ratio, key)| ((ratio - start) / (end - start), *key))
+                    .filter(|(ratio, _)| *ratio > 0.0 && *ratio < 1.0),
+            );
+            windowed.push((1.0, key_at(&keyframes, end, &interp)));
+            windowed
+        }
+
+        let lerp =
+            |a: &Float3Key, b: &Float3Key, alpha: f32| Float3Key::from_f32(a.decompress().lerp(b.decompress(), alpha));
+        // Not taking the shortest path, as sampling doesn't.
+        let nlerp = |a: &QuaternionKey, b: &QuaternionKey, alpha: f32| {
+            let lerp = Vec4::from(a.decompress()).lerp(Vec4::from(b.decompress()), alpha);
+            QuaternionKey::from_quat(Quat::from_vec4(lerp).normalize())
+        };
+
+        let start = f32_clamp_or_max(start_ratio, 0.0, 1.0);
+        let end = f32_clamp_or_max(end_ratio, 0.0, 1.0);
+        if end <= start {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let mut builder = AnimationBuilder::new(self.duration() * (end - start), self.num_tracks());
+        builder.set_name(self.name());
+        for track in 0..self.num_tracks() {
+            let keyframes = self.track_keyframes(self.translations(), &self.translations_ctrl(), track);
+            for (ratio, key) in window(keyframes, start, end, lerp) {
+                builder.push_translation(track, ratio, key)?;
+            }
+            let keyframes = self.track_keyframes(self.rotations(), &self.rotations_ctrl(), track);
+            for (ratio, key) in window(keyframes, start, end, nlerp) {
+                builder.push_rotation(track, ratio, key)?;
+            }
+            let keyframes = self.track_keyframes(self.scales(), &self.scales_ctrl(), track);
+            for (ratio, key) in window(keyframes, start, end, lerp) {
+                builder.push_scale(track, ratio, key)?;
+            }
+        }
+        builder.build()
+    }
+
+    /// Builds an `Animation` playing `self`, then `other`.
+    ///
+    /// Both animations must have the same number of tracks, otherwise `OzzError::InvalidJob` is returned. Keys
+    /// are copied as is, and their ratios shifted into the combined timeline.
+    ///
+    /// `other` starts `blend_seconds` after the end of `self`, keys are linearly interpolated in between, which
+    /// crossfades from the last pose of `self` to the first pose of `other`. The duration is the sum of both
+    /// durations and `blend_seconds`. With a zero `blend_seconds`, the first keys of `other` are replaced by the
+    /// last keys of `self` as boundary keys, so there's no pop at the seam. `blend_seconds` must be positive or
+    /// zero.
+    pub fn concat(&self, other: &Animation, blend_seconds: f32) -> Result<Animation, OzzError> {
+        fn append<K: Copy>(first: Vec<(f32, K)>, second: Vec<(f32, K)>, seam: f32, offset: f32) -> Vec<(f32, K)> {
+            let mut keyframes = Vec::with_capacity(first.len() + second.len());
+            keyframes.extend(first.into_iter().map(|(ratio, key)| (ratio * seam, key)));
+            keyframes.extend(
+                second
+                    .into_iter()
+                    .map(|(ratio, key)| {
+                        (
+                            if ratio < 1.0 {
+                                offset + ratio * (1.0 - offset)
+                            } else {
+                                1.0
+                            },
+                            key,
+                        )
+                    })
+                    .filter(|(ratio, _)| *ratio > seam),
+            );
+            keyframes
+        }
+
+        let ok = self.num_tracks() == other.num_tracks() && blend_seconds >= 0.0 && blend_seconds.is_finite();
+        if !ok {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let duration = self.duration() + blend_seconds + other.duration();
+        let seam = self.duration() / duration;
+        let offset = (self.duration() + blend_seconds) / duration;
+        let mut builder = AnimationBuilder::new(duration, self.num_tracks());
+        builder.set_name(self.name());
+        for track in 0..self.num_tracks() {
+            let keyframes = append(
+                self.track_keyframes(self.translations(), &self.translations_ctrl(), track),
+                other.track_keyframes(other.translations(), &other.translations_ctrl(), track),
+                seam,
+                offset,
+            );
+            for (ratio, key) in keyframes {
+                builder.push_translation(track, ratio, key)?;
+            }
+            let keyframes = append(
+                self.track_keyframes(self.rotations(), &self.rotations_ctrl(), track),
+                other.track_keyframes(other.rotations(), &other.rotations_ctrl(), track),
+                seam,
+                offset,
+            );
+            for (ratio, key) in keyframes {
+                builder.push_rotation(track, ratio, key)?;
+            }
+            let keyframes = append(
+                self.track_keyframes(self.scales(), &self.scales_ctrl(), track),
+                other.track_keyframes(other.scales(), &other.scales_ctrl(), track),
+                seam,
+                offset,
+            );
+            for (ratio, key) in keyframes {
+                builder.push_scale(track, ratio, key)?;
+            }
+        }
+        builder.build()
+    }
+
+    /// Builds an additive `Animation`, the difference between `self` and a `reference` pose.
+    ///
+    /// Each key is replaced by its delta to the reference transform of its track: translation minus reference
+    /// translation, inverse reference rotation times rotation, and scale divided by reference scale. That's
+    /// the inverse of what `BlendingJob` additive layers apply, so adding the result to `reference` gives back
+    /// `self`. Keys ratios are unchanged.
+    ///
+    /// `reference` is a SoA pose, like `Skeleton::joint_rest_poses()` or the first sampled frame of `self`.
+    /// It must cover all tracks, otherwise `OzzError::InvalidJob` is returned.
+    pub fn make_additive(&self, reference: &[SoaTransform]) -> Result<Animation, OzzError> {
+        if reference.len() * 4 < self.num_tracks() {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let mut builder = AnimationBuilder::new(self.duration(), self.num_tracks());
+        builder.set_name(self.name());
+        for track in 0..self.num_tracks() {
+            let (soa, lane) = (&reference[track / 4], track % 4);
+            let translation = soa.translation.vec3(lane);
+            let rotation = soa.rotation.quat(lane).conjugate();
+            let scale = soa.scale.vec3(lane);
+
+            let keyframes = self.track_keyframes(self.translations(), &self.translations_ctrl(), track);
+            for (ratio, key) in keyframes {
+                builder.push_translation(track, ratio, Float3Key::from_f32(key.decompress() - translation))?;
+            }
+            let keyframes = self.track_keyframes(self.rotations(), &self.rotations_ctrl(), track);
+            for (ratio, key) in keyframes {
+                let delta = (rotation * key.decompress()).normalize();
+                builder.push_rotation(track, ratio, QuaternionKey::from_quat(delta))?;
+            }
+            let keyframes = self.track_keyframes(self.scales(), &self.scales_ctrl(), track);
+            for (ratio, key) in keyframes {
+                builder.push_scale(track, ratio, Float3Key::from_f32(key.decompress() / scale))?;
+            }
+        }
+        builder.build()
+    }
+
+    // Finds the keys bracketing `ratio` for all (aligned) tracks, and their interpolation coefficients.
+    fn brackets(&self, ctrl: &KeyframesCtrl<'_>, ratio: f32) -> Result<Vec<(usize, usize, f32)>, OzzError> {
+        let num_aligned_tracks = self.num_aligned_tracks();
+        if ctrl.ratios.len() < num_aligned_tracks * 2 {
+            return Err(OzzError::InvalidIndex);
+        }
+
+        let timepoints = self.timepoints();
+        let time = |key: usize| timepoints[ctrl.ratios[key] as usize];
+        let ratio = ratio.clamp(0.0, 1.0);
+
+        // The first 2 keys of each track are stored in track order. The following ones are sorted by the time of
+        // their previous key, so iteration can stop as soon as a previous key is later than ratio.
+        // Keys' tracks are found back following previouses.
+        let mut k0: Vec<usize> = (0..num_aligned_tracks).collect();
+        let mut k1: Vec<usize> = (num_aligned_tracks..num_aligned_tracks * 2).collect();
+        let mut tracks: Vec<usize> = (0..num_aligned_tracks * 2).map(|k| k % num_aligned_tracks).collect();
+        for next in (num_aligned_tracks * 2)..ctrl.ratios.len() {
+            let owner = tracks[next - ctrl.previouses[next] as usize];
+            if time(k1[owner]) >= ratio {
+                break;
+            }
+            tracks.push(owner);
+            k0[owner] = k1[owner];
+            k1[owner] = next;
+        }
+
+        let brackets = k0
+            .into_iter()
+            .zip(k1)
+            .map(|(k0, k1)| {
+                let (t0, t1) = (time(k0), time(k1));
+                let coeff = if t1 > t0 {
+                    ((ratio - t0) / (t1 - t0)).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                (k0, k1, coeff)
+            })
+            .collect();
+        Ok(brackets)
+    }
+
+    /// Measures the pose mismatch between ratio 0 and ratio 1, for looping animations.
+    ///
+    /// Returns the largest translation distance and the largest rotation angle (in radians) between first and
+    /// last keys of all tracks, which would be a visible pop when the animation loops. Tracks always have keys at
+    /// ratio 0 and 1, so keys are compared directly. Scales are ignored.
+    pub fn loop_discontinuity(&self) -> (f32, f32) {
+        fn seam<K: Copy>(keyframes: &[(f32, K)]) -> Option<(K, K)> {
+            Some((keyframes.first()?.1, keyframes.last()?.1))
+        }
+
+        let mut max_translation: f32 = 0.0;
+        let mut max_angle: f32 = 0.0;
+        for track in 0..self.num_tracks() {
+            let keyframes = self.track_keyframes(self.translations(), &self.translations_ctrl(), track);
+            if let Some((first, last)) = seam(&keyframes) {
+                max_translation = max_translation.max(first.decompress().distance(last.decompress()));
+            }
+            let keyframes = self.track_keyframes(self.rotations(), &self.rotations_ctrl(), track);
+            if let Some((first, last)) = seam(&keyframes) {
+                max_angle = max_angle.max(first.decompress().angle_between(last.decompress()));
+            }
+        }
+        (max_translation, max_angle)
+    }
+
+    /// Decompresses rotation of `track` at `ratio`.
+    ///
+    /// Rotation is interpolated from the 2 keys bracketing `ratio`, the same way `SamplingJob` does.
+    pub fn decompress_rotations_at(&self, track: usize, ratio: f32) -> Result<Quat, OzzError> {
+        let (k0, k1, coeff) = self.bracket_rotation(track, ratio)?;
+        let q0 = Vec4::from(k0.decompress());
+        let q1 = Vec4::from(k1.decompress());
+        Ok(Quat::from_vec4(q0.lerp(q1, coeff).normalize()))
+    }
+
+    /// Decompresses rotations of all tracks at `ratio` to `output`.
+    ///
+    /// Rotations are decompressed and interpolated 4 tracks at a time, then converted to `Quat`.
+    /// If `output` is shorter than the number of tracks, then the last tracks are not decompressed.
+    /// If `output` is longer, remaining `Quat` are left unchanged.
+    pub fn decompress_all_rotations(&self, ratio: f32, output: &mut [Quat]) -> Result<(), OzzError> {
+        if self.num_tracks() == 0 {
+            return Ok(());
+        }
+
+        let rotations = self.rotations();
+        let brackets = self.brackets(&self.rotations_ctrl(), ratio)?;
+        let mut soa0 = SoaQuat::default();
+        let mut soa1 = SoaQuat::default();
+        let num_tracks = usize::min(self.num_tracks(), output.len());
+        for (idx, chunk) in output[..num_tracks].chunks_mut(4).enumerate() {
+            let b = &brackets[idx * 4..idx * 4 + 4];
+            let k0: [&QuaternionKey; 4] = core::array::from_fn(|n| &rotations[b[n].0]);
+            let k1: [&QuaternionKey; 4] = core::array::from_fn(|n| &rotations[b[n].1]);
+            QuaternionKey::simd_decompress(k0[0], k0[1], k0[2], k0[3], &mut soa0);
+            QuaternionKey::simd_decompress(k1[0], k1[1], k1[2], k1[3], &mut soa1);
+            let coeff = f32x4::from_array(core::array::from_fn(|n| b[n].2));
+            let soa = soa0.nlerp(&soa1, coeff);
+
+            // padding lanes are ignored
+            for (lane, out) in chunk.iter_mut().enumerate() {
+                *out = soa.quat(lane);
+            }
+        }
+        Ok(())
+    }
+
+    /// Decompresses every translation key to `out`, as `(ratio, track, value)`, for offline conversion.
+    ///
+    /// `out` is cleared first. Keys are in storage order, sorted by the ratio of their previous key then by
+    /// track, so keys of a track are in ratio order. Keys of the padding tracks aren't written. Keys are
+    /// decompressed 4 at a time.
+    pub fn decompress_translation_keys(&self, out: &mut Vec<(f32, u16, Vec3)>) {
+        self.decompress_keys(self.translations(), &self.translations_ctrl(), out, |k| {
+            let mut soa = SoaVec3::default();
+            Float3Key::simd_decompress(k[0], k[1], k[2], k[3], &mut soa);
+            core::array::from_fn(|lane| soa.vec3(lane))
+        });
+    }
+
+    /// Decompresses every rotation key to `out`, see `decompress_translation_keys()`.
+    pub fn decompress_rotation_keys(&self, out: &mut Vec<(f32, u16, Quat)>) {
+        self.decompress_keys(self.rotations(), &self.rotations_ctrl(), out, |k| {
+            let mut soa = SoaQuat::default();
+            QuaternionKey::simd_decompress(k[0], k[1], k[2], k[3], &mut soa);
+            core::array::from_fn(|lane| soa.quat(lane))
+        });
+    }
+
+    /// Decompresses every scale key to `out`, see `decompress_translation_keys()`.
+    pub fn decompress_scale_keys(&self, out: &mut Vec<(f32, u16, Vec3)>) {
+        self.decompress_keys(self.scales(), &self.scales_ctrl(), out, |k| {
+            let mut soa = SoaVec3::default();
+            Float3Key::simd_decompress(k[0], k[1], k[2], k[3], &mut soa);
+            core::array::from_fn(|lane| soa.vec3(lane))
+        });
+    }
+
+    fn decompress_keys<K, V: Copy>(
+        &self,
+        keys: &[K],
+        ctrl: &KeyframesCtrl<'_>,
+        out: &mut Vec<(f32, u16, V)>,
+        decompress4: impl Fn([&K; 4]) -> [V; 4],
+    ) {
+        out.clear();
+        let tracks = self.key_tracks(ctrl.previouses);
+        let timepoints = self.timepoints();
+        for (chunk_idx, chunk) in keys.chunks(4).enumerate() {
+            // The last chunk is completed with its last key.
+            let values = decompress4(core::array::from_fn(|n| &chunk[n.min(chunk.len() - 1)]));
+            for (n, value) in values.into_iter().take(chunk.len()).enumerate() {
+                let idx = chunk_idx * 4 + n;
+                if tracks[idx] < self.num_tracks() {
+                    out.push((timepoints[ctrl.ratios[idx] as usize], tracks[idx] as u16, value));
+                }
+            }
+        }
+    }
+}
+
+/// Animation keyframes control structure.
+#[derive(Debug, Default)]
+pub struct KeyframesCtrl<'t> {
+    pub ratios: &'t [u16],
+    pub previouses: &'t [u16],
+    pub iframe_entries: &'t [u8],
+    pub iframe_desc: &'t [u32],
+    pub iframe_interval: f32,
+}
+
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+impl Animation {
+    /// Gets the animation clip duration.
+    #[inline]
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// Converts a time in seconds to a time ratio, clamped in range 0.0-1.0.
+    ///
+    /// Negative times return 0, times past the duration return 1.
+    #[inline]
+    pub fn ratio_at_time(&self, seconds: f32) -> f32 {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        f32_clamp_or_max(seconds / self.duration, 0.0, 1.0)
+    }
+
+    /// Converts a time in seconds to a time ratio, wrapped in range 0.0-1.0 (excluded) for looping animations.
+    #[inline]
+    pub fn ratio_at_looped_time(&self, seconds: f32) -> f32 {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        let ratio = seconds / self.duration;
+        f32_clamp_or_max(ratio - libm::floorf(ratio), 0.0, 1.0)
+    }
+
+    /// Gets the number of animated tracks.
+    #[inline]
+    pub fn num_tracks(&self) -> usize {
+        self.num_tracks as usize
+    }
+
+    /// Gets the number of animated tracks (aligned to 4 * SoA).
+    #[inline]
+    pub fn num_aligned_tracks(&self) -> usize {
+        ((self.num_tracks as usize) + 3) & !0x3
+    }
+
+    /// Gets the number of SoA elements matching the number of tracks of `Animation`.
+    /// This value is useful to allocate SoA runtime data structures.
+    #[inline]
+    pub fn num_soa_tracks(&self) -> usize {
+        (self.num_tracks as usize).div_ceil(4)
+    }
+
+    /// Gets animation name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the buffer of time points.
+    #[inline]
+    pub fn timepoints(&self) -> &[f32] {
+        unsafe { slice::from_raw_parts(self.timepoints, self.timepoints_count as usize) }
+    }
+
+    #[inline]
+    fn timepoints_mut(&mut self) -> &mut [f32] {
+        unsafe { slice::from_raw_parts_mut(self.timepoints, self.timepoints_count as usize) }
+    }
+
+    /// Gets the buffer of translation keys.
+    #[inline]
+    pub fn translations(&self) -> &[Float3Key] {
+        unsafe { slice::from_raw_parts(self.translations, self.translations_count as usize) }
+    }
+
+    #[inline]
+    fn translations_mut(&mut self) -> &mut [Float3Key] {
+        unsafe { slice::from_raw_parts_mut(self.translations, self.translations_count as usize) }
+    }
+
+    #[inline]
+    fn t_ratios(&self) -> &[u16] {
+        unsafe { slice::from_raw_parts(self.t_ratios, self.translations_count as usize) }
+    }
+
+    #[inline]
+    fn t_ratios_mut(&mut self) -> &mut [u16] {
         unsafe { slice::from_raw_parts_mut(self.t_ratios, self.translations_count as usize) }
     }
 
@@ -1090,9 +2557,56 @@ const _: () = {
 #[allow(clippy::excessive_precision)]
 #[cfg(test)]
 mod tests {
+    use glam::Mat4;
+    use rand::Rng;
     use wasm_bindgen_test::*;
 
     use super::*;
+    use crate::base::DeterministicState;
+    use crate::local_to_model_job::{LocalToModelJob, LocalToModelJobRef};
+    use crate::math::SoaTransform;
+    use crate::sampling_job::{SamplingContext, SamplingJob, SamplingJobRef};
+    use crate::skeleton::{JointHashMap, SkeletonRaw};
+
+    fn sample_pose(animation: &Animation, ratio: f32) -> Vec<SoaTransform> {
+        sample_pose_with(animation, &mut SamplingContext::from_animation(animation), ratio)
+    }
+
+    fn sample_pose_with(animation: &Animation, ctx: &mut SamplingContext, ratio: f32) -> Vec<SoaTransform> {
+        let mut pose = ::alloc::vec![SoaTransform::IDENTITY; animation.num_soa_tracks()];
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(animation);
+        job.set_context(ctx);
+        job.set_output(&mut pose);
+        job.set_ratio(ratio);
+        job.run().unwrap();
+        pose
+    }
+
+    // Pushes the same key at ratio 0 and 1 for each given transformation type of `track`.
+    fn constant_track(
+        builder: &mut AnimationBuilder,
+        track: usize,
+        translation: Option<Vec3>,
+        rotation: Option<Quat>,
+        scale: Option<Vec3>,
+    ) {
+        for ratio in [0.0, 1.0] {
+            if let Some(translation) = translation {
+                builder
+                    .push_translation(track, ratio, Float3KeyF32::from(translation))
+                    .unwrap();
+            }
+            if let Some(rotation) = rotation {
+                builder
+                    .push_rotation(track, ratio, QuaternionKeyF32::from(rotation))
+                    .unwrap();
+            }
+            if let Some(scale) = scale {
+                builder.push_scale(track, ratio, Float3KeyF32::from(scale)).unwrap();
+            }
+        }
+    }
 
     #[test]
     #[wasm_bindgen_test]
@@ -1182,16 +2696,663 @@ mod tests {
 
     #[test]
     #[wasm_bindgen_test]
-    fn test_read_animation() {
-        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+    fn test_simd_decompress_quaternion_bits() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let k: [QuaternionKey; 4] = [(); 4].map(|_| QuaternionKey(rng.gen()));
+            let mut soa = SoaQuat::default();
+            QuaternionKey::simd_decompress(&k[0], &k[1], &k[2], &k[3], &mut soa);
+            for (lane, key) in k.iter().enumerate() {
+                let quat = key.decompress();
+                assert_eq!(soa.x[lane].to_bits(), quat.x.to_bits(), "{:?}", key);
+                assert_eq!(soa.y[lane].to_bits(), quat.y.to_bits(), "{:?}", key);
+                assert_eq!(soa.z[lane].to_bits(), quat.z.to_bits(), "{:?}", key);
+                assert_eq!(soa.w[lane].to_bits(), quat.w.to_bits(), "{:?}", key);
+            }
+        }
+    }
 
-        assert_eq!(animation.duration(), 8.60000038);
-        assert_eq!(animation.num_tracks(), 67);
-        assert_eq!(animation.name(), "crossarms".to_string());
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_quantization_error() {
+        assert_eq!(
+            QuaternionKey::max_component_error(),
+            1.0 / (32767.0 * core::f32::consts::SQRT_2)
+        );
+        assert!(QuaternionKey::quantization_error(Quat::IDENTITY) < 2e-4);
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let quat = Quat::from_xyzw(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            );
+            let err = QuaternionKey::quantization_error(quat);
+            assert!(err < 2e-4, "{:?} error {}", quat, err);
+        }
+    }
 
-        assert_eq!(animation.timepoints().len(), 252);
-        assert_eq!(animation.timepoints().first().unwrap(), &0.0);
-        assert_eq!(animation.timepoints().last().unwrap(), &1.0);
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_pack_bits() {
+        let mut rng = rand::thread_rng();
+        let quats: Vec<Quat> = (0..1000)
+            .map(|_| {
+                Quat::from_xyzw(
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                )
+                .normalize()
+            })
+            .collect();
+        let keys: Vec<QuaternionKey> = quats.iter().map(|q| QuaternionKey::from_quat(*q)).collect();
+
+        // 15 bits is lossless
+        let bytes = QuaternionKey::write_packed(&keys, 15).unwrap();
+        assert_eq!(bytes.len(), (1000 * 48usize).div_ceil(8));
+        assert_eq!(QuaternionKey::read_packed(&bytes, keys.len(), 15).unwrap(), keys);
+
+        let bytes = QuaternionKey::write_packed(&keys, 12).unwrap();
+        assert_eq!(bytes.len(), (1000 * 39usize).div_ceil(8));
+        let unpacked = QuaternionKey::read_packed(&bytes, keys.len(), 12).unwrap();
+        for ((quat, key), unpacked) in quats.iter().zip(keys.iter()).zip(unpacked.iter()) {
+            assert_eq!(
+                QuaternionKey::unpack_bits(key.pack_bits(12).unwrap(), 12).unwrap(),
+                *unpacked
+            );
+            let diff = unpacked.decompress().inverse() * *quat;
+            let err = 2.0 * libm::atan2f(diff.xyz().length(), diff.w.abs());
+            assert!(err < 2e-3, "{:?} error {}", quat, err);
+        }
+
+        assert!(QuaternionKey::read_packed(&bytes, keys.len() + 1, 12)
+            .unwrap_err()
+            .is_invalid_data());
+        assert!(QuaternionKey::read_packed(&bytes, 1, 16).unwrap_err().is_invalid_data());
+        assert!(QuaternionKey::read_packed(&bytes, 1, 7).unwrap_err().is_invalid_data());
+        for bits in [0, 7, 16, 32] {
+            assert!(QuaternionKey::write_packed(&keys, bits).unwrap_err().is_invalid_data());
+            assert!(keys[0].pack_bits(bits).unwrap_err().is_invalid_data());
+            assert!(QuaternionKey::unpack_bits(0, bits).unwrap_err().is_invalid_data());
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_decompress_scalar4() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let k: [Float3Key; 4] = [(); 4].map(|_| Float3Key(rng.gen()));
+            let mut simd = SoaVec3::default();
+            Float3Key::simd_decompress(&k[0], &k[1], &k[2], &k[3], &mut simd);
+            let mut scalar = SoaVec3::default();
+            Float3Key::decompress_scalar4(&k[0], &k[1], &k[2], &k[3], &mut scalar);
+            assert!(lanes_approx_eq(simd.x, scalar.x), "{:?}", k);
+            assert!(lanes_approx_eq(simd.y, scalar.y), "{:?}", k);
+            assert!(lanes_approx_eq(simd.z, scalar.z), "{:?}", k);
+
+            let k: [QuaternionKey; 4] = [(); 4].map(|_| QuaternionKey(rng.gen()));
+            let mut simd = SoaQuat::default();
+            QuaternionKey::simd_decompress(&k[0], &k[1], &k[2], &k[3], &mut simd);
+            let mut scalar = SoaQuat::default();
+            QuaternionKey::decompress_scalar4(&k[0], &k[1], &k[2], &k[3], &mut scalar);
+            assert!(lanes_approx_eq(simd.x, scalar.x), "{:?}", k);
+            assert!(lanes_approx_eq(simd.y, scalar.y), "{:?}", k);
+            assert!(lanes_approx_eq(simd.z, scalar.z), "{:?}", k);
+            assert!(lanes_approx_eq(simd.w, scalar.w), "{:?}", k);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_float3_key_compress() {
+        let key = Float3Key([11405, 34240, 31]);
+        assert_eq!(Float3Key::from_f32(key.decompress()), key);
+
+        let key = Float3KeyF32::new([1.0, -3.5, 0.0]).compress();
+        assert_eq!(key.decompress(), Vec3::new(1.0, -3.5, 0.0));
+
+        let key = Float3KeyF32::from(Vec3::new(0.1, 1000.0, -0.3333)).compress();
+        assert!(key.decompress().abs_diff_eq(Vec3::new(0.1, 1000.0, -0.3333), 0.5));
+        assert!(key.decompress().x == 0.099975586);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_quaternion_key_compress() {
+        let key = QuaternionKey([39974, 18396, 53990]);
+        assert_eq!(QuaternionKey::from_quat(key.decompress()), key);
+
+        let key = QuaternionKeyF32::new([0.0, 0.0, 0.0, 1.0]).compress();
+        assert!(key.decompress().abs_diff_eq(Quat::IDENTITY, 1e-4));
+        let key = QuaternionKeyF32::new([0.0, -1.0, 0.0, 0.0]).compress();
+        assert!(key.decompress().abs_diff_eq(Quat::from_xyzw(0.0, -1.0, 0.0, 0.0), 1e-4));
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let quat = Quat::from_xyzw(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            )
+            .normalize();
+            let res = QuaternionKeyF32::from(quat).compress().decompress();
+            assert!(res.abs_diff_eq(quat, 1e-4), "{:?} != {:?}", res, quat);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validate_against() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        assert!(animation.validate_against(&skeleton).is_ok());
+
+        let skeleton = Skeleton::from_path("./resource/two_bone_ik/skeleton.ozz").unwrap();
+        let err = animation.validate_against(&skeleton).unwrap_err();
+        assert!(err.is_track_mismatch());
+        assert!(matches!(
+            err,
+            OzzError::TrackMismatch {
+                animation: 67,
+                skeleton: 18
+            }
+        ));
+    }
+
+    fn new_rig(names: &[&str], parents: Vec<i16>) -> Skeleton {
+        let mut joint_names = JointHashMap::with_hashers(DeterministicState::new(), DeterministicState::new());
+        let mut joint_rest_poses = vec![SoaTransform::IDENTITY; names.len().div_ceil(4)];
+        for (joint, name) in names.iter().enumerate() {
+            joint_names.insert((*name).into(), joint as i16);
+            // rest pose only depends on the joint name
+            let offset = name.len() as f32;
+            joint_rest_poses[joint / 4]
+                .translation
+                .set_vec3(joint & 3, Vec3::new(offset, 1.0, -offset));
+        }
+        Skeleton::from_raw(&SkeletonRaw {
+            joint_rest_poses,
+            joint_names,
+            joint_parents: parents,
+        })
+    }
+
+    fn model_pose(skeleton: &Skeleton, animation: &Animation, ratio: f32) -> Vec<Mat4> {
+        let mut locals = vec![SoaTransform::IDENTITY; skeleton.num_soa_joints()];
+        let mut context = SamplingContext::from_animation(animation);
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(animation);
+        job.set_context(&mut context);
+        job.set_output(&mut locals);
+        job.set_ratio(ratio);
+        job.run().unwrap();
+
+        let mut models = vec![Mat4::IDENTITY; skeleton.num_joints()];
+        let mut job: LocalToModelJobRef = LocalToModelJob::default();
+        job.set_skeleton(skeleton);
+        job.set_input(&locals);
+        job.set_output(&mut models);
+        job.run().unwrap();
+        models
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_remap_to() {
+        let source = new_rig(
+            &["spine", "left_arm", "right_arm", "left_hand", "right_hand"],
+            vec![-1, 0, 0, 1, 2],
+        );
+        let target = new_rig(
+            &["spine", "right_arm", "right_hand", "left_arm", "left_hand"],
+            vec![-1, 0, 1, 0, 3],
+        );
+
+        let mut rng = rand::thread_rng();
+        let mut builder = AnimationBuilder::new(2.0, source.num_joints());
+        builder.set_name("remap");
+        for track in 0..source.num_joints() {
+            for (idx, ratio) in [0.0, 0.25 * track as f32 / 5.0 + 0.1, 0.6, 1.0].into_iter().enumerate() {
+                let translation = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), track as f32);
+                let rotation = Quat::from_rotation_y(rng.gen_range(-1.0..1.0)) * Quat::from_rotation_x(track as f32);
+                builder
+                    .push_translation(track, ratio, Float3KeyF32::from(translation))
+                    .unwrap();
+                builder
+                    .push_rotation(track, ratio, QuaternionKeyF32::from(rotation))
+                    .unwrap();
+                if idx != 1 {
+                    builder
+                        .push_scale(track, ratio, Float3KeyF32::from(Vec3::splat(1.0 + ratio)))
+                        .unwrap();
+                }
+            }
+        }
+        let animation = builder.build().unwrap();
+
+        let remapped = animation.remap_to(&source, &target).unwrap();
+        assert_eq!(remapped.num_tracks(), target.num_joints());
+        assert_eq!(remapped.duration(), animation.duration());
+        assert_eq!(remapped.name(), animation.name());
+        assert!(remapped.validate_against(&target).is_ok());
+        assert!(animation.remap_to_strict(&source, &target).is_ok());
+
+        for ratio in [0.0, 0.05, 0.2, 0.5, 0.7, 1.0] {
+            let expected = model_pose(&source, &animation, ratio);
+            let actual = model_pose(&target, &remapped, ratio);
+            for (joint, actual) in actual.iter().enumerate() {
+                let name = target.name_by_joint(joint as i16).unwrap();
+                let track = source.joint_by_name(name).unwrap() as usize;
+                assert_eq!(actual, &expected[track], "ratio={} joint={}", ratio, name);
+            }
+        }
+
+        // unmatched joints are left at rest
+        let target = new_rig(&["spine", "tail", "right_arm"], vec![-1, 0, 0]);
+        let remapped = animation.remap_to(&source, &target).unwrap();
+        let expected = model_pose(&source, &animation, 0.3);
+        let actual = model_pose(&target, &remapped, 0.3);
+        assert_eq!(actual[0], expected[0]);
+        assert_eq!(actual[2], expected[2]);
+        let rest = target.joint_rest_poses()[0].translation.vec3(1);
+        assert!(actual[1]
+            .w_axis
+            .truncate()
+            .abs_diff_eq(actual[0].transform_point3(rest), 2e-3));
+
+        let err = animation.remap_to_strict(&source, &target).unwrap_err();
+        assert!(err.is_unmatched_joints());
+        assert!(matches!(err, OzzError::UnmatchedJoints(names) if names == vec!["tail".to_string()]));
+
+        // animation tracks don't match source skeleton
+        let err = animation.remap_to(&target, &source).unwrap_err();
+        assert!(err.is_track_mismatch());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_remap_to_same_skeleton() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let remapped = animation.remap_to_strict(&skeleton, &skeleton).unwrap();
+        for ratio in [0.0, 0.1234, 0.5, 0.9, 1.0] {
+            assert_eq!(
+                model_pose(&skeleton, &remapped, ratio),
+                model_pose(&skeleton, &animation, ratio)
+            );
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_bracket() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let num_aligned_tracks = animation.num_aligned_tracks();
+        let timepoints = animation.timepoints();
+
+        let ctrl = animation.translations_ctrl();
+        let t0 = timepoints[ctrl.ratios[0] as usize];
+        let t1 = timepoints[ctrl.ratios[num_aligned_tracks] as usize];
+        let (k0, k1, coeff) = animation.bracket_translation(0, t0 + (t1 - t0) * 0.25).unwrap();
+        assert_eq!(k0, &animation.translations()[0]);
+        assert_eq!(k1, &animation.translations()[num_aligned_tracks]);
+        assert!((coeff - 0.25).abs() < 1e-5);
+
+        let ctrl = animation.rotations_ctrl();
+        let t0 = timepoints[ctrl.ratios[3] as usize];
+        let t1 = timepoints[ctrl.ratios[num_aligned_tracks + 3] as usize];
+        let (k0, k1, coeff) = animation.bracket_rotation(3, (t0 + t1) * 0.5).unwrap();
+        assert_eq!(k0, &animation.rotations()[3]);
+        assert_eq!(k1, &animation.rotations()[num_aligned_tracks + 3]);
+        assert!((coeff - 0.5).abs() < 1e-5);
+
+        let (k0, k1, coeff) = animation.bracket_scale(5, -1.0).unwrap();
+        assert_eq!(k0, &animation.scales()[5]);
+        assert_eq!(k1, &animation.scales()[num_aligned_tracks + 5]);
+        assert_eq!(coeff, 0.0);
+
+        for track in 0..animation.num_tracks() {
+            let (_, _, coeff) = animation.bracket_rotation(track, 2.0).unwrap();
+            assert_eq!(coeff, 1.0);
+        }
+
+        assert!(animation.bracket_translation(67, 0.5).unwrap_err().is_invalid_index());
+
+        // a single track walk matches all tracks brackets
+        let ctrl = animation.scales_ctrl();
+        for ratio in [0.0, 0.01, 0.3333, 0.5, 0.97, 1.0] {
+            let brackets = animation.brackets(&ctrl, ratio).unwrap();
+            for (track, (k0, k1, coeff)) in brackets.into_iter().enumerate().take(animation.num_tracks()) {
+                let expected = (&animation.scales()[k0], &animation.scales()[k1], coeff);
+                assert_eq!(animation.bracket_scale(track, ratio).unwrap(), expected);
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_decompress_rotations() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let num_tracks = animation.num_tracks();
+        let num_aligned_tracks = animation.num_aligned_tracks();
+        let rotations = animation.rotations();
+
+        let mut output = vec![Quat::NAN; num_tracks + 1];
+        animation.decompress_all_rotations(0.0, &mut output).unwrap();
+        for (track, rotation) in output.iter().enumerate().take(num_tracks) {
+            assert!(rotation.abs_diff_eq(rotations[track].decompress(), 1e-6));
+            let single = animation.decompress_rotations_at(track, 0.0).unwrap();
+            assert!(rotation.abs_diff_eq(single, 1e-6));
+        }
+        assert!(output[num_tracks].is_nan());
+
+        let ctrl = animation.rotations_ctrl();
+        let timepoints = animation.timepoints();
+        let ratio = timepoints[ctrl.ratios[num_aligned_tracks + 7] as usize];
+        animation.decompress_all_rotations(ratio, &mut output).unwrap();
+        let expected = rotations[num_aligned_tracks + 7].decompress();
+        assert!(output[7].abs_diff_eq(expected, 1e-6));
+        assert!(animation
+            .decompress_rotations_at(7, ratio)
+            .unwrap()
+            .abs_diff_eq(expected, 1e-6));
+
+        for ratio in [0.1, 0.33, 0.5, 0.9, 1.0] {
+            animation.decompress_all_rotations(ratio, &mut output).unwrap();
+            for (track, rotation) in output.iter().enumerate().take(num_tracks) {
+                let single = animation.decompress_rotations_at(track, ratio).unwrap();
+                assert!(rotation.abs_diff_eq(single, 1e-6));
+            }
+        }
+
+        let mut output = vec![Quat::NAN; 5];
+        animation.decompress_all_rotations(1.0, &mut output).unwrap();
+        assert!(output.iter().all(|q| q.is_normalized()));
+        assert!(animation
+            .decompress_rotations_at(num_tracks, 0.5)
+            .unwrap_err()
+            .is_invalid_index());
+    }
+
+    fn crafted_header(num_tracks: u32, name_len: u32, translations_count: u32) -> Vec<u8> {
+        let mut bytes = vec![1u8];
+        bytes.extend_from_slice(b"ozz-animation\0");
+        bytes.extend_from_slice(&7u32.to_le_bytes());
+        bytes.extend_from_slice(&1.0f32.to_le_bytes());
+        let counts = [num_tracks, name_len, 10, translations_count, 8, 8, 0, 0, 0, 0, 0, 0];
+        for count in counts {
+            bytes.extend_from_slice(&count.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_read_invalid_data() {
+        let read = |bytes: Vec<u8>| Animation::from_archive(&mut Archive::from_vec(bytes).unwrap());
+
+        let err = read(crafted_header(2, 0, -1i32 as u32)).unwrap_err();
+        assert!(matches!(
+            err,
+            OzzError::InvalidData {
+                field: "translations_count",
+                value: -1
+            }
+        ));
+
+        let err = read(crafted_header(2, 0x7FFF_FFFF, 8)).unwrap_err();
+        assert!(matches!(
+            err,
+            OzzError::InvalidData {
+                field: "name_len",
+                value: 0x7FFF_FFFF
+            }
+        ));
+
+        let err = read(crafted_header(-5i32 as u32, 0, 8)).unwrap_err();
+        assert!(err.is_invalid_data());
+
+        let err = read(crafted_header(2, 0, 1000)).unwrap_err();
+        assert!(err.is_invalid_data());
+
+        // valid header, truncated content
+        let err = read(crafted_header(2, 4, 8)).unwrap_err();
+        assert!(err.is_io());
+
+        // counts within bounds but far beyond the file size, fails before allocating
+        let timepoints_count = u16::MAX as u32 + 1;
+        let max_keys = (SKELETON_MAX_JOINTS as u32).div_ceil(4) * 4 * timepoints_count;
+        let mut bytes = crafted_header(SKELETON_MAX_JOINTS as u32, 0, max_keys);
+        bytes[31..35].copy_from_slice(&timepoints_count.to_le_bytes());
+        bytes[39..47].copy_from_slice(&[max_keys.to_le_bytes(), max_keys.to_le_bytes()].concat());
+        let err = read(bytes.clone()).unwrap_err();
+        assert!(err.is_io());
+        let err = Animation::from_archive(&mut Archive::from_slice(&bytes).unwrap()).unwrap_err();
+        assert!(err.is_io());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_read_animation_from_slice() {
+        let bytes: Vec<u8> = include_bytes!("../resource/playback/animation.ozz").to_vec();
+        let from_slice = Animation::from_archive(&mut Archive::from_slice(&bytes).unwrap()).unwrap();
+        let from_vec = Animation::from_archive(&mut Archive::from_vec(bytes.clone()).unwrap()).unwrap();
+        let from_path = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+
+        for animation in [&from_slice, &from_vec] {
+            assert_eq!(animation.duration(), from_path.duration());
+            assert_eq!(animation.num_tracks(), from_path.num_tracks());
+            assert_eq!(animation.name(), from_path.name());
+            assert_eq!(animation.timepoints(), from_path.timepoints());
+            assert_eq!(animation.translations(), from_path.translations());
+            assert_eq!(animation.rotations(), from_path.rotations());
+            assert_eq!(animation.scales(), from_path.scales());
+            assert_eq!(
+                animation.translations_ctrl().ratios,
+                from_path.translations_ctrl().ratios
+            );
+            assert_eq!(
+                animation.rotations_ctrl().previouses,
+                from_path.rotations_ctrl().previouses
+            );
+            assert_eq!(animation.scales_ctrl().ratios, from_path.scales_ctrl().ratios);
+        }
+    }
+
+    // Writes an ozz v7 little endian archive, in the layout read by `Animation::from_archive()`.
+    fn write_archive(animation: &Animation) -> Vec<u8> {
+        let raw = animation.to_raw();
+        let mut buf = vec![1u8];
+        buf.extend_from_slice(b"ozz-animation\0");
+        buf.extend_from_slice(&Animation::version().to_le_bytes());
+        buf.extend_from_slice(&raw.duration.to_le_bytes());
+        let counts = [
+            raw.num_tracks,
+            raw.name.len() as u32,
+            raw.timepoints.len() as u32,
+            raw.translations.len() as u32,
+            raw.rotations.len() as u32,
+            raw.scales.len() as u32,
+            raw.t_iframe_entries.len() as u32,
+            raw.t_iframe_desc.len() as u32,
+            raw.r_iframe_entries.len() as u32,
+            raw.r_iframe_desc.len() as u32,
+            raw.s_iframe_entries.len() as u32,
+            raw.s_iframe_desc.len() as u32,
+        ];
+        counts.iter().for_each(|c| buf.extend_from_slice(&c.to_le_bytes()));
+        buf.extend_from_slice(raw.name.as_bytes());
+        raw.timepoints
+            .iter()
+            .for_each(|t| buf.extend_from_slice(&t.to_le_bytes()));
+
+        let is_ratio_u8 = raw.timepoints.len() <= (u8::MAX as usize);
+        let channels = [
+            (
+                &raw.t_ratios,
+                &raw.t_previouses,
+                &raw.t_iframe_entries,
+                &raw.t_iframe_desc,
+                raw.t_iframe_interval,
+            ),
+            (
+                &raw.r_ratios,
+                &raw.r_previouses,
+                &raw.r_iframe_entries,
+                &raw.r_iframe_desc,
+                raw.r_iframe_interval,
+            ),
+            (
+                &raw.s_ratios,
+                &raw.s_previouses,
+                &raw.s_iframe_entries,
+                &raw.s_iframe_desc,
+                raw.s_iframe_interval,
+            ),
+        ];
+        let keys: [Vec<[u16; 3]>; 3] = [
+            raw.translations.iter().map(|k| k.0).collect(),
+            raw.rotations.iter().map(|k| k.0).collect(),
+            raw.scales.iter().map(|k| k.0).collect(),
+        ];
+        for ((ratios, previouses, entries, desc, interval), keys) in channels.into_iter().zip(keys) {
+            for ratio in ratios {
+                match is_ratio_u8 {
+                    true => buf.push(*ratio as u8),
+                    false => buf.extend_from_slice(&ratio.to_le_bytes()),
+                }
+            }
+            previouses.iter().for_each(|p| buf.extend_from_slice(&p.to_le_bytes()));
+            buf.extend_from_slice(entries);
+            desc.iter().for_each(|d| buf.extend_from_slice(&d.to_le_bytes()));
+            buf.extend_from_slice(&interval.to_le_bytes());
+            keys.iter()
+                .flatten()
+                .for_each(|v| buf.extend_from_slice(&v.to_le_bytes()));
+        }
+        buf
+    }
+
+    // Builds iframes every `interval` ratio, from the sampling cache state at each iframe.
+    fn with_iframes(animation: &Animation, interval: f32) -> Animation {
+        fn encode_gv4(values: &[u32], buf: &mut Vec<u8>) {
+            for chunk in values.chunks(4) {
+                let chunk = <[u32; 4]>::try_from(chunk).unwrap();
+                let sizes = chunk.map(|v| usize::max(1, (32 - v.leading_zeros() as usize).div_ceil(8)));
+                buf.push(
+                    sizes
+                        .iter()
+                        .enumerate()
+                        .fold(0, |p, (i, s)| p | (((s - 1) << (i * 2)) as u8)),
+                );
+                for (v, s) in chunk.iter().zip(sizes) {
+                    buf.extend_from_slice(&v.to_le_bytes()[..s]);
+                }
+            }
+        }
+
+        let mut raw = animation.to_raw();
+        raw.t_iframe_interval = interval;
+        raw.r_iframe_interval = interval;
+        raw.s_iframe_interval = interval;
+        let num_aligned_tracks = animation.num_aligned_tracks();
+        let mut output = vec![SoaTransform::IDENTITY; animation.num_soa_tracks()];
+        for iframe in 1..=(1.0 / interval) as usize {
+            let mut ctx = SamplingContext::from_animation(animation);
+            let mut job: SamplingJobRef = SamplingJob::default();
+            job.set_animation(animation);
+            job.set_context(&mut ctx);
+            job.set_output(&mut output);
+            job.set_ratio(iframe as f32 * interval);
+            job.run().unwrap();
+
+            for (entries, next, iframe_entries, iframe_desc) in [
+                (
+                    ctx.translation_entries(),
+                    ctx.translation_next(),
+                    &mut raw.t_iframe_entries,
+                    &mut raw.t_iframe_desc,
+                ),
+                (
+                    ctx.rotation_entries(),
+                    ctx.rotation_next(),
+                    &mut raw.r_iframe_entries,
+                    &mut raw.r_iframe_desc,
+                ),
+                (
+                    ctx.scale_entries(),
+                    ctx.scale_next(),
+                    &mut raw.s_iframe_entries,
+                    &mut raw.s_iframe_desc,
+                ),
+            ] {
+                iframe_desc.push(iframe_entries.len() as u32);
+                iframe_desc.push(next as u32 - 1);
+                encode_gv4(&entries[..num_aligned_tracks], iframe_entries);
+            }
+        }
+        // Decoding loads 4 bytes at once.
+        for iframe_entries in [
+            &mut raw.t_iframe_entries,
+            &mut raw.r_iframe_entries,
+            &mut raw.s_iframe_entries,
+        ] {
+            iframe_entries.extend_from_slice(&[0; 3]);
+        }
+        Animation::from_raw(&raw)
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_rewrite_animation() {
+        // Exported by ozz, without iframes.
+        let path = "./resource/playback/animation.ozz";
+        let animation = Animation::from_path(path).unwrap();
+        assert_eq!(write_archive(&animation), std::fs::read(path).unwrap());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_sample_animation_iframes() {
+        // No animation exported by ozz with iframes is available in resource/, so iframes are synthesized here.
+        // It checks that iframe sections are read back and used to initialize sampling, not that the layout
+        // matches ozz exports.
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let iframes = with_iframes(&animation, 0.25);
+        assert_eq!(iframes.translations_ctrl().iframe_desc.len(), 8);
+        let bytes = write_archive(&iframes);
+        let mut archive = Archive::from_vec(bytes.clone()).unwrap();
+        let read = Animation::from_archive(&mut archive).unwrap();
+        assert_eq!(read.to_raw(), iframes.to_raw());
+        assert_eq!(write_archive(&read), bytes);
+
+        // Jumps over more than half an interval are initialized from iframes.
+        let mut ctx1 = SamplingContext::from_animation(&animation);
+        let mut ctx2 = SamplingContext::from_animation(&read);
+        for ratio in [0.0, 0.9, 0.1, 0.6, 0.62, 0.3, 1.0, 0.45] {
+            let expected = sample_pose_with(&animation, &mut ctx1, ratio);
+            assert_eq!(sample_pose_with(&read, &mut ctx2, ratio), expected, "ratio={}", ratio);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_read_animation() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+
+        assert_eq!(animation.duration(), 8.60000038);
+        assert_eq!(animation.num_tracks(), 67);
+        assert_eq!(animation.name(), "crossarms".to_string());
+
+        assert_eq!(animation.timepoints().len(), 252);
+        assert_eq!(animation.timepoints().first().unwrap(), &0.0);
+        assert_eq!(animation.timepoints().last().unwrap(), &1.0);
 
         assert_eq!(animation.translations_ctrl().ratios.len(), 178);
         assert_eq!(animation.translations_ctrl().ratios.first().unwrap(), &0);
@@ -1236,6 +3397,726 @@ mod tests {
         assert_eq!(animation.scales().last().unwrap().0, [15360, 15360, 15360]);
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_track_keys() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let timepoints = animation.timepoints();
+        let num_aligned_tracks = animation.num_aligned_tracks();
+
+        let check_ratios = |indices: Vec<usize>, ratios: &[u16]| {
+            assert_eq!(timepoints[ratios[indices[0]] as usize], 0.0);
+            assert_eq!(timepoints[ratios[*indices.last().unwrap()] as usize], 1.0);
+            for pair in indices.windows(2) {
+                assert!(timepoints[ratios[pair[0]] as usize] < timepoints[ratios[pair[1]] as usize]);
+            }
+        };
+
+        let translations = animation.translations();
+        let indices: Vec<usize> = animation
+            .track_translations(0)
+            .map(|key| {
+                (key as *const Float3Key as usize - translations.as_ptr() as usize) / mem::size_of::<Float3Key>()
+            })
+            .collect();
+        assert_eq!(indices.len(), 29);
+        assert_eq!(&indices[..2], &[0, num_aligned_tracks]);
+        check_ratios(indices, animation.translations_ctrl().ratios);
+
+        let rotations = animation.rotations();
+        let indices: Vec<usize> = animation
+            .track_rotations(0)
+            .map(|key| {
+                (key as *const QuaternionKey as usize - rotations.as_ptr() as usize) / mem::size_of::<QuaternionKey>()
+            })
+            .collect();
+        assert_eq!(indices.len(), 86);
+        assert_eq!(&indices[..2], &[0, num_aligned_tracks]);
+        check_ratios(indices, animation.rotations_ctrl().ratios);
+
+        // Padding tracks have 2 keys.
+        let num_padding_keys = (num_aligned_tracks - animation.num_tracks()) * 2;
+        let count = |f: &dyn Fn(usize) -> usize| (0..animation.num_tracks()).map(f).sum::<usize>() + num_padding_keys;
+        assert_eq!(
+            count(&|track| animation.track_translations(track).count()),
+            translations.len()
+        );
+        assert_eq!(
+            count(&|track| animation.track_rotations(track).count()),
+            rotations.len()
+        );
+        assert_eq!(
+            count(&|track| animation.track_scales(track).count()),
+            animation.scales().len()
+        );
+        assert_eq!(animation.track_translations(animation.num_tracks()).count(), 0);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_track_key_counts() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let counts = animation.track_key_counts();
+        assert_eq!(counts.len(), animation.num_tracks());
+
+        // padding tracks have their first 2 keys only
+        let padding = (animation.num_aligned_tracks() - animation.num_tracks()) * 2;
+        let (mut translations, mut rotations, mut scales) = (padding, padding, padding);
+        for (idx, &(track, t, r, s)) in counts.iter().enumerate() {
+            assert_eq!(track, idx);
+            assert_eq!(t, animation.track_translations(track).count());
+            assert_eq!(r, animation.track_rotations(track).count());
+            assert_eq!(s, animation.track_scales(track).count());
+            translations += t;
+            rotations += r;
+            scales += s;
+        }
+        assert_eq!(translations, animation.translations().len());
+        assert_eq!(rotations, animation.rotations().len());
+        assert_eq!(scales, animation.scales().len());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_rotation_deltas() {
+        let mut builder = AnimationBuilder::new(4.0, 2);
+        for idx in 0..=100 {
+            let ratio = idx as f32 / 100.0;
+            // track 0: slow twist, track 1: fast rotation
+            let twist = Quat::from_rotation_x(0.2 * libm::sinf(ratio * 6.0)) * Quat::from_rotation_y(0.1 * ratio);
+            let fast = Quat::from_rotation_z(6.0 * ratio) * Quat::from_rotation_x(2.0 * ratio);
+            for (track, rotation) in [twist, fast].into_iter().enumerate() {
+                builder
+                    .push_rotation(track, ratio, QuaternionKeyF32::from(rotation))
+                    .unwrap();
+            }
+        }
+        for track in 0..2 {
+            constant_track(&mut builder, track, Some(Vec3::X), None, Some(Vec3::ONE));
+        }
+        let animation = builder.build().unwrap();
+        // `Quat::angle_between()` acos lacks precision for tiny angles.
+        let angle = |a: QuaternionKey, b: QuaternionKey| {
+            let diff = a.decompress().inverse() * b.decompress();
+            2.0 * libm::atan2f(diff.xyz().length(), diff.w.abs())
+        };
+
+        let encoded = animation.encode_rotation_deltas(0).unwrap();
+        assert_eq!(encoded.ratios.len(), 101);
+        assert_eq!(encoded.deltas.len(), 100);
+        assert!(encoded.range < 0.01);
+        let decoded = encoded.decode();
+        let keyframes = animation.track_keyframes(animation.rotations(), &animation.rotations_ctrl(), 0);
+        assert_eq!(decoded.len(), keyframes.len());
+        for ((ratio, key), (expected_ratio, expected)) in decoded.iter().zip(keyframes.iter()) {
+            assert_eq!(ratio, expected_ratio);
+            let angle = angle(*key, *expected);
+            assert!(angle < 3e-4, "ratio={} angle={}", ratio, angle);
+        }
+
+        let rebuilt = animation.with_rotation_deltas(&[(0, encoded.clone())]).unwrap();
+        assert_eq!(rebuilt.num_tracks(), 2);
+        assert_eq!(rebuilt.duration(), 4.0);
+        // other tracks are copied as is
+        assert!(rebuilt
+            .track_rotations(1)
+            .zip(animation.track_rotations(1))
+            .all(|(a, b)| a.value_eq(b)));
+        for ratio in [0.0, 0.123, 0.5, 0.77, 1.0] {
+            let (k0, k1, alpha) = animation.bracket_rotation(0, ratio).unwrap();
+            let expected = k0.decompress().lerp(k1.decompress(), alpha);
+            let (k0, k1, alpha) = rebuilt.bracket_rotation(0, ratio).unwrap();
+            let actual = k0.decompress().lerp(k1.decompress(), alpha);
+            assert!(actual.angle_between(expected) < 5e-4, "ratio={}", ratio);
+        }
+
+        // fast rotation deltas are still decoded within quantization tolerance
+        let encoded = animation.encode_rotation_deltas(1).unwrap();
+        let keyframes = animation.track_keyframes(animation.rotations(), &animation.rotations_ctrl(), 1);
+        for ((_, key), (_, expected)) in encoded.decode().iter().zip(keyframes.iter()) {
+            assert!(angle(*key, *expected) < 2e-3);
+        }
+
+        assert!(animation.encode_rotation_deltas(2).unwrap_err().is_invalid_index());
+        assert!(animation
+            .with_rotation_deltas(&[(2, encoded.clone())])
+            .unwrap_err()
+            .is_invalid_index());
+        let mut truncated = encoded;
+        truncated.ratios.pop();
+        truncated.deltas.pop();
+        assert!(animation
+            .with_rotation_deltas(&[(1, truncated)])
+            .unwrap_err()
+            .is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_decompress_keys() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let padding = (animation.num_aligned_tracks() - animation.num_tracks()) * 2;
+
+        let mut translations = vec![(0.0, 0, Vec3::NAN); 3];
+        animation.decompress_translation_keys(&mut translations);
+        assert_eq!(translations.len(), animation.translations().len() - padding);
+        let mut rotations = Vec::new();
+        animation.decompress_rotation_keys(&mut rotations);
+        assert_eq!(rotations.len(), animation.rotations().len() - padding);
+        let mut scales = Vec::new();
+        animation.decompress_scale_keys(&mut scales);
+        assert_eq!(scales.len(), animation.scales().len() - padding);
+
+        // first key of track 0 is the first translation key
+        assert_eq!(translations[0], (0.0, 0, animation.translations()[0].decompress()));
+
+        for track in 0..animation.num_tracks() {
+            let expected = animation.track_keyframes(animation.translations(), &animation.translations_ctrl(), track);
+            let actual: Vec<_> = translations.iter().filter(|(_, t, _)| *t as usize == track).collect();
+            assert_eq!(actual.len(), expected.len());
+            for ((ratio, _, value), (expected_ratio, key)) in actual.into_iter().zip(expected) {
+                assert_eq!(*ratio, expected_ratio);
+                assert_eq!(*value, key.decompress());
+            }
+
+            let expected = animation.track_keyframes(animation.rotations(), &animation.rotations_ctrl(), track);
+            let actual: Vec<_> = rotations.iter().filter(|(_, t, _)| *t as usize == track).collect();
+            assert_eq!(actual.len(), expected.len());
+            for ((ratio, _, value), (expected_ratio, key)) in actual.into_iter().zip(expected) {
+                assert_eq!(*ratio, expected_ratio);
+                assert!(value.abs_diff_eq(key.decompress(), 1e-6));
+            }
+
+            let expected = animation.track_keyframes(animation.scales(), &animation.scales_ctrl(), track);
+            let actual = scales.iter().filter(|(_, t, _)| *t as usize == track).count();
+            assert_eq!(actual, expected.len());
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_display() {
+        let mut builder = AnimationBuilder::new(1.5, 1);
+        builder.set_name("summary");
+        for ratio in [0.0, 0.5, 1.0] {
+            builder
+                .push_translation(0, ratio, Float3KeyF32::from(Vec3::new(123.25, 0.0, 0.0)))
+                .unwrap();
+        }
+        constant_track(&mut builder, 0, None, Some(Quat::IDENTITY), Some(Vec3::ONE));
+        let animation = builder.build().unwrap();
+        let summary = animation.to_string();
+        assert_eq!(
+            summary,
+            "Animation { name: \"summary\", duration: 1.5s, num_tracks: 1, translations: 9, rotations: 8, scales: 8 }"
+        );
+        assert!(!summary.contains("123"));
+
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let summary = animation.to_string();
+        assert!(summary.contains(&format!("num_tracks: {}", animation.num_tracks())));
+        assert!(summary.contains(&format!("rotations: {}", animation.rotations().len())));
+        assert!(!summary.contains('\n'));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_rotation_largest_histogram() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let histogram = animation.rotation_largest_histogram();
+        assert_eq!(histogram.iter().sum::<usize>(), animation.rotations().len());
+        assert!(histogram[3] > 0);
+        for key in animation.rotations() {
+            let quat = key.decompress().to_array().map(f32::abs);
+            assert!(quat.iter().all(|v| *v <= quat[key.largest()] + 1e-3), "{:?}", quat);
+        }
+
+        for (quat, largest) in [
+            (Quat::from_xyzw(0.9, 0.1, 0.3, 0.2), 0),
+            (Quat::from_xyzw(0.1, -0.9, 0.3, 0.2), 1),
+            (Quat::from_xyzw(0.1, 0.3, -0.9, 0.2), 2),
+            (Quat::IDENTITY, 3),
+        ] {
+            assert_eq!(QuaternionKey::from_quat(quat.normalize()).largest(), largest);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validate_keys() {
+        let mut animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        assert!(animation.validate_keys().is_ok());
+
+        // infinite scale, then an earlier nan translation
+        let scale_track = animation.key_tracks(animation.scales_ctrl().previouses)[12];
+        animation.scales_mut()[12] = Float3Key([0x3c00, 0x7c00, 0x3c00]);
+        let err = animation.validate_keys().unwrap_err();
+        assert!(err.is_invalid_key());
+        assert!(matches!(
+            err,
+            OzzError::InvalidKey {
+                field: "scale_key",
+                track,
+                key: 12
+            } if track == scale_track
+        ));
+
+        animation.translations_mut()[41] = Float3Key([0, 0x7e00, 0]);
+        animation.translations_mut()[57] = Float3Key([0xfc00, 0, 0]);
+        let err = animation.validate_keys().unwrap_err();
+        let translation_track = animation.key_tracks(animation.translations_ctrl().previouses)[41];
+        assert!(animation
+            .track_translations(translation_track)
+            .any(|key| !key.decompress().is_finite()));
+        assert!(matches!(
+            err,
+            OzzError::InvalidKey {
+                field: "translation_key",
+                track,
+                key: 41
+            } if track == translation_track
+        ));
+        assert_eq!(
+            err.to_string(),
+            format!("Invalid key: translation_key track {} key 41", translation_track)
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_value_eq() {
+        let a = Float3Key::from_f32(Vec3::new(1.0, 2.0, 3.0));
+        assert!(a.value_eq(&Float3Key::from_f32(Vec3::new(1.0, 2.0, 3.0))));
+        assert!(!a.value_eq(&Float3Key::from_f32(Vec3::new(1.0, 2.0, 3.01))));
+
+        let q = QuaternionKey::from_quat(Quat::from_rotation_x(0.5));
+        assert!(q.value_eq(&QuaternionKey::from_quat(Quat::from_rotation_x(0.5))));
+        assert!(!q.value_eq(&QuaternionKey::from_quat(Quat::from_rotation_x(0.6))));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_enforce_rotation_continuity() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let key = QuaternionKey(rng.gen());
+            assert!(
+                key.negated().decompress().abs_diff_eq(-key.decompress(), 1e-4),
+                "{:?}",
+                key
+            );
+            assert_eq!(key.negated().negated(), key);
+        }
+
+        // rotation around z from 0.2 to 0.6 radians, last key in the opposite hemisphere
+        let (q0, q1) = (Quat::from_rotation_z(0.2), Quat::from_rotation_z(0.6));
+        let mut builder = AnimationBuilder::new(1.0, 1);
+        constant_track(&mut builder, 0, Some(Vec3::ZERO), None, Some(Vec3::ONE));
+        builder.push_rotation(0, 0.0, QuaternionKeyF32::from(q0)).unwrap();
+        builder.push_rotation(0, 1.0, QuaternionKeyF32::from(-q1)).unwrap();
+        let mut animation = builder.build().unwrap();
+        let sample = |animation: &Animation, ratio: f32| sample_pose(animation, ratio)[0].rotation.quat(0);
+
+        // interpolation takes the long way
+        let flipped = sample(&animation, 0.5);
+        assert!(flipped.angle_between(Quat::from_rotation_z(0.4)) > 1.0);
+
+        assert_eq!(animation.enforce_rotation_continuity(), 1);
+        assert_eq!(animation.enforce_rotation_continuity(), 0);
+        for ratio in [0.0, 0.25, 0.5, 0.75, 1.0] {
+            let expected = Quat::from_rotation_z(0.2 + 0.4 * ratio);
+            let rotation = sample(&animation, ratio);
+            assert!(rotation.angle_between(expected) < 2e-3, "{} {}", ratio, rotation);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_decimate() {
+        let mut builder = AnimationBuilder::new(2.0, 2);
+        builder.set_name("decimate");
+        for idx in 0..=20 {
+            let ratio = idx as f32 / 20.0;
+            // track 0: linear, track 1: linear with a bump at ratio 0.5
+            let bump = if idx == 10 { 1.0 } else { 0.0 };
+            for track in 0..2 {
+                let translation = Vec3::new(2.0 * ratio, -ratio, bump * track as f32);
+                builder
+                    .push_translation(track, ratio, Float3KeyF32::from(translation))
+                    .unwrap();
+                let rotation = Quat::from_rotation_z(0.5 * ratio);
+                builder
+                    .push_rotation(track, ratio, QuaternionKeyF32::from(rotation))
+                    .unwrap();
+                let scale = Vec3::splat(1.0 + ratio);
+                builder.push_scale(track, ratio, Float3KeyF32::from(scale)).unwrap();
+            }
+        }
+        let animation = builder.build().unwrap();
+        assert!(animation.decimate(-1.0, 0.0, 0.0).unwrap_err().is_invalid_job());
+        assert!(animation.decimate(0.0, f32::NAN, 0.0).unwrap_err().is_invalid_job());
+
+        let decimated = animation.decimate(2e-3, 2e-3, 2e-3).unwrap();
+        assert_eq!(decimated.name(), "decimate");
+        assert_eq!(decimated.duration(), 2.0);
+        assert_eq!(decimated.track_translations(0).count(), 2);
+        assert_eq!(decimated.track_rotations(0).count(), 2);
+        assert_eq!(decimated.track_scales(0).count(), 2);
+        assert_eq!(decimated.track_translations(1).count(), 5); // bump and its neighbors
+        assert_eq!(decimated.track_rotations(1).count(), 2);
+
+        for track in 0..2 {
+            for ratio in [0.0, 0.1, 0.33, 0.5, 0.52, 0.75, 0.9, 1.0] {
+                let (k0, k1, alpha) = animation.bracket_translation(track, ratio).unwrap();
+                let expected = k0.decompress().lerp(k1.decompress(), alpha);
+                let (k0, k1, alpha) = decimated.bracket_translation(track, ratio).unwrap();
+                let actual = k0.decompress().lerp(k1.decompress(), alpha);
+                assert!(actual.abs_diff_eq(expected, 2e-3), "{} {} {}", ratio, actual, expected);
+
+                let (k0, k1, alpha) = animation.bracket_rotation(track, ratio).unwrap();
+                let expected = k0.decompress().lerp(k1.decompress(), alpha);
+                let (k0, k1, alpha) = decimated.bracket_rotation(track, ratio).unwrap();
+                let actual = k0.decompress().lerp(k1.decompress(), alpha);
+                assert!(
+                    actual.angle_between(expected) < 2e-3,
+                    "{} {} {}",
+                    ratio,
+                    actual,
+                    expected
+                );
+
+                let (k0, k1, alpha) = animation.bracket_scale(track, ratio).unwrap();
+                let expected = k0.decompress().lerp(k1.decompress(), alpha);
+                let (k0, k1, alpha) = decimated.bracket_scale(track, ratio).unwrap();
+                let actual = k0.decompress().lerp(k1.decompress(), alpha);
+                assert!(actual.abs_diff_eq(expected, 2e-3), "{} {} {}", ratio, actual, expected);
+            }
+        }
+
+        // a large tolerance removes the bump too
+        let decimated = animation.decimate(10.0, 0.0, 0.0).unwrap();
+        assert_eq!(decimated.track_translations(1).count(), 2);
+
+        // last rotation key is in the opposite hemisphere, sampling goes the long way without the middle key
+        let mut builder = AnimationBuilder::new(1.0, 1);
+        for ratio in [0.0, 1.0] {
+            builder
+                .push_translation(0, ratio, Float3KeyF32::from(Vec3::ZERO))
+                .unwrap();
+            builder.push_scale(0, ratio, Float3KeyF32::from(Vec3::ONE)).unwrap();
+        }
+        let rotations = [Quat::IDENTITY, Quat::from_rotation_z(0.25), -Quat::from_rotation_z(0.5)];
+        for (ratio, rotation) in [0.0, 0.5, 1.0].into_iter().zip(rotations) {
+            builder
+                .push_rotation(0, ratio, QuaternionKeyF32::from(rotation))
+                .unwrap();
+        }
+        let animation = builder.build().unwrap();
+        let decimated = animation.decimate(0.0, 2e-3, 0.0).unwrap();
+        assert_eq!(decimated.track_rotations(0).count(), 3);
+        let actual = decimated.decompress_rotations_at(0, 0.5).unwrap();
+        assert!(actual.angle_between(rotations[1]) < 2e-3);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_dedup_consecutive() {
+        let (a, b) = (Vec3::new(1.0, 2.0, 3.0), Vec3::new(-1.0, 0.5, 0.0));
+        let mut builder = AnimationBuilder::new(3.0, 2);
+        builder.set_name("dedup");
+        // track 0: 3 identical keys
+        for ratio in [0.0, 0.5, 1.0] {
+            builder.push_translation(0, ratio, Float3KeyF32::from(a)).unwrap();
+        }
+        // track 1: only keys between 2 identical keys are redundant
+        for (ratio, value) in [(0.0, a), (0.2, a), (0.4, a), (0.6, b), (0.8, a), (1.0, a)] {
+            builder.push_translation(1, ratio, Float3KeyF32::from(value)).unwrap();
+        }
+        for track in 0..2 {
+            for ratio in [0.0, 0.3, 0.7, 1.0] {
+                builder
+                    .push_rotation(track, ratio, QuaternionKeyF32::from(Quat::from_rotation_z(1.0)))
+                    .unwrap();
+            }
+            constant_track(&mut builder, track, None, None, Some(Vec3::ONE));
+        }
+        let animation = builder.build().unwrap();
+        let dedup = animation.dedup_consecutive().unwrap();
+        assert_eq!(dedup.name(), "dedup");
+        assert_eq!(dedup.duration(), 3.0);
+        assert_eq!(dedup.num_tracks(), 2);
+
+        let ratios = |animation: &Animation, track: usize| -> Vec<f32> {
+            let ctrl = animation.translations_ctrl();
+            animation
+                .track_key_indices(animation.translations().len(), ctrl.previouses, track)
+                .map(|idx| animation.timepoints()[ctrl.ratios[idx] as usize])
+                .collect()
+        };
+        assert_eq!(ratios(&dedup, 0), vec![0.0, 1.0]);
+        assert_eq!(ratios(&dedup, 1), vec![0.0, 0.4, 0.6, 0.8, 1.0]);
+        assert_eq!(dedup.track_rotations(0).count(), 2);
+        assert_eq!(dedup.track_scales(1).count(), 2);
+        assert!(dedup
+            .track_translations(0)
+            .all(|key| key.value_eq(&Float3Key::from_f32(a))));
+
+        for track in 0..2 {
+            for ratio in [0.0, 0.1, 0.3, 0.5, 0.65, 0.9, 1.0] {
+                let (k0, k1, alpha) = animation.bracket_translation(track, ratio).unwrap();
+                let expected = k0.decompress().lerp(k1.decompress(), alpha);
+                let (k0, k1, alpha) = dedup.bracket_translation(track, ratio).unwrap();
+                let actual = k0.decompress().lerp(k1.decompress(), alpha);
+                assert!(actual.abs_diff_eq(expected, 1e-6), "{} {} {}", ratio, actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_slice() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let sliced = animation.slice(0.3, 0.7).unwrap();
+        assert_eq!(sliced.name(), animation.name());
+        assert_eq!(sliced.num_tracks(), animation.num_tracks());
+        assert!((sliced.duration() - animation.duration() * 0.4).abs() < 1e-5);
+        for (ratio, original) in [(0.0, 0.3), (0.25, 0.4), (0.5, 0.5), (0.8, 0.62), (1.0, 0.7)] {
+            let expected = sample_pose(&animation, original);
+            let actual = sample_pose(&sliced, ratio);
+            for track in 0..animation.num_tracks() {
+                let (expected, actual, lane) = (&expected[track / 4], &actual[track / 4], track % 4);
+                let (t0, t1) = (expected.translation.vec3(lane), actual.translation.vec3(lane));
+                assert!(t0.abs_diff_eq(t1, 2e-3), "{} {} {} {}", ratio, track, t0, t1);
+                let (r0, r1) = (expected.rotation.quat(lane), actual.rotation.quat(lane));
+                assert!(r0.abs_diff_eq(r1, 2e-4), "{} {} {} {}", ratio, track, r0, r1);
+                let (s0, s1) = (expected.scale.vec3(lane), actual.scale.vec3(lane));
+                assert!(s0.abs_diff_eq(s1, 2e-3), "{} {} {} {}", ratio, track, s0, s1);
+            }
+        }
+
+        // keys only at 0 and 1, outside the window
+        let mut builder = AnimationBuilder::new(2.0, 1);
+        builder
+            .push_translation(0, 0.0, Float3KeyF32::from(Vec3::ZERO))
+            .unwrap();
+        builder.push_translation(0, 1.0, Float3KeyF32::from(Vec3::X)).unwrap();
+        builder
+            .push_rotation(0, 0.0, QuaternionKeyF32::from(Quat::IDENTITY))
+            .unwrap();
+        builder
+            .push_rotation(0, 0.5, QuaternionKeyF32::from(Quat::from_rotation_y(1.0)))
+            .unwrap();
+        builder
+            .push_rotation(0, 1.0, QuaternionKeyF32::from(Quat::IDENTITY))
+            .unwrap();
+        constant_track(&mut builder, 0, None, None, Some(Vec3::ONE));
+        let animation = builder.build().unwrap();
+        let sliced = animation.slice(0.25, 0.5).unwrap();
+        assert_eq!(sliced.duration(), 0.5);
+        let translations: Vec<Vec3> = sliced.track_translations(0).map(|key| key.decompress()).collect();
+        assert_eq!(translations, [Vec3::new(0.25, 0.0, 0.0), Vec3::new(0.5, 0.0, 0.0)]);
+        // key at the boundary is kept as is
+        let rotations: Vec<&QuaternionKey> = sliced.track_rotations(0).collect();
+        assert_eq!(rotations.len(), 2);
+        assert!(rotations[1].value_eq(&QuaternionKey::from_quat(Quat::from_rotation_y(1.0))));
+
+        assert!(animation.slice(0.5, 0.5).unwrap_err().is_invalid_job());
+        assert!(animation.slice(0.7, 0.2).unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_loop_discontinuity() {
+        let build = |seam_translation: Vec3, seam_angle: f32| {
+            let mut builder = AnimationBuilder::new(1.0, 5);
+            for track in 0..5 {
+                let (translation, angle) = if track == 3 {
+                    (seam_translation, seam_angle)
+                } else {
+                    (Vec3::ZERO, 0.0)
+                };
+                builder
+                    .push_translation(track, 0.0, Float3KeyF32::from(Vec3::X))
+                    .unwrap();
+                builder
+                    .push_translation(track, 0.5, Float3KeyF32::from(Vec3::Y))
+                    .unwrap();
+                builder
+                    .push_translation(track, 1.0, Float3KeyF32::from(Vec3::X + translation))
+                    .unwrap();
+                let rotation = Quat::from_rotation_y(0.3);
+                builder
+                    .push_rotation(track, 0.0, QuaternionKeyF32::from(rotation))
+                    .unwrap();
+                builder
+                    .push_rotation(track, 0.5, QuaternionKeyF32::from(Quat::from_rotation_x(1.0)))
+                    .unwrap();
+                let rotation = rotation * Quat::from_rotation_z(angle);
+                builder
+                    .push_rotation(track, 1.0, QuaternionKeyF32::from(rotation))
+                    .unwrap();
+                constant_track(&mut builder, track, None, None, Some(Vec3::ONE));
+            }
+            builder.build().unwrap()
+        };
+
+        let (translation, angle) = build(Vec3::ZERO, 0.0).loop_discontinuity();
+        assert!(translation < 1e-3 && angle < 1e-3, "{} {}", translation, angle);
+
+        let (translation, angle) = build(Vec3::new(0.0, 0.3, -0.4), 0.5).loop_discontinuity();
+        assert!((translation - 0.5).abs() < 1e-3, "{}", translation);
+        assert!((angle - 0.5).abs() < 2e-3, "{}", angle);
+
+        // rotations are compared as orientations, a negated key is no discontinuity
+        let (_, angle) = build(Vec3::ZERO, core::f32::consts::TAU).loop_discontinuity();
+        assert!(angle < 2e-3, "{}", angle);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_ratio_seconds() {
+        assert_eq!(Ratio::new(0.25).value(), 0.25);
+        assert_eq!(Ratio::new(-0.5).value(), 0.0);
+        assert_eq!(Ratio::from(1.5).value(), 1.0);
+        assert_eq!(Ratio::new(f32::NAN).value(), 1.0);
+        assert_eq!(f32::from(Ratio::new(0.75)), 0.75);
+        assert_eq!(Ratio::new(0.5).to_seconds(4.0), Seconds::new(2.0));
+
+        assert_eq!(Seconds::new(1.0).to_ratio(4.0), Ratio::new(0.25));
+        assert_eq!(Seconds::new(-1.0).to_ratio(4.0), Ratio::new(0.0));
+        assert_eq!(Seconds::new(5.0).to_ratio(4.0), Ratio::new(1.0));
+        assert_eq!(Seconds::new(1.0).to_ratio(0.0), Ratio::new(0.0));
+        let seconds: f32 = Seconds::from(3.0).into();
+        assert_eq!(seconds, 3.0);
+        assert_eq!(Seconds::new(-2.5).value(), -2.5);
+
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        for seconds in [0.0, 0.3, animation.duration() * 0.5, animation.duration() + 1.0] {
+            let ratio = Seconds::new(seconds).to_ratio(animation.duration());
+            assert_eq!(ratio.value(), animation.ratio_at_time(seconds));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_make_additive() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let reference = sample_pose(&animation, 0.0);
+        let additive = animation.make_additive(&reference).unwrap();
+        assert_eq!(additive.duration(), animation.duration());
+        assert_eq!(additive.num_tracks(), animation.num_tracks());
+
+        let delta = sample_pose(&additive, 0.0);
+        for track in 0..animation.num_tracks() {
+            let (delta, lane) = (&delta[track / 4], track % 4);
+            assert!(delta.translation.vec3(lane).abs_diff_eq(Vec3::ZERO, 2e-3));
+            assert!(delta.rotation.quat(lane).abs_diff_eq(Quat::IDENTITY, 2e-4));
+            assert!(delta.scale.vec3(lane).abs_diff_eq(Vec3::ONE, 2e-3));
+        }
+
+        // adding the delta back to the reference
+        let (expected, delta) = (sample_pose(&animation, 0.6), sample_pose(&additive, 0.6));
+        for track in 0..animation.num_tracks() {
+            let (r, e, d, lane) = (
+                &reference[track / 4],
+                &expected[track / 4],
+                &delta[track / 4],
+                track % 4,
+            );
+            let rotation = r.rotation.quat(lane) * d.rotation.quat(lane);
+            assert!(rotation.dot(e.rotation.quat(lane)).abs() > 1.0 - 1e-5);
+            let translation = r.translation.vec3(lane) + d.translation.vec3(lane);
+            assert!(translation.abs_diff_eq(e.translation.vec3(lane), 5e-3));
+        }
+
+        assert!(animation.make_additive(&reference[1..]).unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_concat() {
+        let assert_pose_eq = |expected: &[SoaTransform], actual: &[SoaTransform], tolerance: f32| {
+            for (expected, actual) in expected.iter().zip(actual.iter()) {
+                for lane in 0..4 {
+                    let (t0, t1) = (expected.translation.vec3(lane), actual.translation.vec3(lane));
+                    assert!(t0.abs_diff_eq(t1, tolerance), "{} {}", t0, t1);
+                    let (r0, r1) = (expected.rotation.quat(lane), actual.rotation.quat(lane));
+                    assert!(r0.abs_diff_eq(r1, tolerance), "{} {}", r0, r1);
+                    let (s0, s1) = (expected.scale.vec3(lane), actual.scale.vec3(lane));
+                    assert!(s0.abs_diff_eq(s1, tolerance), "{} {}", s0, s1);
+                }
+            }
+        };
+
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let concat = animation.concat(&animation, 0.0).unwrap();
+        assert_eq!(concat.name(), animation.name());
+        assert_eq!(concat.num_tracks(), animation.num_tracks());
+        assert_eq!(concat.duration(), animation.duration() * 2.0);
+        for ratio in [0.0, 0.2, 0.6, 1.0] {
+            assert_pose_eq(
+                &sample_pose(&animation, ratio),
+                &sample_pose(&concat, ratio * 0.5),
+                1e-5,
+            );
+        }
+        assert_pose_eq(&sample_pose(&animation, 1.0), &sample_pose(&concat, 0.5), 1e-5);
+        // no pop across the seam
+        assert_pose_eq(&sample_pose(&concat, 0.4999), &sample_pose(&concat, 0.5001), 2e-2);
+        // the second clip plays once the first keys after the seam are reached
+        for ratio in [0.6, 0.9, 1.0] {
+            assert_pose_eq(
+                &sample_pose(&animation, ratio),
+                &sample_pose(&concat, 0.5 + ratio * 0.5),
+                1e-5,
+            );
+        }
+
+        // crossfade
+        let blend = animation.duration() * 0.5;
+        let concat = animation.concat(&animation, blend).unwrap();
+        assert_eq!(concat.duration(), animation.duration() * 2.5);
+        assert_pose_eq(&sample_pose(&animation, 0.3), &sample_pose(&concat, 0.3 * 0.4), 1e-5);
+        assert_pose_eq(
+            &sample_pose(&animation, 0.3),
+            &sample_pose(&concat, 0.6 + 0.3 * 0.4),
+            1e-5,
+        );
+        let (end, start, middle) = (
+            sample_pose(&animation, 1.0),
+            sample_pose(&animation, 0.0),
+            sample_pose(&concat, 0.5),
+        );
+        for ((end, start), middle) in end.iter().zip(start.iter()).zip(middle.iter()) {
+            let translation = SoaVec3::lerp(&end.translation, &start.translation, f32x4::splat(0.5));
+            assert!(translation.vec3(0).abs_diff_eq(middle.translation.vec3(0), 1e-5));
+        }
+
+        let mut builder = AnimationBuilder::new(1.0, 1);
+        constant_track(&mut builder, 0, Some(Vec3::ZERO), Some(Quat::IDENTITY), Some(Vec3::ONE));
+        let single = builder.build().unwrap();
+        assert!(animation.concat(&single, 0.0).unwrap_err().is_invalid_job());
+        assert!(single.concat(&single, -1.0).unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_ratio_at_time() {
+        let animation = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        assert_eq!(animation.ratio_at_time(0.0), 0.0);
+        assert_eq!(animation.ratio_at_time(-1.0), 0.0);
+        assert_eq!(animation.ratio_at_time(animation.duration()), 1.0);
+        assert_eq!(animation.ratio_at_time(100.0), 1.0);
+        assert!((animation.ratio_at_time(4.3) - 0.5).abs() < 1e-6);
+        assert!((animation.ratio_at_time(2.15) - 0.25).abs() < 1e-6);
+
+        assert_eq!(animation.ratio_at_looped_time(0.0), 0.0);
+        assert_eq!(animation.ratio_at_looped_time(animation.duration()), 0.0);
+        assert!((animation.ratio_at_looped_time(4.3) - 0.5).abs() < 1e-6);
+        assert!((animation.ratio_at_looped_time(8.6 + 4.3) - 0.5).abs() < 1e-6);
+        assert!((animation.ratio_at_looped_time(-2.15) - 0.75).abs() < 1e-6);
+    }
+
     #[cfg(feature = "rkyv")]
     #[test]
     #[wasm_bindgen_test]
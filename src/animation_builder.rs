@@ -0,0 +1,329 @@
+//!
+//! Animation builder, builds a runtime `Animation` from keyframes.
+//!
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use glam::{Quat, Vec3};
+
+use crate::animation::{Animation, AnimationRaw, Float3Key, QuaternionKey};
+use crate::base::OzzError;
+
+/// Builds a runtime `Animation` from per-track keyframes.
+///
+/// Keyframes are pushed with a ratio (0 is the beginning of the animation, 1 is the end), in any order. Every
+/// track must have a key at ratio 0 and ratio 1 for each transformation type, so that sampling is always
+/// bracketed by 2 keys. Keyframes can be provided either compressed (`Float3Key`, `QuaternionKey`) or as f32
+/// (`Float3KeyF32`, `QuaternionKeyF32`), which are compressed while building.
+///
+/// [`Float3KeyF32`]: crate::animation::Float3KeyF32
+/// [`QuaternionKeyF32`]: crate::animation::QuaternionKeyF32
+#[derive(Debug, Default, Clone)]
+pub struct AnimationBuilder {
+    duration: f32,
+    name: String,
+    translations: Vec<Vec<(f32, Float3Key)>>,
+    rotations: Vec<Vec<(f32, QuaternionKey)>>,
+    scales: Vec<Vec<(f32, Float3Key)>>,
+}
+
+impl AnimationBuilder {
+    /// Creates a new `AnimationBuilder` with `num_tracks` empty tracks.
+    pub fn new(duration: f32, num_tracks: usize) -> AnimationBuilder {
+        AnimationBuilder {
+            duration,
+            name: String::new(),
+            translations: vec![Vec::new(); num_tracks],
+            rotations: vec![Vec::new(); num_tracks],
+            scales: vec![Vec::new(); num_tracks],
+        }
+    }
+
+    /// Gets duration of `AnimationBuilder`.
+    #[inline]
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// Sets duration of `AnimationBuilder`.
+    ///
+    /// Animation duration in seconds, must be greater than 0.
+    #[inline]
+    pub fn set_duration(&mut self, duration: f32) {
+        self.duration = duration;
+    }
+
+    /// Gets the number of tracks of `AnimationBuilder`.
+    #[inline]
+    pub fn num_tracks(&self) -> usize {
+        self.translations.len()
+    }
+
+    /// Gets name of `AnimationBuilder`.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Sets name of `AnimationBuilder`.
+    #[inline]
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    /// Pushes a translation key to `track` at `ratio`.
+    pub fn push_translation(&mut self, track: usize, ratio: f32, key: impl Into<Float3Key>) -> Result<(), OzzError> {
+        let keys = self.translations.get_mut(track).ok_or(OzzError::InvalidIndex)?;
+        keys.push((ratio, key.into()));
+        Ok(())
+    }
+
+    /// Pushes a rotation key to `track` at `ratio`.
+    pub fn push_rotation(&mut self, track: usize, ratio: f32, key: impl Into<QuaternionKey>) -> Result<(), OzzError> {
+        let keys = self.rotations.get_mut(track).ok_or(OzzError::InvalidIndex)?;
+        keys.push((ratio, key.into()));
+        Ok(())
+    }
+
+    /// Pushes a scale key to `track` at `ratio`.
+    pub fn push_scale(&mut self, track: usize, ratio: f32, key: impl Into<Float3Key>) -> Result<(), OzzError> {
+        let keys = self.scales.get_mut(track).ok_or(OzzError::InvalidIndex)?;
+        keys.push((ratio, key.into()));
+        Ok(())
+    }
+
+    /// Validates `AnimationBuilder` parameters.
+    ///
+    /// Duration must be positive, there must be at least one track, and every track must have keys at ratio 0
+    /// and 1, with unique ratios.
+    pub fn validate(&self) -> bool {
+        if self.duration <= 0.0 || self.duration.is_nan() || self.num_tracks() == 0 {
+            return false;
+        }
+        let tracks = self.translations.iter().map(|keys| Self::validate_track(keys));
+        let tracks = tracks.chain(self.rotations.iter().map(|keys| Self::validate_track(keys)));
+        let mut tracks = tracks.chain(self.scales.iter().map(|keys| Self::validate_track(keys)));
+        tracks.all(|ok| ok)
+    }
+
+    fn validate_track<K>(keys: &[(f32, K)]) -> bool {
+        let mut ratios: Vec<f32> = keys.iter().map(|(ratio, _)| *ratio).collect();
+        ratios.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        ratios.first() == Some(&0.0) && ratios.last() == Some(&1.0) && ratios.windows(2).all(|w| w[0] < w[1])
+    }
+
+    /// Builds the `Animation`.
+    /// The validate job before any operation is performed.
+    pub fn build(&self) -> Result<Animation, OzzError> {
+        if !self.validate() {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let mut timepoints: Vec<f32> = Vec::new();
+        let keys = self.translations.iter().chain(self.scales.iter()).flatten();
+        timepoints.extend(keys.map(|(ratio, _)| *ratio));
+        timepoints.extend(self.rotations.iter().flatten().map(|(ratio, _)| *ratio));
+        timepoints.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        timepoints.dedup();
+        if timepoints.len() > u16::MAX as usize + 1 {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let (translations, t_ratios, t_previouses) =
+            Self::build_keys(&self.translations, Float3Key::from_f32(Vec3::ZERO), &timepoints)?;
+        let (rotations, r_ratios, r_previouses) =
+            Self::build_keys(&self.rotations, QuaternionKey::from_quat(Quat::IDENTITY), &timepoints)?;
+        let (scales, s_ratios, s_previouses) =
+            Self::build_keys(&self.scales, Float3Key::from_f32(Vec3::ONE), &timepoints)?;
+
+        let raw = AnimationRaw {
+            duration: self.duration,
+            num_tracks: self.num_tracks() as u32,
+            name: self.name.clone(),
+            timepoints,
+
+            translations,
+            t_ratios,
+            t_previouses,
+            t_iframe_interval: 1.0,
+            t_iframe_entries: Vec::new(),
+            t_iframe_desc: Vec::new(),
+
+            rotations,
+            r_ratios,
+            r_previouses,
+            r_iframe_interval: 1.0,
+            r_iframe_entries: Vec::new(),
+            r_iframe_desc: Vec::new(),
+
+            scales,
+            s_ratios,
+            s_previouses,
+            s_iframe_interval: 1.0,
+            s_iframe_entries: Vec::new(),
+            s_iframe_desc: Vec::new(),
+        };
+        Ok(Animation::from_raw(&raw))
+    }
+
+    // Sorts keys in the runtime order: by the ratio of the previous key of the same track (a key is needed as
+    // soon as its predecessor is reached), then by track. Padding tracks are filled with identity keys.
+    #[allow(clippy::type_complexity)]
+    fn build_keys<K: Copy>(
+        tracks: &[Vec<(f32, K)>],
+        identity: K,
+        timepoints: &[f32],
+    ) -> Result<(Vec<K>, Vec<u16>, Vec<u16>), OzzError> {
+        let num_aligned_tracks = (tracks.len() + 3) & !0x3;
+        let padding = [(0.0, identity), (1.0, identity)];
+
+        let mut sorting = Vec::new(); // (previous ratio, track, ratio, key)
+        for track in 0..num_aligned_tracks {
+            let mut keys = match tracks.get(track) {
+                Some(keys) => keys.clone(),
+                None => padding.to_vec(),
+            };
+            keys.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+            let mut prev_ratio = -1.0;
+            for (ratio, key) in keys {
+                sorting.push((prev_ratio, track, ratio, key));
+                prev_ratio = ratio;
+            }
+        }
+        sorting.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then(a.1.cmp(&b.1)));
+
+        let mut keys = Vec::with_capacity(sorting.len());
+        let mut ratios = Vec::with_capacity(sorting.len());
+        let mut previouses = Vec::with_capacity(sorting.len());
+        let mut lasts: Vec<Option<usize>> = vec![None; num_aligned_tracks];
+        for (idx, (_, track, ratio, key)) in sorting.into_iter().enumerate() {
+            let previous = match lasts[track] {
+                Some(last) => idx - last,
+                None => 0,
+            };
+            if previous > u16::MAX as usize {
+                return Err(OzzError::InvalidJob);
+            }
+            lasts[track] = Some(idx);
+
+            let timepoint = timepoints.binary_search_by(|x| x.partial_cmp(&ratio).unwrap());
+            keys.push(key);
+            ratios.push(timepoint.map_err(|_| OzzError::Unexcepted)? as u16);
+            previouses.push(previous as u16);
+        }
+        Ok((keys, ratios, previouses))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::animation::{Float3KeyF32, QuaternionKeyF32};
+    use crate::math::SoaTransform;
+    use crate::sampling_job::{SamplingContext, SamplingJob};
+
+    fn new_builder() -> AnimationBuilder {
+        let mut builder = AnimationBuilder::new(1.0, 2);
+        builder.set_name("builder");
+        for track in 0..2 {
+            builder
+                .push_translation(track, 0.0, Float3KeyF32::new([0.0; 3]))
+                .unwrap();
+            builder
+                .push_rotation(track, 0.0, QuaternionKeyF32::from(Quat::IDENTITY))
+                .unwrap();
+            builder
+                .push_rotation(track, 1.0, QuaternionKeyF32::from(Quat::IDENTITY))
+                .unwrap();
+            builder.push_scale(track, 0.0, Float3KeyF32::new([1.0; 3])).unwrap();
+            builder.push_scale(track, 1.0, Float3KeyF32::new([1.0; 3])).unwrap();
+        }
+        builder
+            .push_translation(0, 1.0, Float3KeyF32::new([2.0, 0.0, 0.0]))
+            .unwrap();
+        builder
+            .push_translation(1, 1.0, Float3KeyF32::new([0.0, 0.0, -4.0]))
+            .unwrap();
+        builder
+            .push_translation(1, 0.5, Float3KeyF32::new([0.0, 1.0, 0.0]))
+            .unwrap();
+        builder
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validity() {
+        assert!(new_builder().build().is_ok());
+
+        let mut builder = new_builder();
+        builder.set_duration(0.0);
+        assert!(builder.build().unwrap_err().is_invalid_job());
+
+        let builder = AnimationBuilder::new(1.0, 0);
+        assert!(!builder.validate());
+        assert!(builder.build().unwrap_err().is_invalid_job()); // no track
+
+        let mut builder = AnimationBuilder::new(1.0, 1);
+        builder.push_translation(0, 0.0, Float3KeyF32::new([0.0; 3])).unwrap();
+        builder
+            .push_rotation(0, 0.0, QuaternionKeyF32::from(Quat::IDENTITY))
+            .unwrap();
+        builder
+            .push_rotation(0, 1.0, QuaternionKeyF32::from(Quat::IDENTITY))
+            .unwrap();
+        builder.push_scale(0, 0.0, Float3KeyF32::new([1.0; 3])).unwrap();
+        builder.push_scale(0, 1.0, Float3KeyF32::new([1.0; 3])).unwrap();
+        assert!(builder.build().unwrap_err().is_invalid_job()); // no translation key at ratio 1
+
+        builder.push_translation(0, 1.0, Float3KeyF32::new([0.0; 3])).unwrap();
+        assert!(builder.build().is_ok());
+
+        builder.push_translation(0, 1.0, Float3KeyF32::new([1.0; 3])).unwrap();
+        assert!(builder.build().unwrap_err().is_invalid_job()); // duplicated ratio
+
+        assert!(builder
+            .push_scale(1, 0.0, Float3KeyF32::new([1.0; 3]))
+            .unwrap_err()
+            .is_invalid_index());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_build_and_sample() {
+        let animation = Rc::new(new_builder().build().unwrap());
+        assert_eq!(animation.duration(), 1.0);
+        assert_eq!(animation.num_tracks(), 2);
+        assert_eq!(animation.name(), "builder");
+        assert_eq!(animation.timepoints(), &[0.0, 0.5, 1.0]);
+        assert_eq!(animation.translations().len(), 9);
+        assert_eq!(animation.rotations().len(), 8);
+
+        let mut job: SamplingJob = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::new(animation.num_tracks()));
+        let output = Rc::new(RefCell::new(vec![SoaTransform::default(); animation.num_soa_tracks()]));
+        job.set_output(output.clone());
+
+        for (ratio, t0, t1) in [
+            (0.0, Vec3::ZERO, Vec3::ZERO),
+            (0.25, Vec3::new(0.5, 0.0, 0.0), Vec3::new(0.0, 0.5, 0.0)),
+            (0.5, Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+            (0.75, Vec3::new(1.5, 0.0, 0.0), Vec3::new(0.0, 0.5, -2.0)),
+            (1.0, Vec3::new(2.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -4.0)),
+        ] {
+            job.set_ratio(ratio);
+            job.run().unwrap();
+            let out = output.borrow()[0];
+            assert!(out.translation.vec3(0).abs_diff_eq(t0, 1e-6), "ratio={}", ratio);
+            assert!(out.translation.vec3(1).abs_diff_eq(t1, 1e-6), "ratio={}", ratio);
+            assert!(out.rotation.quat(0).abs_diff_eq(Quat::IDENTITY, 1e-4));
+            assert_eq!(out.scale.vec3(1), Vec3::ONE);
+        }
+    }
+}
@@ -5,7 +5,7 @@
 use glam::{Quat, Vec2, Vec3, Vec4};
 #[cfg(not(feature = "wasm"))]
 use std::fs::File;
-use std::io::{Cursor, Read};
+use std::io::{Cursor, Read, Seek, SeekFrom};
 #[cfg(not(feature = "wasm"))]
 use std::path::Path;
 use std::{mem, slice, str};
@@ -16,26 +16,89 @@ use crate::endian::{Endian, SwapEndian};
 /// Implements input archive concept used to load/de-serialize data.
 /// Endianness conversions are automatically performed according to the Archive
 /// and the native formats.
+///
+/// With `compression` feature, gzip and zstd compressed streams are detected by their magic header, and
+/// decompressed (checksums verified) before parsing.
 pub struct Archive<R: Read> {
-    read: R,
+    read: ArchiveSource<R>,
     endian_swap: bool,
     tag: String,
     version: u32,
+    position: u64,
+    len: Option<u64>,
+}
+
+// Stream read by an `Archive`, the original one or the buffered content of a compressed stream.
+enum ArchiveSource<R: Read> {
+    Plain(R),
+    #[cfg(feature = "compression")]
+    Buffered(Cursor<Vec<u8>>),
+}
+
+impl<R: Read> ArchiveSource<R> {
+    // Size of the stream, known only once buffered.
+    fn len(&self) -> Option<u64> {
+        match self {
+            ArchiveSource::Plain(_) => None,
+            #[cfg(feature = "compression")]
+            ArchiveSource::Buffered(cursor) => Some(cursor.get_ref().len() as u64),
+        }
+    }
+}
+
+impl<R: Read> Read for ArchiveSource<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            ArchiveSource::Plain(read) => read.read(buf),
+            #[cfg(feature = "compression")]
+            ArchiveSource::Buffered(cursor) => cursor.read(buf),
+        }
+    }
+
+    #[inline]
+    fn read_exact(&mut self, buf: &mut [u8]) -> std::io::Result<()> {
+        match self {
+            ArchiveSource::Plain(read) => read.read_exact(buf),
+            #[cfg(feature = "compression")]
+            ArchiveSource::Buffered(cursor) => cursor.read_exact(buf),
+        }
+    }
 }
 
 impl<R: Read> Archive<R> {
     /// Creates an `Archive` from a file.
-    pub fn new(mut read: R) -> Result<Archive<R>, OzzError> {
+    ///
+    /// With `compression` feature, a gzip or zstd compressed stream is read to the end and decompressed first.
+    ///
+    /// The size of an arbitrary `read` is unknown, so counts read from it can't be checked against the bytes
+    /// left before allocating. Prefer `from_file()`, `from_vec()` or `from_slice()` for untrusted data.
+    pub fn new(read: R) -> Result<Archive<R>, OzzError> {
+        let mut read = ArchiveSource::Plain(read);
         let mut endian_tag = [0u8; 1];
         read.read_exact(&mut endian_tag)?;
+        #[cfg(feature = "compression")]
+        if endian_tag[0] == GZIP_MAGIC[0] || endian_tag[0] == ZSTD_MAGIC[0] {
+            let mut buf = endian_tag.to_vec();
+            read.read_to_end(&mut buf)?;
+            if buf.starts_with(&GZIP_MAGIC) {
+                buf = gunzip(&buf)?;
+            } else if buf.starts_with(&ZSTD_MAGIC) {
+                buf = unzstd(&buf)?;
+            }
+            read = ArchiveSource::Buffered(Cursor::new(buf));
+            read.read_exact(&mut endian_tag)?;
+        }
         let file_endian = Endian::from_tag(endian_tag[0]);
         let native_endian = Endian::native();
 
         let mut archive = Archive {
+            len: read.len(),
             read,
             endian_swap: file_endian != native_endian,
             tag: String::new(),
             version: 0,
+            position: endian_tag.len() as u64,
         };
 
         let tag = archive.read::<String>()?;
@@ -77,6 +140,63 @@ impl<R: Read> Archive<R> {
     pub fn version(&self) -> u32 {
         self.version
     }
+
+    /// Checks that `bytes` are left to read, so that counts read from a truncated or hostile archive fail
+    /// before anything is allocated for them. Always succeeds if the archive size is unknown.
+    pub(crate) fn check_remaining(&self, bytes: u64) -> Result<(), OzzError> {
+        match self.len {
+            Some(len) if self.position.saturating_add(bytes) > len => {
+                Err(OzzError::IO(std::io::ErrorKind::UnexpectedEof))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    #[inline]
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), OzzError> {
+        self.read.read_exact(buf)?;
+        self.position += buf.len() as u64;
+        Ok(())
+    }
+
+    /// Moves to the next object of the archive, once the current one is fully read.
+    ///
+    /// An archive can contain several objects one after another (e.g. a skeleton and its animations), each
+    /// one starting with its own tag and version. `Archive::new()` reads the header of the first object, this
+    /// reads the header of the following one, so that `tag()` and `version()` describe it and it can be read
+    /// with `Skeleton::from_archive()`, `Animation::from_archive()`... Returns the new tag, or `None` at the
+    /// end of the stream.
+    pub fn next_object_tag(&mut self) -> Result<Option<&str>, OzzError> {
+        let mut first = [0u8; 1];
+        if self.read.read(&mut first)? == 0 {
+            return Ok(None);
+        }
+        self.position += 1;
+        let tag = if first[0] != 0 {
+            let mut tag = vec![first[0]];
+            tag.extend(self.read::<String>()?.into_bytes());
+            String::from_utf8(tag).map_err(|e| e.utf8_error())?
+        } else {
+            String::new()
+        };
+        self.version = self.read::<u32>()?;
+        self.tag = tag;
+        Ok(Some(&self.tag))
+    }
+}
+
+impl<R: Read + Seek> Archive<R> {
+    /// Reads the tag and version of the archive at the current position of `read`, without decoding the
+    /// rest of it.
+    ///
+    /// `read` is rewound to its original position afterward, so it can then be passed to `Archive::new()`
+    /// for a full read. Useful to dispatch a file to `Animation` or `Skeleton` reading, e.g. in asset browsers.
+    pub fn peek_header(read: &mut R) -> Result<(String, u32), OzzError> {
+        let position = read.stream_position()?;
+        let header = Archive::new(&mut *read).map(|archive| (archive.tag, archive.version));
+        read.seek(SeekFrom::Start(position))?;
+        header
+    }
 }
 
 #[cfg(not(feature = "wasm"))]
@@ -84,20 +204,25 @@ impl Archive<File> {
     /// Creates an `Archive` from a path.
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Archive<File>, OzzError> {
         let file = File::open(path)?;
-        Archive::new(file)
+        Archive::from_file(file)
     }
 
     /// Creates an `Archive` from a file.
-    pub fn from_file(file: File) -> Result<Archive<File>, OzzError> {
-        Archive::new(file)
+    pub fn from_file(mut file: File) -> Result<Archive<File>, OzzError> {
+        let len = file.metadata()?.len().saturating_sub(file.stream_position()?);
+        let mut archive = Archive::new(file)?;
+        archive.len.get_or_insert(len);
+        Ok(archive)
     }
 }
 
 impl Archive<Cursor<Vec<u8>>> {
     /// Creates an `Archive` from a `Vec<u8>`.
     pub fn from_vec(buf: Vec<u8>) -> Result<Archive<Cursor<Vec<u8>>>, OzzError> {
-        let cursor = Cursor::new(buf);
-        Archive::new(cursor)
+        let len = buf.len() as u64;
+        let mut archive = Archive::new(Cursor::new(buf))?;
+        archive.len.get_or_insert(len);
+        Ok(archive)
     }
 
     /// Creates an `Archive` from a path.
@@ -113,11 +238,87 @@ impl Archive<Cursor<Vec<u8>>> {
 impl Archive<Cursor<&[u8]>> {
     /// Creates an `Archive` from a `&[u8]`.
     pub fn from_slice(buf: &[u8]) -> Result<Archive<Cursor<&[u8]>>, OzzError> {
-        let cursor = Cursor::new(buf);
-        Archive::new(cursor)
+        let len = buf.len() as u64;
+        let mut archive = Archive::new(Cursor::new(buf))?;
+        archive.len.get_or_insert(len);
+        Ok(archive)
+    }
+}
+
+#[cfg(feature = "compression")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+#[cfg(feature = "compression")]
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+// Decompresses a gzip (RFC 1952) single member buffer.
+#[cfg(feature = "compression")]
+fn gunzip(buf: &[u8]) -> Result<Vec<u8>, OzzError> {
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+
+    let invalid = |value: usize| OzzError::InvalidData {
+        field: "gzip",
+        value: value as i64,
+    };
+    if buf.len() < 18 || buf[2] != 8 {
+        return Err(invalid(0));
+    }
+    let flags = buf[3];
+    let mut pos = 10;
+    if flags & FEXTRA != 0 {
+        pos += 2 + u16::from_le_bytes([buf[pos], buf[pos + 1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let len = buf.get(pos..).and_then(|rest| rest.iter().position(|c| *c == 0));
+            pos += len.ok_or(invalid(pos))? + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+    if pos + 8 > buf.len() {
+        return Err(invalid(pos));
+    }
+
+    let data = miniz_oxide::inflate::decompress_to_vec(&buf[pos..]).map_err(|_| invalid(pos))?;
+    let trailer = &buf[buf.len() - 8..];
+    let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    if crc32fast::hash(&data) != crc {
+        return Err(invalid(buf.len() - 8));
+    }
+    if data.len() as u32 != u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]) {
+        return Err(invalid(buf.len() - 4));
+    }
+    Ok(data)
+}
+
+// Decompresses a zstd (RFC 8878) single frame buffer.
+#[cfg(feature = "compression")]
+fn unzstd(buf: &[u8]) -> Result<Vec<u8>, OzzError> {
+    let invalid = |value: usize| OzzError::InvalidData {
+        field: "zstd",
+        value: value as i64,
+    };
+    let mut decoder = ruzstd::StreamingDecoder::new(buf).map_err(|_| invalid(0))?;
+    let mut data = Vec::new();
+    if decoder.read_to_end(&mut data).is_err() {
+        return Err(invalid(data.len()));
+    }
+    // Frames written without the content checksum flag have nothing to verify.
+    if let Some(stored) = decoder.decoder.get_checksum_from_data() {
+        if Some(stored) != decoder.decoder.get_calculated_checksum() {
+            return Err(invalid(data.len()));
+        }
     }
+    Ok(data)
 }
 
+// Maximum number of elements reserved up front by `ArchiveRead::read_vec()`.
+const READ_VEC_CHUNK: usize = 4096;
+
 /// Implements `ArchiveRead` to read `T` from Archive.
 pub trait ArchiveRead<T> {
     /// Reads `T` from the archive.
@@ -127,7 +328,9 @@ pub trait ArchiveRead<T> {
     /// * `count` - The number of elements to read.
     #[inline]
     fn read_vec<R: Read>(archive: &mut Archive<R>, count: usize) -> Result<Vec<T>, OzzError> {
-        let mut buffer = Vec::with_capacity(count);
+        // Grows with the elements actually read, a bogus count fails at the end of the stream instead of
+        // allocating up front.
+        let mut buffer = Vec::with_capacity(count.min(READ_VEC_CHUNK));
         for _ in 0..count {
             buffer.push(Self::read(archive)?);
         }
@@ -153,9 +356,7 @@ macro_rules! primitive_reader {
                 let mut val: $type = Default::default();
                 let size = mem::size_of::<$type>();
                 let ptr = &mut val as *mut $type as *mut u8;
-                archive
-                    .read
-                    .read_exact(unsafe { slice::from_raw_parts_mut(ptr, size) })?;
+                archive.read_exact(unsafe { slice::from_raw_parts_mut(ptr, size) })?;
                 match archive.endian_swap {
                     true => Ok(val.swap_endian()),
                     false => Ok(val),
@@ -249,4 +450,139 @@ mod tests {
         assert_eq!(archive.tag, "ozz-animation");
         assert_eq!(archive.version, 7);
     }
+
+    #[test]
+    #[cfg(not(feature = "wasm"))]
+    fn test_archive_peek_header() {
+        let mut file = File::open("./resource/playback/animation.ozz").unwrap();
+        let (tag, version) = Archive::peek_header(&mut file).unwrap();
+        assert_eq!(tag, "ozz-animation");
+        assert_eq!(version, 7);
+        assert_eq!(file.stream_position().unwrap(), 0);
+
+        let mut archive = Archive::from_file(file).unwrap();
+        assert_eq!(archive.tag(), tag);
+        assert!(crate::animation::Animation::from_archive(&mut archive).is_ok());
+
+        let buf = std::fs::read("./resource/playback/skeleton.ozz").unwrap();
+        let mut cursor = Cursor::new(&buf[..]);
+        let (tag, _) = Archive::peek_header(&mut cursor).unwrap();
+        assert_eq!(tag, "ozz-skeleton");
+        assert_eq!(cursor.position(), 0);
+
+        let mut cursor = Cursor::new(&buf[..4]);
+        assert!(Archive::peek_header(&mut cursor).is_err());
+        assert_eq!(cursor.position(), 0);
+    }
+
+    #[cfg(all(feature = "compression", not(feature = "wasm")))]
+    fn assert_same_animation(archive: &mut Archive<impl Read>, expected: &crate::animation::Animation) {
+        use crate::animation::Animation;
+
+        assert_eq!(archive.tag(), "ozz-animation");
+        let animation = Animation::from_archive(archive).unwrap();
+        assert_eq!(animation.duration(), expected.duration());
+        assert_eq!(animation.timepoints(), expected.timepoints());
+        assert_eq!(animation.translations(), expected.translations());
+        assert_eq!(animation.rotations(), expected.rotations());
+        assert_eq!(animation.scales(), expected.scales());
+    }
+
+    #[cfg(all(feature = "compression", not(feature = "wasm")))]
+    #[test]
+    fn test_archive_gzip() {
+        use crate::animation::Animation;
+
+        let buf = std::fs::read("./resource/playback/animation.ozz").unwrap();
+        let mut gzip = vec![0x1f, 0x8b, 8, 0x08, 0, 0, 0, 0, 0, 0xff];
+        gzip.extend_from_slice(b"animation.ozz\0");
+        gzip.extend(miniz_oxide::deflate::compress_to_vec(&buf, 6));
+        gzip.extend_from_slice(&crc32fast::hash(&buf).to_le_bytes());
+        gzip.extend_from_slice(&(buf.len() as u32).to_le_bytes());
+
+        let expected = Animation::from_archive(&mut Archive::from_vec(buf).unwrap()).unwrap();
+        assert_same_animation(&mut Archive::from_vec(gzip.clone()).unwrap(), &expected);
+        assert_same_animation(&mut Archive::from_slice(&gzip).unwrap(), &expected);
+        assert_same_animation(&mut Archive::new(Cursor::new(&gzip)).unwrap(), &expected);
+
+        // corrupted crc
+        let mut corrupted = gzip.clone();
+        let crc = corrupted.len() - 8;
+        corrupted[crc] ^= 0xff;
+        assert!(matches!(
+            Archive::from_slice(&corrupted),
+            Err(OzzError::InvalidData { field: "gzip", .. })
+        ));
+
+        // truncated
+        gzip.truncate(gzip.len() - 100);
+        assert!(matches!(
+            Archive::from_vec(gzip),
+            Err(OzzError::InvalidData { field: "gzip", .. })
+        ));
+    }
+
+    #[cfg(all(feature = "compression", not(feature = "wasm")))]
+    #[test]
+    fn test_archive_zstd() {
+        use crate::animation::Animation;
+
+        let expected = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        assert_same_animation(
+            &mut Archive::from_path("./resource/playback/animation.ozz.zst").unwrap(),
+            &expected,
+        );
+        let file = File::open("./resource/playback/animation.ozz.zst").unwrap();
+        assert_same_animation(&mut Archive::from_file(file).unwrap(), &expected);
+
+        let mut zstd = std::fs::read("./resource/playback/animation.ozz.zst").unwrap();
+        assert_same_animation(&mut Archive::from_slice(&zstd).unwrap(), &expected);
+
+        // compressed without checksum (zstd --no-check)
+        assert_same_animation(
+            &mut Archive::from_path("./resource/playback/animation_no_check.ozz.zst").unwrap(),
+            &expected,
+        );
+
+        // corrupted checksum
+        let checksum = zstd.len() - 1;
+        zstd[checksum] ^= 0xff;
+        assert!(matches!(
+            Archive::from_slice(&zstd),
+            Err(OzzError::InvalidData { field: "zstd", .. })
+        ));
+
+        // truncated
+        zstd.truncate(zstd.len() - 100);
+        assert!(matches!(
+            Archive::from_vec(zstd),
+            Err(OzzError::InvalidData { field: "zstd", .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(not(feature = "wasm"))]
+    fn test_archive_next_object_tag() {
+        use crate::animation::Animation;
+        use crate::skeleton::Skeleton;
+
+        // skeleton + animation, sharing the endian tag of the first object
+        let mut buf = std::fs::read("./resource/playback/skeleton.ozz").unwrap();
+        buf.extend_from_slice(&std::fs::read("./resource/playback/animation.ozz").unwrap()[1..]);
+
+        let mut archive = Archive::from_vec(buf).unwrap();
+        assert_eq!(archive.tag(), "ozz-skeleton");
+        let skeleton = Skeleton::from_archive(&mut archive).unwrap();
+        assert_eq!(archive.next_object_tag().unwrap(), Some("ozz-animation"));
+        assert_eq!(archive.version(), 7);
+        let animation = Animation::from_archive(&mut archive).unwrap();
+        assert_eq!(archive.next_object_tag().unwrap(), None);
+
+        let expected = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        assert_eq!(skeleton.joint_parents(), expected.joint_parents());
+        assert_eq!(skeleton.joint_rest_poses(), expected.joint_rest_poses());
+        let expected = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        assert_eq!(animation.duration(), expected.duration());
+        assert_eq!(animation.rotations(), expected.rotations());
+    }
 }
@@ -2,55 +2,83 @@
 //! Base types, traits and utils.
 //!
 
-use std::cell::{Ref, RefCell, RefMut};
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::{Ref, RefCell, RefMut};
+use core::fmt::Debug;
+use core::ops::{Deref, DerefMut};
+#[cfg(feature = "std")]
 use std::collections::hash_map::DefaultHasher;
-use std::fmt::Debug;
+#[cfg(feature = "std")]
 use std::hash::BuildHasher;
-use std::ops::{Deref, DerefMut};
-use std::rc::Rc;
-use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[cfg(feature = "std")]
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[cfg(feature = "std")]
 use thiserror::Error;
 
 /// Ozz error type.
-#[derive(Error, Debug)]
+///
+/// Implements `Display` and `std::error::Error` with the std feature only.
+#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Debug)]
 pub enum OzzError {
     /// Lock poisoned, only happens when using `Arc<RWLock<T>>` as `OzzBuf<T>`.
-    #[error("Lock poisoned")]
+    #[cfg_attr(feature = "std", error("Lock poisoned"))]
     LockPoison,
     /// Validates job failed.
-    #[error("Invalid job")]
+    #[cfg_attr(feature = "std", error("Invalid job"))]
     InvalidJob,
     /// Invalid buffer index.
-    #[error("Invalid index")]
+    #[cfg_attr(feature = "std", error("Invalid index"))]
     InvalidIndex,
+    /// Animation tracks count does not match skeleton joints count.
+    #[cfg_attr(feature = "std", error("Track mismatch: animation {animation}, skeleton {skeleton}"))]
+    TrackMismatch { animation: usize, skeleton: usize },
+    /// Skeleton joints without a matching joint (by name) when remapping an animation.
+    #[cfg_attr(feature = "std", error("Unmatched joints: {0:?}"))]
+    UnmatchedJoints(Vec<String>),
 
     /// Std io errors.
-    #[error("IO error: {0}")]
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "std", error("IO error: {0}"))]
     IO(std::io::ErrorKind),
     /// Std string errors.
-    #[error("Utf8 error: valid_up_to {0}")]
+    #[cfg_attr(feature = "std", error("Utf8 error: valid_up_to {0}"))]
     Utf8(u32),
 
     /// Read ozz archive tag error.
-    #[error("Invalid tag")]
+    #[cfg_attr(feature = "std", error("Invalid tag"))]
     InvalidTag,
     /// Read ozz archive version error.
-    #[error("Invalid version")]
+    #[cfg_attr(feature = "std", error("Invalid version"))]
     InvalidVersion,
+    /// Read ozz archive malformed count or length, like a negative or oversized key count.
+    #[cfg_attr(feature = "std", error("Invalid data: {field} {value}"))]
+    InvalidData { field: &'static str, value: i64 },
+    /// Animation key that can't be used, like a non-finite one, located by track and key index.
+    #[cfg_attr(feature = "std", error("Invalid key: {field} track {track} key {key}"))]
+    InvalidKey {
+        field: &'static str,
+        track: usize,
+        key: usize,
+    },
 
     /// Unexcepted error.
-    #[error("Unexcepted error")]
+    #[cfg_attr(feature = "std", error("Unexcepted error"))]
     Unexcepted,
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for OzzError {
     fn from(err: std::io::Error) -> Self {
         OzzError::IO(err.kind())
     }
 }
 
-impl From<std::str::Utf8Error> for OzzError {
-    fn from(err: std::str::Utf8Error) -> Self {
+impl From<core::str::Utf8Error> for OzzError {
+    fn from(err: core::str::Utf8Error) -> Self {
         OzzError::Utf8(err.valid_up_to() as u32)
     }
 }
@@ -64,6 +92,19 @@ impl OzzError {
         matches!(self, OzzError::InvalidJob)
     }
 
+    pub fn is_invalid_index(&self) -> bool {
+        matches!(self, OzzError::InvalidIndex)
+    }
+
+    pub fn is_track_mismatch(&self) -> bool {
+        matches!(self, OzzError::TrackMismatch { .. })
+    }
+
+    pub fn is_unmatched_joints(&self) -> bool {
+        matches!(self, OzzError::UnmatchedJoints(_))
+    }
+
+    #[cfg(feature = "std")]
     pub fn is_io(&self) -> bool {
         matches!(self, OzzError::IO(_))
     }
@@ -80,6 +121,14 @@ impl OzzError {
         matches!(self, OzzError::InvalidVersion)
     }
 
+    pub fn is_invalid_data(&self) -> bool {
+        matches!(self, OzzError::InvalidData { .. })
+    }
+
+    pub fn is_invalid_key(&self) -> bool {
+        matches!(self, OzzError::InvalidKey { .. })
+    }
+
     pub fn is_unexcepted(&self) -> bool {
         matches!(self, OzzError::Unexcepted)
     }
@@ -100,9 +149,11 @@ pub const SKELETON_MAX_SOA_JOINTS: i32 = (SKELETON_MAX_JOINTS + 3) / 4;
 pub const SKELETON_NO_PARENT: i32 = -1;
 
 /// A hasher builder that creates `DefaultHasher` with default keys.
+#[cfg(feature = "std")]
 #[derive(Debug, Default, Clone, Copy)]
 pub struct DeterministicState;
 
+#[cfg(feature = "std")]
 impl DeterministicState {
     /// Creates a new `DeterministicState` that builds `DefaultHasher` with default keys.
     pub const fn new() -> DeterministicState {
@@ -110,6 +161,7 @@ impl DeterministicState {
     }
 }
 
+#[cfg(feature = "std")]
 impl BuildHasher for DeterministicState {
     type Hasher = DefaultHasher;
 
@@ -376,6 +428,7 @@ impl<T> DerefMut for ObCellRefMut<'_, T> {
 // Arc<RwLock<Vec<T>>>
 //
 
+#[cfg(feature = "std")]
 impl<T: 'static + Debug + Clone> OzzBuf<T> for Arc<RwLock<Vec<T>>> {
     type Buf<'t> = ObRwLockReadGuard<'t, T>;
 
@@ -388,8 +441,10 @@ impl<T: 'static + Debug + Clone> OzzBuf<T> for Arc<RwLock<Vec<T>>> {
     }
 }
 
+#[cfg(feature = "std")]
 pub struct ObRwLockReadGuard<'t, T>(pub RwLockReadGuard<'t, Vec<T>>);
 
+#[cfg(feature = "std")]
 impl<T> Deref for ObRwLockReadGuard<'_, T> {
     type Target = [T];
 
@@ -399,6 +454,7 @@ impl<T> Deref for ObRwLockReadGuard<'_, T> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T: 'static + Debug + Clone> OzzMutBuf<T> for Arc<RwLock<Vec<T>>> {
     type MutBuf<'t> = ObRwLockWriteGuard<'t, T>;
 
@@ -411,8 +467,10 @@ impl<T: 'static + Debug + Clone> OzzMutBuf<T> for Arc<RwLock<Vec<T>>> {
     }
 }
 
+#[cfg(feature = "std")]
 pub struct ObRwLockWriteGuard<'t, T>(pub RwLockWriteGuard<'t, Vec<T>>);
 
+#[cfg(feature = "std")]
 impl<T> Deref for ObRwLockWriteGuard<'_, T> {
     type Target = [T];
 
@@ -422,6 +480,7 @@ impl<T> Deref for ObRwLockWriteGuard<'_, T> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<T> DerefMut for ObRwLockWriteGuard<'_, T> {
     #[inline(always)]
     fn deref_mut(&mut self) -> &mut Self::Target {
@@ -439,9 +498,11 @@ pub fn ozz_rc_buf<T>(v: Vec<T>) -> OzzRcBuf<T> {
 }
 
 /// Shortcuts for `Arc<RwLock<T>>`.
+#[cfg(feature = "std")]
 pub type OzzArcBuf<T> = Arc<RwLock<Vec<T>>>;
 
 /// Creates a new `Arc<RwLock<Vec<T>>>`.
+#[cfg(feature = "std")]
 #[inline]
 pub fn ozz_arc_buf<T>(v: Vec<T>) -> OzzArcBuf<T> {
     Arc::new(RwLock::new(v))
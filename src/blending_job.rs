@@ -2,10 +2,14 @@
 //! Blending job.
 //!
 
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::ops::Range;
+use core::simd::prelude::*;
 use glam::Vec4;
-use std::cell::RefCell;
-use std::rc::Rc;
-use std::simd::prelude::*;
+#[cfg(feature = "std")]
 use std::sync::{Arc, RwLock};
 
 use crate::base::{OzzBuf, OzzError, OzzMutBuf, OzzObj};
@@ -15,6 +19,10 @@ use crate::skeleton::Skeleton;
 const ZERO: f32x4 = f32x4::from_array([0.0; 4]);
 const ONE: f32x4 = f32x4::from_array([1.0; 4]);
 
+/// Number of soa joints blended by a rayon task.
+#[cfg(feature = "rayon")]
+const PARALLEL_CHUNK_SIZE: usize = 16;
+
 /// Defines a layer of blending input data (local space transforms) and parameters (weights).
 #[derive(Debug, Clone)]
 pub struct BlendingLayer<I: OzzBuf<SoaTransform>> {
@@ -60,28 +68,57 @@ impl<I: OzzBuf<SoaTransform>> BlendingLayer<I> {
             joint_weights,
         }
     }
+}
+
+// Borrowed data of a `BlendingLayer`, which can be shared between threads.
+struct LayerData<'t> {
+    transform: &'t [SoaTransform],
+    weight: f32,
+    joint_weights: &'t [Vec4],
+}
 
+impl LayerData<'_> {
+    #[inline]
     fn joint_weight(&self, idx: usize) -> f32x4 {
         fx4_from_vec4(self.joint_weights[idx])
     }
 }
 
-/// Blending context for storing intermediate blending data in `BlendingJob`.
-#[derive(Debug, Clone)]
-pub struct BlendingContext {
+// Job parameters shared by all the blended soa joints.
+struct BlendingArgs<'t> {
+    joint_rest_poses: &'t [SoaTransform],
+    threshold: f32,
+    hemisphere: bool,
+}
+
+// Blending passes state. It only depends on layer weights, so it's the same for all soa joints.
+#[derive(Debug, Default)]
+struct BlendingPasses {
     num_passes: u32,
     num_partial_passes: u32,
     accumulated_weight: f32,
+}
+
+/// Blending context for storing intermediate blending data in `BlendingJob`.
+#[derive(Debug, Clone)]
+pub struct BlendingContext {
     accumulated_weights: Vec<f32x4>,
+
+    subtree_key: Option<(usize, usize)>, // (skeleton address, root joint)
+    subtree_masks: Vec<mask32x4>,
+    subtree_range: Range<usize>,
+    subtree_base: Vec<SoaTransform>,
 }
 
 impl Default for BlendingContext {
     fn default() -> BlendingContext {
         BlendingContext {
-            num_passes: 0,
-            num_partial_passes: 0,
-            accumulated_weight: 0.0,
             accumulated_weights: Vec::new(),
+
+            subtree_key: None,
+            subtree_masks: Vec::new(),
+            subtree_range: 0..0,
+            subtree_base: Vec::new(),
         }
     }
 }
@@ -90,12 +127,34 @@ impl BlendingContext {
     /// New blending context with a given soa joints.
     pub fn new(soa_joints: usize) -> BlendingContext {
         BlendingContext {
-            num_passes: 0,
-            num_partial_passes: 0,
-            accumulated_weight: 0.0,
             accumulated_weights: vec![f32x4::splat(0.0); soa_joints],
+
+            subtree_key: None,
+            subtree_masks: Vec::new(),
+            subtree_range: 0..0,
+            subtree_base: Vec::new(),
         }
     }
+
+    /// Computes the joints of the subtree starting at `root`, only if skeleton or root changed.
+    fn update_subtree(&mut self, skeleton: &Skeleton, root: usize) {
+        let key = (skeleton as *const Skeleton as usize, root);
+        if self.subtree_key == Some(key) && self.subtree_masks.len() == skeleton.num_soa_joints() {
+            return;
+        }
+
+        self.subtree_key = Some(key);
+        self.subtree_masks.clear();
+        self.subtree_masks
+            .resize(skeleton.num_soa_joints(), mask32x4::splat(false));
+        let mut last = root;
+        skeleton.iter_depth_first(root, |joint, _| {
+            let joint = joint as usize;
+            self.subtree_masks[joint / 4].set(joint % 4, true);
+            last = joint;
+        });
+        self.subtree_range = (root / 4)..(last / 4 + 1);
+    }
 }
 
 ///
@@ -112,6 +171,9 @@ impl BlendingContext {
 /// are considered as a unit weight of 1.0, allowing to mix full and partial
 /// blend operations in a single pass.
 ///
+/// Blending can also be restricted to the joints of a subtree with `set_root_joint`. Joints outside of
+/// the subtree keep the values already in the output buffer.
+///
 #[derive(Debug)]
 pub struct BlendingJob<S = Rc<Skeleton>, I = Rc<RefCell<Vec<SoaTransform>>>, O = Rc<RefCell<Vec<SoaTransform>>>>
 where
@@ -125,10 +187,15 @@ where
     layers: Vec<BlendingLayer<I>>,
     additive_layers: Vec<BlendingLayer<I>>,
     output: Option<O>,
+    root_joint: Option<usize>,
+    hemisphere_correction: bool,
+    #[cfg(feature = "rayon")]
+    parallel: bool,
 }
 
 pub type BlendingJobRef<'t> = BlendingJob<&'t Skeleton, &'t [SoaTransform], &'t mut SoaTransform>;
 pub type BlendingJobRc<'t> = BlendingJob<Rc<Skeleton>, Rc<RefCell<Vec<SoaTransform>>>, Rc<RefCell<Vec<SoaTransform>>>>;
+#[cfg(feature = "std")]
 pub type BlendingJobArc = BlendingJob<Arc<Skeleton>, Arc<RwLock<Vec<SoaTransform>>>, Arc<RwLock<Vec<SoaTransform>>>>;
 
 impl<S, I, O> Default for BlendingJob<S, I, O>
@@ -145,6 +212,10 @@ where
             layers: Vec::new(),
             additive_layers: Vec::new(),
             output: None,
+            root_joint: None,
+            hemisphere_correction: true,
+            #[cfg(feature = "rayon")]
+            parallel: true,
         }
     }
 }
@@ -268,6 +339,60 @@ where
         self.output = None;
     }
 
+    /// Gets root joint of `BlendingJob`.
+    #[inline]
+    pub fn root_joint(&self) -> Option<usize> {
+        self.root_joint
+    }
+
+    /// Sets root joint of `BlendingJob`.
+    ///
+    /// Restricts blending to the joint and all its descendants. The other joints of the output buffer are
+    /// left untouched, so the output should be filled with the base pose before running the job.
+    /// The joint set is computed once and cached in the `BlendingContext`.
+    #[inline]
+    pub fn set_root_joint(&mut self, root_joint: usize) {
+        self.root_joint = Some(root_joint);
+    }
+
+    /// Clears root joint of `BlendingJob`, all joints are blended.
+    #[inline]
+    pub fn clear_root_joint(&mut self) {
+        self.root_joint = None;
+    }
+
+    /// Gets hemisphere correction flag of `BlendingJob`.
+    #[inline]
+    pub fn hemisphere_correction(&self) -> bool {
+        self.hemisphere_correction
+    }
+
+    /// Sets hemisphere correction flag of `BlendingJob`. Default is true.
+    ///
+    /// When enabled, each blended rotation is negated if it's not in the same hemisphere as the accumulated
+    /// rotation, so that nlerp takes the shortest path. Disabling it blends the rotations as they are.
+    #[inline]
+    pub fn set_hemisphere_correction(&mut self, hemisphere_correction: bool) {
+        self.hemisphere_correction = hemisphere_correction;
+    }
+
+    /// Gets parallel flag of `BlendingJob`.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn parallel(&self) -> bool {
+        self.parallel
+    }
+
+    /// Sets parallel flag of `BlendingJob`. Default is true.
+    ///
+    /// When enabled, soa joints are split into chunks blended by rayon tasks. Soa joints are blended
+    /// independently, so the output is the same as the serial one.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+    }
+
     /// Validates `BlendingJob` parameters.
     pub fn validate(&self) -> bool {
         (|| {
@@ -277,6 +402,7 @@ where
 
             let mut ok = self.threshold > 0.0;
             ok &= output.len() >= skeleton.num_soa_joints();
+            ok &= self.root_joint.map_or(true, |root| root < skeleton.num_joints());
 
             for layer in &self.layers {
                 ok &= layer.transform.buf().ok()?.len() >= skeleton.num_soa_joints();
@@ -306,122 +432,242 @@ where
 
         let mut ok = self.threshold > 0.0;
         ok &= output.len() >= skeleton.num_soa_joints();
+        ok &= self.root_joint.map_or(true, |root| root < skeleton.num_joints());
         if !ok {
             return Err(OzzError::InvalidJob);
         }
 
-        ctx.num_partial_passes = 0;
-        ctx.num_passes = 0;
-        ctx.accumulated_weight = 0.0;
         if ctx.accumulated_weights.len() < skeleton.num_soa_joints() {
             ctx.accumulated_weights.resize(skeleton.num_soa_joints(), ZERO);
         }
 
-        Self::blend_layers(skeleton, ctx, &self.layers, &mut output)?;
-        Self::blend_rest_pose(skeleton, ctx, self.threshold, &mut output);
-        Self::normalize(skeleton, ctx, &mut output);
-        Self::add_layers(skeleton, &self.additive_layers, &mut output)?;
+        for layer in self.layers.iter().chain(self.additive_layers.iter()) {
+            Self::validate_layer(skeleton, layer)?;
+        }
+        let args = BlendingArgs {
+            joint_rest_poses: skeleton.joint_rest_poses(),
+            threshold: self.threshold,
+            hemisphere: self.hemisphere_correction,
+        };
+
+        let range = match self.root_joint {
+            Some(root) => {
+                ctx.update_subtree(skeleton, root);
+                ctx.subtree_base.clear();
+                ctx.subtree_base.extend_from_slice(&output[ctx.subtree_range.clone()]);
+                ctx.subtree_range.clone()
+            }
+            None => 0..skeleton.num_soa_joints(),
+        };
+
+        let weights = &mut ctx.accumulated_weights[range.clone()];
+        #[cfg(feature = "rayon")]
+        if self.parallel {
+            use rayon::prelude::*;
+            // Layer buffers are borrowed once, and shared with rayon tasks.
+            let layer_bufs = Self::layer_bufs(&self.layers)?;
+            let additive_layer_bufs = Self::layer_bufs(&self.additive_layers)?;
+            let layers = Self::layer_data(&self.layers, &layer_bufs);
+            let additive_layers = Self::layer_data(&self.additive_layers, &additive_layer_bufs);
+            output[range.clone()]
+                .par_chunks_mut(PARALLEL_CHUNK_SIZE)
+                .zip(weights.par_chunks_mut(PARALLEL_CHUNK_SIZE))
+                .enumerate()
+                .for_each(|(chunk, (output, weights))| {
+                    let offset = range.start + chunk * PARALLEL_CHUNK_SIZE;
+                    let mut passes = BlendingPasses::default();
+                    for layer in &layers {
+                        Self::blend_layer(&args, layer, &mut passes, offset, weights, output);
+                    }
+                    Self::blend_rest_pose(&args, &mut passes, offset, weights, output);
+                    Self::normalize(&passes, weights, output);
+                    for layer in &additive_layers {
+                        Self::add_layer(layer, offset, output);
+                    }
+                });
+        } else {
+            Self::blend_serial(
+                &args,
+                &self.layers,
+                &self.additive_layers,
+                range.start,
+                weights,
+                &mut output[range.clone()],
+            )?;
+        }
+        #[cfg(not(feature = "rayon"))]
+        Self::blend_serial(
+            &args,
+            &self.layers,
+            &self.additive_layers,
+            range.start,
+            weights,
+            &mut output[range.clone()],
+        )?;
+
+        if self.root_joint.is_some() {
+            for (idx, base) in range.zip(ctx.subtree_base.iter()) {
+                output[idx] = Self::select_lanes(ctx.subtree_masks[idx], &output[idx], base);
+            }
+        }
         Ok(())
     }
 
-    fn blend_layers(
-        skeleton: &Skeleton,
-        ctx: &mut BlendingContext,
+    fn validate_layer(skeleton: &Skeleton, layer: &BlendingLayer<I>) -> Result<(), OzzError> {
+        if layer.transform.buf()?.len() < skeleton.num_soa_joints() {
+            return Err(OzzError::InvalidJob);
+        }
+        if !layer.joint_weights.is_empty() && layer.joint_weights.len() < skeleton.num_soa_joints() {
+            return Err(OzzError::InvalidJob);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    fn layer_bufs(layers: &[BlendingLayer<I>]) -> Result<Vec<I::Buf<'_>>, OzzError> {
+        layers.iter().map(|layer| layer.transform.buf()).collect()
+    }
+
+    #[cfg(feature = "rayon")]
+    fn layer_data<'l: 'b, 'b>(layers: &'l [BlendingLayer<I>], bufs: &'b [I::Buf<'l>]) -> Vec<LayerData<'b>> {
+        let layers = layers.iter().zip(bufs.iter());
+        layers
+            .map(|(layer, buf)| LayerData {
+                transform: buf,
+                weight: layer.weight,
+                joint_weights: &layer.joint_weights,
+            })
+            .collect()
+    }
+
+    // Blends the soa joints starting at `offset`, borrowing layer buffers one after another without allocation.
+    // `weights` and `output` are the sub-slices of these joints.
+    fn blend_serial(
+        args: &BlendingArgs<'_>,
         layers: &[BlendingLayer<I>],
+        additive_layers: &[BlendingLayer<I>],
+        offset: usize,
+        weights: &mut [f32x4],
         output: &mut [SoaTransform],
     ) -> Result<(), OzzError> {
-        let num_soa_joints = skeleton.num_soa_joints();
-
+        let mut passes = BlendingPasses::default();
         for layer in layers {
             let transform = layer.transform.buf()?;
-            if transform.len() < skeleton.num_soa_joints() {
-                return Err(OzzError::InvalidJob);
-            }
-            if !layer.joint_weights.is_empty() && layer.joint_weights.len() < skeleton.num_soa_joints() {
-                return Err(OzzError::InvalidJob);
-            }
+            let layer = LayerData {
+                transform: &transform,
+                weight: layer.weight,
+                joint_weights: &layer.joint_weights,
+            };
+            Self::blend_layer(args, &layer, &mut passes, offset, weights, output);
+        }
+        Self::blend_rest_pose(args, &mut passes, offset, weights, output);
+        Self::normalize(&passes, weights, output);
+        for layer in additive_layers {
+            let transform = layer.transform.buf()?;
+            let layer = LayerData {
+                transform: &transform,
+                weight: layer.weight,
+                joint_weights: &layer.joint_weights,
+            };
+            Self::add_layer(&layer, offset, output);
+        }
+        Ok(())
+    }
 
-            if layer.weight <= 0.0 {
-                continue;
+    fn blend_layer(
+        args: &BlendingArgs<'_>,
+        layer: &LayerData<'_>,
+        passes: &mut BlendingPasses,
+        offset: usize,
+        weights: &mut [f32x4],
+        output: &mut [SoaTransform],
+    ) {
+        if layer.weight <= 0.0 {
+            return;
+        }
+        let hemisphere = args.hemisphere;
+        passes.accumulated_weight += layer.weight;
+        let layer_weight = f32x4::splat(layer.weight);
+        let transform = &layer.transform[offset..];
+
+        if !layer.joint_weights.is_empty() {
+            passes.num_partial_passes += 1;
+
+            if passes.num_passes == 0 {
+                for (idx, out) in output.iter_mut().enumerate() {
+                    let weight = layer_weight * layer.joint_weight(offset + idx).simd_max(ZERO);
+                    weights[idx] = weight;
+                    Self::blend_1st_pass(&transform[idx], weight, out);
+                }
+            } else {
+                for (idx, out) in output.iter_mut().enumerate() {
+                    let weight = layer_weight * layer.joint_weight(offset + idx).simd_max(ZERO);
+                    weights[idx] += weight;
+                    Self::blend_n_pass(&transform[idx], weight, hemisphere, out);
+                }
             }
-            ctx.accumulated_weight += layer.weight;
-            let layer_weight = f32x4::splat(layer.weight);
-
-            if !layer.joint_weights.is_empty() {
-                ctx.num_partial_passes += 1;
-
-                if ctx.num_passes == 0 {
-                    for idx in 0..num_soa_joints {
-                        let weight = layer_weight * layer.joint_weight(idx).simd_max(ZERO);
-                        ctx.accumulated_weights[idx] = weight;
-                        Self::blend_1st_pass(&transform[idx], weight, &mut output[idx]);
-                    }
-                } else {
-                    for idx in 0..num_soa_joints {
-                        let weight = layer_weight * layer.joint_weight(idx).simd_max(ZERO);
-                        ctx.accumulated_weights[idx] += weight;
-                        Self::blend_n_pass(&transform[idx], weight, &mut output[idx]);
-                    }
+            passes.num_passes += 1;
+        } else {
+            if passes.num_passes == 0 {
+                for (idx, out) in output.iter_mut().enumerate() {
+                    weights[idx] = layer_weight;
+                    Self::blend_1st_pass(&transform[idx], layer_weight, out);
                 }
-                ctx.num_passes += 1;
             } else {
-                if ctx.num_passes == 0 {
-                    for idx in 0..num_soa_joints {
-                        ctx.accumulated_weights[idx] = layer_weight;
-                        Self::blend_1st_pass(&transform[idx], layer_weight, &mut output[idx]);
-                    }
-                } else {
-                    for idx in 0..num_soa_joints {
-                        ctx.accumulated_weights[idx] += layer_weight;
-                        Self::blend_n_pass(&transform[idx], layer_weight, &mut output[idx]);
-                    }
+                for (idx, out) in output.iter_mut().enumerate() {
+                    weights[idx] += layer_weight;
+                    Self::blend_n_pass(&transform[idx], layer_weight, hemisphere, out);
                 }
-                ctx.num_passes += 1;
             }
+            passes.num_passes += 1;
         }
-        Ok(())
     }
 
-    fn blend_rest_pose(skeleton: &Skeleton, ctx: &mut BlendingContext, threshold: f32, output: &mut [SoaTransform]) {
-        let joint_rest_poses = skeleton.joint_rest_poses();
+    fn blend_rest_pose(
+        args: &BlendingArgs<'_>,
+        passes: &mut BlendingPasses,
+        offset: usize,
+        weights: &mut [f32x4],
+        output: &mut [SoaTransform],
+    ) {
+        let joint_rest_poses = &args.joint_rest_poses[offset..offset + output.len()];
+        let (threshold, hemisphere) = (args.threshold, args.hemisphere);
 
-        if ctx.num_partial_passes == 0 {
-            let bp_weight = threshold - ctx.accumulated_weight;
+        if passes.num_partial_passes == 0 {
+            let bp_weight = threshold - passes.accumulated_weight;
             if bp_weight > 0.0 {
-                if ctx.num_passes == 0 {
-                    ctx.accumulated_weight = 1.0;
+                if passes.num_passes == 0 {
+                    passes.accumulated_weight = 1.0;
                     output.copy_from_slice(joint_rest_poses);
                 } else {
-                    ctx.accumulated_weight = threshold;
+                    passes.accumulated_weight = threshold;
                     let simd_bp_weight = f32x4::splat(bp_weight);
-                    for idx in 0..joint_rest_poses.len() {
-                        Self::blend_n_pass(&joint_rest_poses[idx], simd_bp_weight, &mut output[idx]);
+                    for (rest_pose, out) in joint_rest_poses.iter().zip(output.iter_mut()) {
+                        Self::blend_n_pass(rest_pose, simd_bp_weight, hemisphere, out);
                     }
                 }
             }
         } else {
             let simd_threshold = f32x4::splat(threshold);
-            for idx in 0..joint_rest_poses.len() {
-                let bp_weight = (simd_threshold - ctx.accumulated_weights[idx]).simd_max(ZERO);
-                ctx.accumulated_weights[idx] = simd_threshold.simd_max(ctx.accumulated_weights[idx]);
-                Self::blend_n_pass(&joint_rest_poses[idx], bp_weight, &mut output[idx]);
+            for (idx, out) in output.iter_mut().enumerate() {
+                let bp_weight = (simd_threshold - weights[idx]).simd_max(ZERO);
+                weights[idx] = simd_threshold.simd_max(weights[idx]);
+                Self::blend_n_pass(&joint_rest_poses[idx], bp_weight, hemisphere, out);
             }
         }
     }
 
-    fn normalize(skeleton: &Skeleton, ctx: &mut BlendingContext, output: &mut [SoaTransform]) {
-        let joint_rest_poses = skeleton.joint_rest_poses();
-
-        if ctx.num_partial_passes == 0 {
-            let ratio = f32x4::splat(ctx.accumulated_weight.recip());
-            for dest in output.iter_mut().take(joint_rest_poses.len()) {
+    fn normalize(passes: &BlendingPasses, weights: &[f32x4], output: &mut [SoaTransform]) {
+        if passes.num_partial_passes == 0 {
+            let ratio = f32x4::splat(passes.accumulated_weight.recip());
+            for dest in output.iter_mut() {
                 dest.translation = dest.translation.mul_num(ratio);
                 dest.rotation = dest.rotation.normalize();
                 dest.scale = dest.scale.mul_num(ratio);
             }
         } else {
-            for (idx, dest) in output.iter_mut().enumerate().take(joint_rest_poses.len()) {
-                let ratio = ctx.accumulated_weights[idx].recip();
+            for (weight, dest) in weights.iter().zip(output.iter_mut()) {
+                let ratio = weight.recip();
                 dest.translation = dest.translation.mul_num(ratio);
                 dest.rotation = dest.rotation.normalize();
                 dest.scale = dest.scale.mul_num(ratio);
@@ -429,55 +675,59 @@ where
         }
     }
 
-    fn add_layers(
-        skeleton: &Skeleton,
-        layers: &[BlendingLayer<I>],
-        output: &mut [SoaTransform],
-    ) -> Result<(), OzzError> {
-        let joint_rest_poses = skeleton.joint_rest_poses();
+    fn add_layer(layer: &LayerData<'_>, offset: usize, output: &mut [SoaTransform]) {
+        let transform = &layer.transform[offset..];
 
-        for layer in layers {
-            let transform = layer.transform.buf()?;
-            if transform.len() < skeleton.num_soa_joints() {
-                return Err(OzzError::InvalidJob);
-            }
-            if !layer.joint_weights.is_empty() && layer.joint_weights.len() < skeleton.num_soa_joints() {
-                return Err(OzzError::InvalidJob);
-            }
-
-            if layer.weight > 0.0 {
-                let layer_weight = f32x4::splat(layer.weight);
+        if layer.weight > 0.0 {
+            let layer_weight = f32x4::splat(layer.weight);
 
-                if !layer.joint_weights.is_empty() {
-                    for idx in 0..joint_rest_poses.len() {
-                        let weight = layer_weight * layer.joint_weight(idx).simd_max(ZERO);
-                        let one_minus_weight = ONE - weight;
-                        Self::blend_add_pass(&transform[idx], weight, one_minus_weight, &mut output[idx]);
-                    }
-                } else {
-                    let one_minus_weight = ONE - layer_weight;
-                    for idx in 0..joint_rest_poses.len() {
-                        Self::blend_add_pass(&transform[idx], layer_weight, one_minus_weight, &mut output[idx]);
-                    }
+            if !layer.joint_weights.is_empty() {
+                for (idx, out) in output.iter_mut().enumerate() {
+                    let weight = layer_weight * layer.joint_weight(offset + idx).simd_max(ZERO);
+                    let one_minus_weight = ONE - weight;
+                    Self::blend_add_pass(&transform[idx], weight, one_minus_weight, out);
+                }
+            } else {
+                let one_minus_weight = ONE - layer_weight;
+                for (idx, out) in output.iter_mut().enumerate() {
+                    Self::blend_add_pass(&transform[idx], layer_weight, one_minus_weight, out);
                 }
-            } else if layer.weight < 0.0 {
-                let layer_weight = f32x4::splat(-layer.weight);
+            }
+        } else if layer.weight < 0.0 {
+            let layer_weight = f32x4::splat(-layer.weight);
 
-                if !layer.joint_weights.is_empty() {
-                    for idx in 0..joint_rest_poses.len() {
-                        let weight = layer_weight * layer.joint_weight(idx).simd_max(ZERO);
-                        let one_minus_weight = ONE - weight;
-                        Self::blend_sub_pass(&transform[idx], weight, one_minus_weight, &mut output[idx]);
-                    }
-                } else {
-                    let one_minus_weight = ONE - layer_weight;
-                    for idx in 0..joint_rest_poses.len() {
-                        Self::blend_sub_pass(&transform[idx], layer_weight, one_minus_weight, &mut output[idx]);
-                    }
+            if !layer.joint_weights.is_empty() {
+                for (idx, out) in output.iter_mut().enumerate() {
+                    let weight = layer_weight * layer.joint_weight(offset + idx).simd_max(ZERO);
+                    let one_minus_weight = ONE - weight;
+                    Self::blend_sub_pass(&transform[idx], weight, one_minus_weight, out);
+                }
+            } else {
+                let one_minus_weight = ONE - layer_weight;
+                for (idx, out) in output.iter_mut().enumerate() {
+                    Self::blend_sub_pass(&transform[idx], layer_weight, one_minus_weight, out);
                 }
             }
         }
-        Ok(())
+    }
+
+    #[inline(always)]
+    fn select_lanes(mask: mask32x4, input: &SoaTransform, base: &SoaTransform) -> SoaTransform {
+        let select_vec3 = |a: &SoaVec3, b: &SoaVec3| SoaVec3 {
+            x: mask.select(a.x, b.x),
+            y: mask.select(a.y, b.y),
+            z: mask.select(a.z, b.z),
+        };
+        SoaTransform {
+            translation: select_vec3(&input.translation, &base.translation),
+            rotation: SoaQuat {
+                x: mask.select(input.rotation.x, base.rotation.x),
+                y: mask.select(input.rotation.y, base.rotation.y),
+                z: mask.select(input.rotation.z, base.rotation.z),
+                w: mask.select(input.rotation.w, base.rotation.w),
+            },
+            scale: select_vec3(&input.scale, &base.scale),
+        }
     }
 
     #[inline(always)]
@@ -488,10 +738,14 @@ where
     }
 
     #[inline(always)]
-    fn blend_n_pass(input: &SoaTransform, weight: f32x4, output: &mut SoaTransform) {
+    fn blend_n_pass(input: &SoaTransform, weight: f32x4, hemisphere: bool, output: &mut SoaTransform) {
         output.translation = output.translation.add(&input.translation.mul_num(weight));
-        let dot = output.rotation.dot(&input.rotation);
-        let rotation = input.rotation.xor_num(fx4_sign(dot));
+        let rotation = if hemisphere {
+            let dot = output.rotation.dot(&input.rotation);
+            input.rotation.xor_num(fx4_sign(dot))
+        } else {
+            input.rotation
+        };
         output.rotation = output.rotation.add(&rotation.mul_num(weight));
         output.scale = output.scale.add(&input.scale.mul_num(weight));
     }
@@ -1523,4 +1777,186 @@ mod blending_tests {
             )
         };
     }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_root_joint() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap());
+        let num_soa_joints = skeleton.num_soa_joints();
+        let layer_pose = vec![
+            SoaTransform {
+                translation: SoaVec3::splat(5.0),
+                rotation: SoaQuat::splat_quat(glam::Quat::from_rotation_x(1.0)),
+                scale: SoaVec3::splat(2.0),
+            };
+            num_soa_joints
+        ];
+
+        let output = make_buf(skeleton.joint_rest_poses().to_vec());
+        let mut job: BlendingJob = BlendingJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_output(output.clone());
+        job.layers_mut()
+            .push(BlendingLayer::with_weight(make_buf(layer_pose.clone()), 1.0));
+
+        job.set_root_joint(skeleton.num_joints());
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        for name in ["LeftArm", "RightArm"] {
+            let root = skeleton.joint_by_name(name).unwrap() as usize;
+            let mut subtree = vec![false; skeleton.num_joints()];
+            skeleton.iter_depth_first(root, |joint, _| subtree[joint as usize] = true);
+            assert!(subtree.iter().filter(|x| **x).count() > 1);
+
+            output.borrow_mut().copy_from_slice(skeleton.joint_rest_poses());
+            job.set_root_joint(root);
+            assert!(job.validate());
+            job.run().unwrap();
+
+            let output = output.borrow();
+            let rest_poses = skeleton.joint_rest_poses();
+            for (joint, in_subtree) in subtree.iter().enumerate() {
+                let (idx, lane) = (joint / 4, joint % 4);
+                let expected = if *in_subtree {
+                    &layer_pose[idx]
+                } else {
+                    &rest_poses[idx]
+                };
+                let out = &output[idx];
+                assert_eq!(
+                    out.translation.x[lane], expected.translation.x[lane],
+                    "{} {}",
+                    name, joint
+                );
+                assert_eq!(out.scale.y[lane], expected.scale.y[lane], "{} {}", name, joint);
+                assert!(
+                    (out.rotation.x[lane] - expected.rotation.x[lane]).abs() < 1e-6,
+                    "{} {}",
+                    name,
+                    joint
+                );
+                if !*in_subtree {
+                    assert_eq!(out.rotation.w[lane], expected.rotation.w[lane], "{} {}", name, joint);
+                }
+            }
+        }
+
+        job.clear_root_joint();
+        job.run().unwrap();
+        assert_eq!(output.borrow()[0].translation, SoaVec3::splat(5.0));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_hemisphere_correction() {
+        let skeleton = Rc::new(Skeleton::from_raw(&SkeletonRaw {
+            joint_rest_poses: vec![SoaTransform::IDENTITY; 1],
+            joint_names: JointHashMap::with_hashers(DeterministicState::new(), DeterministicState::new()),
+            joint_parents: vec![0; 4],
+        }));
+
+        // Nearly the same rotation, but in opposite hemispheres, the naive sum is close to 0.
+        let q0 = glam::Quat::from_rotation_z(0.01);
+        let q1 = -glam::Quat::from_rotation_z(-0.01);
+        let mut input0 = vec![SoaTransform::IDENTITY; 1];
+        input0[0].rotation = SoaQuat::splat_quat(q0);
+        let mut input1 = vec![SoaTransform::IDENTITY; 1];
+        input1[0].rotation = SoaQuat::splat_quat(q1);
+
+        let output = make_buf(vec![SoaTransform::default(); 1]);
+        let mut job: BlendingJob = BlendingJob::default();
+        assert!(job.hemisphere_correction());
+        job.set_skeleton(skeleton.clone());
+        job.set_output(output.clone());
+        job.layers_mut().push(BlendingLayer::with_weight(make_buf(input0), 0.5));
+        job.layers_mut().push(BlendingLayer::with_weight(make_buf(input1), 0.5));
+
+        job.run().unwrap();
+        let rotation = output.borrow()[0].rotation;
+        for lane in 0..4 {
+            let q = glam::Quat::from_xyzw(rotation.x[lane], rotation.y[lane], rotation.z[lane], rotation.w[lane]);
+            assert!(q.is_normalized(), "{:?}", q);
+            assert!(q.abs_diff_eq(glam::Quat::IDENTITY, 1e-5), "{:?}", q);
+        }
+
+        job.set_hemisphere_correction(false);
+        job.run().unwrap();
+        let rotation = output.borrow()[0].rotation;
+        let q = glam::Quat::from_xyzw(rotation.x[0], rotation.y[0], rotation.z[0], rotation.w[0]);
+        assert!(!q.abs_diff_eq(glam::Quat::IDENTITY, 0.5), "{:?}", q);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel() {
+        use glam::{Quat, Vec3};
+        use rand::Rng;
+
+        const NUM_JOINTS: usize = 256;
+        let mut rng = rand::thread_rng();
+        let mut random_pose = || {
+            let mut pose = vec![SoaTransform::IDENTITY; NUM_JOINTS / 4];
+            for soa in pose.iter_mut() {
+                for lane in 0..4 {
+                    let axis = Vec3::new(rng.gen(), rng.gen(), rng.gen()) + Vec3::splat(0.1);
+                    let rotation = Quat::from_axis_angle(axis.normalize(), rng.gen_range(-3.0..3.0));
+                    soa.rotation.set_quat(lane, rotation);
+                    soa.translation.x[lane] = rng.gen_range(-1.0..1.0);
+                    soa.translation.y[lane] = rng.gen_range(-1.0..1.0);
+                    soa.scale.z[lane] = rng.gen_range(0.5..1.5);
+                }
+            }
+            pose
+        };
+
+        let skeleton = Rc::new(Skeleton::from_raw(&SkeletonRaw {
+            joint_rest_poses: random_pose(),
+            joint_names: JointHashMap::with_hashers(DeterministicState::new(), DeterministicState::new()),
+            joint_parents: (0..NUM_JOINTS as i16).map(|joint| joint - 1).collect(),
+        }));
+        let joint_weights: Vec<Vec4> = (0..NUM_JOINTS / 4).map(|idx| Vec4::splat(idx as f32 / 64.0)).collect();
+
+        let mut job: BlendingJob = BlendingJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.layers_mut()
+            .push(BlendingLayer::with_weight(make_buf(random_pose()), 0.6));
+        job.layers_mut()
+            .push(BlendingLayer::with_weight(make_buf(random_pose()), 0.3));
+        let mut layer = BlendingLayer::with_joint_weights(make_buf(random_pose()), joint_weights.clone());
+        layer.weight = 0.8;
+        job.layers_mut().push(layer);
+        job.additive_layers_mut()
+            .push(BlendingLayer::with_weight(make_buf(random_pose()), 0.5));
+        let mut layer = BlendingLayer::with_joint_weights(make_buf(random_pose()), joint_weights);
+        layer.weight = -0.4;
+        job.additive_layers_mut().push(layer);
+
+        let serial = make_buf(vec![SoaTransform::default(); NUM_JOINTS / 4]);
+        job.set_output(serial.clone());
+        job.set_parallel(false);
+        job.run().unwrap();
+
+        let parallel = make_buf(vec![SoaTransform::default(); NUM_JOINTS / 4]);
+        job.set_output(parallel.clone());
+        job.set_parallel(true);
+        job.run().unwrap();
+
+        let bits = |transform: &SoaTransform| unsafe { mem::transmute::<SoaTransform, [u32; 40]>(*transform) };
+        for (serial, parallel) in serial.borrow().iter().zip(parallel.borrow().iter()) {
+            assert_eq!(bits(serial), bits(parallel));
+        }
+
+        // subtree
+        job.set_root_joint(100);
+        job.set_parallel(false);
+        job.set_output(serial.clone());
+        job.run().unwrap();
+        job.set_parallel(true);
+        job.set_output(parallel.clone());
+        job.run().unwrap();
+        for (serial, parallel) in serial.borrow().iter().zip(parallel.borrow().iter()) {
+            assert_eq!(bits(serial), bits(parallel));
+        }
+    }
 }
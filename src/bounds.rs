@@ -0,0 +1,166 @@
+//!
+//! Bounds of an animated skeleton.
+//!
+
+use core::simd::prelude::*;
+use glam::{Mat4, Vec3A};
+
+use crate::math::SoaVec3;
+
+/// Computes the axis aligned bounding box of joint origins, from model-space joint matrices.
+///
+/// Model-space matrices are usually outputted by a `LocalToModelJob`, see `compute_bounds_soa()` for joint
+/// origins in SoA layout. Returns `(min, max)`, if there's no joint then `min` is greater than `max`.
+#[inline]
+pub fn compute_bounds(model_matrices: &[Mat4]) -> (Vec3A, Vec3A) {
+    compute_bounds_with(model_matrices, None, None)
+}
+
+/// Computes the axis aligned bounding box of joint origins, from model-space joint matrices.
+///
+/// * `radiuses` - Optional radius of each joint, the box is expanded to enclose a sphere of this radius
+///   around the joint origin. Joints without radius (out of slice range) use a radius of 0.
+/// * `rendering` - Optional flag of each joint, joints flagged as false are skipped. Joints without flag
+///   (out of slice range) are rendering.
+///
+/// Returns `(min, max)`, if there's no joint then `min` is greater than `max`.
+pub fn compute_bounds_with(
+    model_matrices: &[Mat4],
+    radiuses: Option<&[f32]>,
+    rendering: Option<&[bool]>,
+) -> (Vec3A, Vec3A) {
+    let mut min = Vec3A::splat(f32::MAX);
+    let mut max = Vec3A::splat(f32::MIN);
+    for (idx, matrix) in model_matrices.iter().enumerate() {
+        if !rendering.and_then(|r| r.get(idx)).copied().unwrap_or(true) {
+            continue;
+        }
+        let radius = radiuses.and_then(|r| r.get(idx)).copied().unwrap_or(0.0);
+        let origin = Vec3A::from(matrix.w_axis.truncate());
+        min = min.min(origin - radius);
+        max = max.max(origin + radius);
+    }
+    (min, max)
+}
+
+/// Computes the axis aligned bounding box of joint origins, from model-space joint origins in SoA layout.
+///
+/// Each `SoaVec3` holds the origins of 4 consecutive joints, lanes past `num_joints` (the padding of the last
+/// `SoaVec3`) are ignored. `radiuses` and `rendering` are indexed by joint, as in `compute_bounds_with()`.
+///
+/// Returns `(min, max)`, if there's no joint then `min` is greater than `max`.
+pub fn compute_bounds_soa(
+    model_origins: &[SoaVec3],
+    num_joints: usize,
+    radiuses: Option<&[f32]>,
+    rendering: Option<&[bool]>,
+) -> (Vec3A, Vec3A) {
+    let mut min = SoaVec3::MAX;
+    let mut max = SoaVec3::MIN;
+    for (idx, origin) in model_origins.iter().enumerate() {
+        let active = mask32x4::from_array(core::array::from_fn(|lane| {
+            let joint = idx * 4 + lane;
+            joint < num_joints && rendering.and_then(|r| r.get(joint)).copied().unwrap_or(true)
+        }));
+        let radius = f32x4::from_array(core::array::from_fn(|lane| {
+            radiuses.and_then(|r| r.get(idx * 4 + lane)).copied().unwrap_or(0.0)
+        }));
+        min.x = active.select(min.x.simd_min(origin.x - radius), min.x);
+        min.y = active.select(min.y.simd_min(origin.y - radius), min.y);
+        min.z = active.select(min.z.simd_min(origin.z - radius), min.z);
+        max.x = active.select(max.x.simd_max(origin.x + radius), max.x);
+        max.y = active.select(max.y.simd_max(origin.y + radius), max.y);
+        max.z = active.select(max.z.simd_max(origin.z + radius), max.z);
+    }
+    (
+        Vec3A::new(min.x.reduce_min(), min.y.reduce_min(), min.z.reduce_min()),
+        Vec3A::new(max.x.reduce_max(), max.y.reduce_max(), max.z.reduce_max()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_compute_bounds() {
+        let (min, max) = compute_bounds(&[]);
+        assert!(min.cmpgt(max).all());
+
+        let origins = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, -2.0, 3.0),
+            Vec3::new(-4.0, 5.0, 0.5),
+            Vec3::new(2.0, 1.0, -6.0),
+        ];
+        let matrices: Vec<Mat4> = origins
+            .iter()
+            .map(|o| Mat4::from_rotation_translation(glam::Quat::from_rotation_y(1.0), *o))
+            .collect();
+
+        let (min, max) = compute_bounds(&matrices);
+        assert_eq!(min, Vec3A::new(-4.0, -2.0, -6.0));
+        assert_eq!(max, Vec3A::new(2.0, 5.0, 3.0));
+        for origin in origins {
+            assert!(Vec3A::from(origin).cmpge(min).all() && Vec3A::from(origin).cmple(max).all());
+        }
+
+        let (min, max) = compute_bounds_with(&matrices, Some(&[0.5, 1.0]), None);
+        assert_eq!(min, Vec3A::new(-4.0, -3.0, -6.0));
+        assert_eq!(max, Vec3A::new(2.0, 5.0, 4.0));
+
+        let (min, max) = compute_bounds_with(&matrices, None, Some(&[true, true, false, false]));
+        assert_eq!(min, Vec3A::new(0.0, -2.0, 0.0));
+        assert_eq!(max, Vec3A::new(1.0, 0.0, 3.0));
+
+        let (min, max) = compute_bounds_with(&matrices, None, Some(&[false; 4]));
+        assert!(min.cmpgt(max).all());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_compute_bounds_soa() {
+        let (min, max) = compute_bounds_soa(&[], 0, None, None);
+        assert!(min.cmpgt(max).all());
+
+        let origins = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, -2.0, 3.0),
+            Vec3::new(-4.0, 5.0, 0.5),
+            Vec3::new(2.0, 1.0, -6.0),
+            Vec3::new(-1.0, 7.0, 2.0),
+        ];
+        // padding lanes are far outside, they must not be enclosed
+        let soa_origins = [
+            SoaVec3::new([0.0, 1.0, -4.0, 2.0], [0.0, -2.0, 5.0, 1.0], [0.0, 3.0, 0.5, -6.0]),
+            SoaVec3::new(
+                [-1.0, 100.0, -100.0, 100.0],
+                [7.0, 100.0, -100.0, 100.0],
+                [2.0, 100.0, -100.0, 100.0],
+            ),
+        ];
+        let matrices: Vec<Mat4> = origins.iter().map(|o| Mat4::from_translation(*o)).collect();
+
+        let radiuses = [0.5, 1.0, 0.0, 0.0, 2.0, 50.0];
+        let rendering = [true, false, true, true, true, true];
+        for (radiuses, rendering) in [
+            (None, None),
+            (Some(&radiuses[..]), None),
+            (None, Some(&rendering[..])),
+            (Some(&radiuses[..]), Some(&rendering[..])),
+        ] {
+            let (min, max) = compute_bounds_soa(&soa_origins, origins.len(), radiuses, rendering);
+            let (aos_min, aos_max) = compute_bounds_with(&matrices, radiuses, rendering);
+            assert_eq!(min, aos_min);
+            assert_eq!(max, aos_max);
+        }
+
+        let (min, max) = compute_bounds_soa(&soa_origins, origins.len(), None, None);
+        assert_eq!(min, Vec3A::new(-4.0, -2.0, -6.0));
+        assert_eq!(max, Vec3A::new(2.0, 7.0, 3.0));
+    }
+}
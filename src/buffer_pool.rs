@@ -0,0 +1,203 @@
+//!
+//! Buffer Pool.
+//!
+
+use ::alloc::collections::BTreeMap;
+use ::alloc::vec;
+use ::alloc::vec::Vec;
+use glam::Mat4;
+
+use crate::math::SoaTransform;
+
+///
+/// Pool of job buffers, reusable across frames.
+///
+/// Jobs output buffers are sized to the animation or skeleton: `num_soa_tracks` (or `num_soa_joints`)
+/// `SoaTransform` for local-space poses, and `num_joints` `Mat4` for model-space matrices.
+/// Allocating them on every frame stresses the allocator. `BufferPool` keeps released buffers by length, and hands
+/// them out again on later requests of the same length.
+///
+/// Buffers are handed out with their previous content, jobs overwrite it anyway. Use them with the `XxxJobRef`
+/// job types, so `run()` only borrows them and never allocates.
+///
+#[derive(Debug, Default)]
+pub struct BufferPool {
+    transforms: Pool<SoaTransform>,
+    matrices: Pool<Mat4>,
+}
+
+impl BufferPool {
+    /// Creates an empty `BufferPool`.
+    pub fn new() -> BufferPool {
+        BufferPool::default()
+    }
+
+    /// Pre-allocates `count` buffers of `len` `SoaTransform`.
+    pub fn reserve_transforms(&mut self, len: usize, count: usize) {
+        self.transforms.reserve(len, count, SoaTransform::IDENTITY);
+    }
+
+    /// Takes a buffer of `len` `SoaTransform`, allocated only if no released buffer of this length is available.
+    pub fn take_transforms(&mut self, len: usize) -> Vec<SoaTransform> {
+        self.transforms.take(len, SoaTransform::IDENTITY)
+    }
+
+    /// Gives back a buffer of `SoaTransform` to the pool, to be reused by later `take_transforms()`.
+    pub fn release_transforms(&mut self, buffer: Vec<SoaTransform>) {
+        self.transforms.release(buffer);
+    }
+
+    /// Gets the number of released `SoaTransform` buffers of `len`, available in the pool.
+    pub fn num_free_transforms(&self, len: usize) -> usize {
+        self.transforms.num_free(len)
+    }
+
+    /// Pre-allocates `count` buffers of `len` `Mat4`.
+    pub fn reserve_matrices(&mut self, len: usize, count: usize) {
+        self.matrices.reserve(len, count, Mat4::IDENTITY);
+    }
+
+    /// Takes a buffer of `len` `Mat4`, allocated only if no released buffer of this length is available.
+    pub fn take_matrices(&mut self, len: usize) -> Vec<Mat4> {
+        self.matrices.take(len, Mat4::IDENTITY)
+    }
+
+    /// Gives back a buffer of `Mat4` to the pool, to be reused by later `take_matrices()`.
+    pub fn release_matrices(&mut self, buffer: Vec<Mat4>) {
+        self.matrices.release(buffer);
+    }
+
+    /// Gets the number of released `Mat4` buffers of `len`, available in the pool.
+    pub fn num_free_matrices(&self, len: usize) -> usize {
+        self.matrices.num_free(len)
+    }
+
+    /// Drops all released buffers.
+    pub fn clear(&mut self) {
+        self.transforms.free.clear();
+        self.matrices.free.clear();
+    }
+}
+
+#[derive(Debug)]
+struct Pool<T> {
+    free: BTreeMap<usize, Vec<Vec<T>>>,
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Pool<T> {
+        Pool { free: BTreeMap::new() }
+    }
+}
+
+impl<T: Clone> Pool<T> {
+    fn reserve(&mut self, len: usize, count: usize, value: T) {
+        let free = self.free.entry(len).or_default();
+        while free.len() < count {
+            free.push(vec![value.clone(); len]);
+        }
+    }
+
+    fn take(&mut self, len: usize, value: T) -> Vec<T> {
+        match self.free.get_mut(&len).and_then(|free| free.pop()) {
+            Some(buffer) => buffer,
+            None => vec![value; len],
+        }
+    }
+
+    fn release(&mut self, buffer: Vec<T>) {
+        self.free.entry(buffer.len()).or_default().push(buffer);
+    }
+
+    fn num_free(&self, len: usize) -> usize {
+        self.free.get(&len).map_or(0, |free| free.len())
+    }
+}
+
+#[cfg(test)]
+mod buffer_pool_tests {
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::animation::Animation;
+    use crate::local_to_model_job::{LocalToModelJob, LocalToModelJobRef};
+    use crate::sampling_job::{SamplingContext, SamplingJob, SamplingJobRef};
+    use crate::skeleton::Skeleton;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_take_release() {
+        let mut pool = BufferPool::new();
+        assert_eq!(pool.num_free_transforms(3), 0);
+
+        pool.reserve_transforms(3, 2);
+        assert_eq!(pool.num_free_transforms(3), 2);
+        let a = pool.take_transforms(3);
+        let b = pool.take_transforms(3);
+        let c = pool.take_transforms(3);
+        assert_eq!((a.len(), b.len(), c.len()), (3, 3, 3));
+        assert_eq!(pool.num_free_transforms(3), 0);
+
+        let ptr = c.as_ptr();
+        pool.release_transforms(c);
+        assert_eq!(pool.num_free_transforms(3), 1);
+        assert_eq!(pool.num_free_transforms(4), 0);
+        let d = pool.take_transforms(3);
+        assert_eq!(d.as_ptr(), ptr);
+        assert_eq!(pool.take_transforms(4).len(), 4);
+
+        pool.release_matrices(vec![Mat4::IDENTITY; 5]);
+        assert_eq!(pool.num_free_matrices(5), 1);
+        assert_eq!(pool.take_matrices(5).len(), 5);
+        pool.release_matrices(vec![Mat4::IDENTITY; 5]);
+        pool.clear();
+        assert_eq!(pool.num_free_matrices(5), 0);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_no_reallocation() {
+        let skeleton = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let mut context = SamplingContext::from_animation(&animation);
+
+        let mut pool = BufferPool::new();
+        pool.reserve_transforms(skeleton.num_soa_joints(), 1);
+        pool.reserve_matrices(skeleton.num_joints(), 1);
+        let transforms = pool.take_transforms(skeleton.num_soa_joints());
+        let matrices = pool.take_matrices(skeleton.num_joints());
+        let (transforms_ptr, transforms_cap) = (transforms.as_ptr(), transforms.capacity());
+        let (matrices_ptr, matrices_cap) = (matrices.as_ptr(), matrices.capacity());
+        pool.release_transforms(transforms);
+        pool.release_matrices(matrices);
+
+        for frame in 0..1000 {
+            let mut transforms = pool.take_transforms(skeleton.num_soa_joints());
+            let mut matrices = pool.take_matrices(skeleton.num_joints());
+            assert_eq!(
+                (transforms.as_ptr(), transforms.capacity()),
+                (transforms_ptr, transforms_cap)
+            );
+            assert_eq!((matrices.as_ptr(), matrices.capacity()), (matrices_ptr, matrices_cap));
+
+            let mut sampling_job: SamplingJobRef = SamplingJob::default();
+            sampling_job.set_animation(&animation);
+            sampling_job.set_context(&mut context);
+            sampling_job.set_output(&mut transforms);
+            sampling_job.set_ratio(frame as f32 / 1000.0);
+            sampling_job.run().unwrap();
+
+            let mut l2m_job: LocalToModelJobRef = LocalToModelJob::default();
+            l2m_job.set_skeleton(&skeleton);
+            l2m_job.set_input(&transforms);
+            l2m_job.set_output(&mut matrices);
+            l2m_job.run().unwrap();
+
+            pool.release_transforms(transforms);
+            pool.release_matrices(matrices);
+        }
+        assert_eq!(pool.num_free_transforms(skeleton.num_soa_joints()), 1);
+        assert_eq!(pool.num_free_matrices(skeleton.num_joints()), 1);
+    }
+}
@@ -0,0 +1,173 @@
+//!
+//! Foot IK, places a foot on the ground.
+//!
+
+use glam::{Mat4, Quat, Vec3A};
+
+use crate::base::OzzError;
+use crate::ik_aim_job::IKAimJob;
+use crate::ik_two_bone_job::IKTwoBoneJob;
+
+/// Output of `foot_ik()`.
+///
+/// Corrections are local-space quaternions, that must be multiplied to the local-space rotation of their
+/// respective joints: start (hip), mid (knee) and foot (ankle).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FootIK {
+    /// Start joint correction of the leg.
+    pub start_correction: Quat,
+    /// Mid joint correction of the leg.
+    pub mid_correction: Quat,
+    /// Foot joint correction, aligning the sole to the ground normal.
+    pub roll: Quat,
+    /// True if the leg reaches the ground target.
+    pub reached: bool,
+}
+
+impl Default for FootIK {
+    fn default() -> FootIK {
+        FootIK {
+            start_correction: Quat::IDENTITY,
+            mid_correction: Quat::IDENTITY,
+            roll: Quat::IDENTITY,
+            reached: false,
+        }
+    }
+}
+
+///
+/// Places a foot on the ground, by composing a two bone IK for the leg and an aim IK for the ankle.
+///
+/// `leg` must be set up with start (hip) and mid (knee) joints, mid axis and pole vector. Its end joint and
+/// target are set from `foot` (model-space matrix of the animated foot) and `ground` (model-space position
+/// the ankle should reach, ground height plus ankle height). Then the foot, moved by leg corrections, is
+/// rotated so that `sole_up` (foot local-space normalized vector, normal to the sole) aims along the ground
+/// `normal`, while keeping its heading.
+///
+/// `weight` blends between the animated foot (0) and the foot placed on the ground (1), it's used for both
+/// solves. Leg stretching isn't taken into account for the ankle roll.
+///
+pub fn foot_ik(
+    leg: &mut IKTwoBoneJob,
+    foot: Mat4,
+    ground: Vec3A,
+    normal: Vec3A,
+    sole_up: Vec3A,
+    weight: f32,
+) -> Result<FootIK, OzzError> {
+    let normal = normal.normalize_or_zero();
+    if normal == Vec3A::ZERO {
+        return Err(OzzError::InvalidJob);
+    }
+
+    leg.set_end_joint(foot);
+    leg.set_target(ground);
+    leg.set_weight(weight);
+    leg.run()?;
+    let start_correction = leg.start_joint_correction();
+    let mid_correction = leg.mid_joint_correction();
+
+    // Foot model-space matrix once leg corrections are applied.
+    let start = leg.start_joint();
+    let mid = leg.mid_joint();
+    let corrected_mid = start * Mat4::from_quat(start_correction) * start.inverse() * mid;
+    let mut corrected_foot = corrected_mid * Mat4::from_quat(mid_correction) * mid.inverse() * foot;
+    corrected_foot.w_axis = leg.corrected_end_position().extend(1.0);
+
+    // Aims sole up at the ground normal, any sole axis is kept in the same direction to preserve heading.
+    let heading = sole_up.any_orthonormal_vector();
+    let mut aim = IKAimJob::default();
+    aim.set_joint(corrected_foot);
+    aim.set_target(Vec3A::from(corrected_foot.w_axis.truncate()) + normal);
+    aim.set_forward(sole_up);
+    aim.set_up(heading);
+    aim.set_pole_vector(corrected_foot.transform_vector3a(heading));
+    aim.set_weight(weight);
+    aim.run()?;
+
+    Ok(FootIK {
+        start_correction,
+        mid_correction,
+        roll: aim.joint_correction(),
+        reached: leg.reached(),
+    })
+}
+
+#[cfg(test)]
+mod foot_ik_tests {
+    use glam::Vec3;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+
+    fn new_leg() -> IKTwoBoneJob {
+        // hip, knee slightly bent forward, ankle
+        let start = Mat4::from_translation(Vec3::new(0.0, 2.0, 0.0));
+        let mid = Mat4::from_translation(Vec3::new(0.0, 1.1, 0.2));
+        let mut leg = IKTwoBoneJob::default();
+        leg.set_start_joint(start);
+        leg.set_mid_joint(mid);
+        leg.set_mid_axis(Vec3A::X);
+        leg.set_pole_vector(Vec3A::Z);
+        leg
+    }
+
+    // Applies corrections to the chain, and returns foot model-space matrix.
+    fn corrected_foot(leg: &IKTwoBoneJob, foot: Mat4, result: &FootIK) -> Mat4 {
+        let start = leg.start_joint();
+        let mid = leg.mid_joint();
+        let corrected_mid = start * Mat4::from_quat(result.start_correction) * start.inverse() * mid;
+        corrected_mid * Mat4::from_quat(result.mid_correction) * mid.inverse() * foot * Mat4::from_quat(result.roll)
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_foot_ik() {
+        let mut leg = new_leg();
+        let foot = Mat4::from_translation(Vec3::new(0.0, 0.2, 0.0));
+
+        // tilted ground plane, within leg reach
+        let normal = Vec3A::new(0.4, 1.0, -0.2).normalize();
+        let ground = Vec3A::new(0.1, 0.25, 0.15);
+        let result = foot_ik(&mut leg, foot, ground, normal, Vec3A::Y, 1.0).unwrap();
+        assert!(result.reached);
+
+        let placed = corrected_foot(&leg, foot, &result);
+        let position = Vec3A::from(placed.w_axis.truncate());
+        assert!(position.abs_diff_eq(ground, 2e-3), "{}", position);
+        let sole_up = placed.transform_vector3a(Vec3A::Y).normalize();
+        assert!(sole_up.abs_diff_eq(normal, 2e-3), "{} {}", sole_up, normal);
+
+        // heading is preserved, sole axis is the leg corrected one projected on the ground plane
+        let heading = Vec3A::Y.any_orthonormal_vector();
+        let unrolled = corrected_foot(
+            &leg,
+            foot,
+            &FootIK {
+                roll: Quat::IDENTITY,
+                ..result
+            },
+        );
+        let expected = unrolled
+            .transform_vector3a(heading)
+            .reject_from_normalized(normal)
+            .normalize();
+        let actual = placed.transform_vector3a(heading).normalize();
+        assert!(actual.abs_diff_eq(expected, 2e-3), "{} {}", actual, expected);
+
+        // no correction
+        let result = foot_ik(&mut leg, foot, ground, normal, Vec3A::Y, 0.0).unwrap();
+        assert!(!result.reached);
+        let placed = corrected_foot(&leg, foot, &result);
+        assert!(placed.abs_diff_eq(foot, 1e-5));
+
+        // invalid normal
+        assert!(foot_ik(&mut leg, foot, ground, Vec3A::ZERO, Vec3A::Y, 1.0)
+            .unwrap_err()
+            .is_invalid_job());
+        // invalid sole up
+        assert!(foot_ik(&mut leg, foot, ground, normal, Vec3A::ONE, 1.0)
+            .unwrap_err()
+            .is_invalid_job());
+    }
+}
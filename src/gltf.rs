@@ -0,0 +1,311 @@
+//!
+//! Conversion of glTF animation channels into an `Animation`.
+//!
+
+use alloc::vec;
+use alloc::vec::Vec;
+use glam::{Quat, Vec3};
+
+use crate::animation::{Animation, Float3KeyF32, QuaternionKeyF32};
+use crate::animation_builder::AnimationBuilder;
+use crate::base::OzzError;
+
+/// Interpolation mode of a glTF animation sampler.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GltfInterpolation {
+    /// Value of the previous keyframe is held until the next one.
+    Step,
+    /// Linear interpolation for translations and scales, spherical linear interpolation for rotations.
+    #[default]
+    Linear,
+}
+
+/// A glTF animation sampler, keyframes times (in seconds) and values of a joint channel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfSampler<T> {
+    /// Interpolation mode between keyframes.
+    pub interpolation: GltfInterpolation,
+    /// Keyframes times in seconds, strictly increasing.
+    pub times: Vec<f32>,
+    /// Keyframes values, one for each time.
+    pub values: Vec<T>,
+}
+
+impl<T: Copy> GltfSampler<T> {
+    /// Creates a new `GltfSampler`.
+    pub fn new(interpolation: GltfInterpolation, times: Vec<f32>, values: Vec<T>) -> GltfSampler<T> {
+        GltfSampler {
+            interpolation,
+            times,
+            values,
+        }
+    }
+
+    /// Creates a `GltfSampler` holding a constant value, for joints without animated channel.
+    pub fn constant(value: T) -> GltfSampler<T> {
+        GltfSampler::new(GltfInterpolation::Step, vec![0.0], vec![value])
+    }
+
+    fn validate(&self) -> bool {
+        let mut ok = !self.times.is_empty() && self.times.len() == self.values.len();
+        ok &= self.times.iter().all(|time| time.is_finite());
+        ok &= self.times.windows(2).all(|pair| pair[0] < pair[1]);
+        ok
+    }
+
+    // Evaluates the sampler at `time`, values are clamped out of the keyframes time range.
+    fn evaluate(&self, time: f32, lerp: impl Fn(T, T, f32) -> T) -> T {
+        let next = self.times.partition_point(|&key_time| key_time <= time);
+        if next == 0 {
+            return self.values[0];
+        }
+        if next == self.times.len() {
+            return self.values[next - 1];
+        }
+        let prev = next - 1;
+        match self.interpolation {
+            GltfInterpolation::Step => self.values[prev],
+            GltfInterpolation::Linear => {
+                let alpha = (time - self.times[prev]) / (self.times[next] - self.times[prev]);
+                lerp(self.values[prev], self.values[next], alpha)
+            }
+        }
+    }
+
+    // Resamples keyframes to ratio space, times are clamped to 0..duration.
+    fn ratio_keys(&self, duration: f32, lerp: impl Fn(T, T, f32) -> T) -> Vec<(f32, T)> {
+        let mut times = vec![0.0];
+        times.extend(self.times.iter().copied().filter(|&time| time > 0.0 && time < duration));
+        times.push(duration);
+
+        // Index of the keyframe held by a step sampler at time (inclusive) or right before time. They differ if
+        // there's a step at time.
+        let held = |time: f32, inclusive: bool| {
+            let count = if inclusive {
+                self.times.partition_point(|&key_time| key_time <= time)
+            } else {
+                self.times.partition_point(|&key_time| key_time < time)
+            };
+            count.saturating_sub(1)
+        };
+
+        let mut keys: Vec<(f32, T)> = Vec::with_capacity(times.len() * 2);
+        for time in times {
+            let ratio = time / duration;
+            if self.interpolation == GltfInterpolation::Step && ratio > 0.0 {
+                // Runtime interpolates linearly, so a step needs a key right before it that holds the previous value.
+                let (last_ratio, _) = keys[keys.len() - 1];
+                let before = f32::from_bits(ratio.to_bits() - 1);
+                let idx = held(time, false);
+                if before > last_ratio && idx != held(time, true) {
+                    keys.push((before, self.values[idx]));
+                }
+            }
+            keys.push((ratio, self.evaluate(time, &lerp)));
+        }
+        keys
+    }
+}
+
+/// glTF animation channels of a joint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GltfChannels {
+    pub translation: GltfSampler<Vec3>,
+    pub rotation: GltfSampler<Quat>,
+    pub scale: GltfSampler<Vec3>,
+}
+
+impl Default for GltfChannels {
+    fn default() -> GltfChannels {
+        GltfChannels {
+            translation: GltfSampler::constant(Vec3::ZERO),
+            rotation: GltfSampler::constant(Quat::IDENTITY),
+            scale: GltfSampler::constant(Vec3::ONE),
+        }
+    }
+}
+
+impl Animation {
+    /// Builds an `Animation` from glTF animation channels, one `GltfChannels` for each joint (track).
+    ///
+    /// Keyframes times are clamped to 0..`duration`, and resampled to ratio space. Values at ratio 0 and 1 are
+    /// evaluated from samplers, as well as extra keys inserted right before steps of `GltfInterpolation::Step`
+    /// samplers, since runtime always interpolates linearly. Rotations are normalized-lerped at runtime, which
+    /// slightly differs from glTF spherical interpolation between keys far apart. Rotation keys are negated when
+    /// needed to stay in the hemisphere of the previous key, so that sign-flipped source keys don't spin the long
+    /// way.
+    ///
+    /// Returns `OzzError::InvalidJob` if duration isn't positive, or if a sampler has no keyframe, unmatched
+    /// times and values counts, or non strictly increasing times.
+    pub fn from_gltf_channels(duration: f32, joints: &[GltfChannels]) -> Result<Animation, OzzError> {
+        if !(duration > 0.0 && duration.is_finite()) {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let mut builder = AnimationBuilder::new(duration, joints.len());
+        for (track, channels) in joints.iter().enumerate() {
+            let ok = channels.translation.validate() && channels.rotation.validate() && channels.scale.validate();
+            if !ok {
+                return Err(OzzError::InvalidJob);
+            }
+
+            for (ratio, value) in channels.translation.ratio_keys(duration, Vec3::lerp) {
+                builder.push_translation(track, ratio, Float3KeyF32::from(value))?;
+            }
+            // Runtime nlerp doesn't take the shortest path, so keys are flipped to the previous key hemisphere.
+            let mut previous: Option<Quat> = None;
+            for (ratio, mut value) in channels.rotation.ratio_keys(duration, Quat::slerp) {
+                if previous.is_some_and(|previous| previous.dot(value) < 0.0) {
+                    value = -value;
+                }
+                previous = Some(value);
+                builder.push_rotation(track, ratio, QuaternionKeyF32::from(value))?;
+            }
+            for (ratio, value) in channels.scale.ratio_keys(duration, Vec3::lerp) {
+                builder.push_scale(track, ratio, Float3KeyF32::from(value))?;
+            }
+        }
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod gltf_tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::math::SoaTransform;
+    use crate::sampling_job::{SamplingContext, SamplingJob};
+
+    fn sample(animation: &Rc<Animation>, time: f32) -> Vec<(Vec3, Quat, Vec3)> {
+        let output = Rc::new(RefCell::new(vec![SoaTransform::default(); animation.num_soa_tracks()]));
+        let mut job: SamplingJob = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::from_animation(animation));
+        job.set_output(output.clone());
+        job.set_time(time).unwrap();
+        job.run().unwrap();
+
+        let output = output.borrow();
+        (0..animation.num_tracks())
+            .map(|track| {
+                let soa = &output[track / 4];
+                let lane = track % 4;
+                let vec3 = |v: &crate::math::SoaVec3| Vec3::new(v.x[lane], v.y[lane], v.z[lane]);
+                (vec3(&soa.translation), soa.rotation.quat(lane), vec3(&soa.scale))
+            })
+            .collect()
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_invalid() {
+        let mut channels = GltfChannels::default();
+        assert!(Animation::from_gltf_channels(0.0, &[channels.clone()]).is_err());
+        assert!(Animation::from_gltf_channels(1.0, &[]).is_err());
+
+        channels.translation.times = vec![0.0, 1.0];
+        assert!(Animation::from_gltf_channels(1.0, &[channels.clone()]).is_err());
+
+        channels.translation.values = vec![Vec3::ZERO, Vec3::ONE];
+        assert!(Animation::from_gltf_channels(1.0, &[channels.clone()]).is_ok());
+
+        channels.translation.times = vec![1.0, 0.0];
+        assert!(Animation::from_gltf_channels(1.0, &[channels.clone()]).is_err());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_from_gltf_channels() {
+        let up = Quat::from_rotation_x(1.0);
+        let joint0 = GltfChannels {
+            translation: GltfSampler::new(
+                GltfInterpolation::Linear,
+                vec![0.0, 0.5, 2.0],
+                vec![Vec3::ZERO, Vec3::new(1.0, 2.0, 0.0), Vec3::new(3.0, 0.0, -1.0)],
+            ),
+            rotation: GltfSampler::new(GltfInterpolation::Linear, vec![0.5, 2.0], vec![Quat::IDENTITY, up]),
+            scale: GltfSampler::constant(Vec3::splat(2.0)),
+        };
+        let joint1 = GltfChannels {
+            // times out of duration are clamped
+            translation: GltfSampler::new(
+                GltfInterpolation::Step,
+                vec![0.0, 1.0, 3.0],
+                vec![Vec3::X, Vec3::Y, Vec3::Z],
+            ),
+            rotation: GltfSampler::new(GltfInterpolation::Step, vec![0.0, 1.5], vec![up, Quat::IDENTITY]),
+            scale: GltfSampler::new(
+                GltfInterpolation::Linear,
+                vec![-1.0, 1.0],
+                vec![Vec3::ZERO, Vec3::splat(4.0)],
+            ),
+        };
+        let animation = Rc::new(Animation::from_gltf_channels(2.0, &[joint0.clone(), joint1.clone()]).unwrap());
+        assert_eq!(animation.duration(), 2.0);
+        assert_eq!(animation.num_tracks(), 2);
+
+        for time in [0.0, 0.5, 1.0, 1.5, 2.0, 0.25, 0.75, 1.25, 1.75] {
+            let pose = sample(&animation, time);
+            for (joint, channels) in [&joint0, &joint1].into_iter().enumerate() {
+                let (translation, rotation, scale) = pose[joint];
+                let expected = channels.translation.evaluate(time, Vec3::lerp);
+                assert!(
+                    translation.abs_diff_eq(expected, 2e-3),
+                    "{} {} {}",
+                    time,
+                    translation,
+                    expected
+                );
+                // Runtime nlerp slightly differs from slerp between keys.
+                let expected = channels.rotation.evaluate(time, Quat::slerp);
+                let epsilon = if channels.rotation.times.contains(&time) {
+                    2e-3
+                } else {
+                    1e-2
+                };
+                assert!(
+                    rotation.angle_between(expected) < epsilon,
+                    "{} {} {}",
+                    time,
+                    rotation,
+                    expected
+                );
+                let expected = channels.scale.evaluate(time, Vec3::lerp);
+                assert!(scale.abs_diff_eq(expected, 2e-3), "{} {} {}", time, scale, expected);
+            }
+        }
+
+        // steps are held until key time
+        let pose = sample(&animation, 0.999);
+        assert!(pose[1].0.abs_diff_eq(Vec3::X, 2e-3));
+        let pose = sample(&animation, 1.499);
+        assert!(pose[1].1.angle_between(up) < 2e-3);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_sign_flipped_rotations() {
+        // Same rotations as 0, 0.5 and 1 radian around x, with the middle key in the opposite hemisphere.
+        let keys = vec![Quat::IDENTITY, -Quat::from_rotation_x(0.5), Quat::from_rotation_x(1.0)];
+        let channels = GltfChannels {
+            rotation: GltfSampler::new(GltfInterpolation::Linear, vec![0.0, 1.0, 2.0], keys),
+            ..Default::default()
+        };
+        let animation = Rc::new(Animation::from_gltf_channels(2.0, &[channels]).unwrap());
+
+        for time in [0.0, 0.25, 0.5, 0.75, 1.0, 1.25, 1.5, 1.75, 2.0] {
+            let rotation = sample(&animation, time)[0].1;
+            let expected = Quat::from_rotation_x(time * 0.5);
+            assert!(
+                rotation.angle_between(expected) < 2e-3,
+                "{} {} {}",
+                time,
+                rotation,
+                expected
+            );
+        }
+    }
+}
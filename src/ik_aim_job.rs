@@ -2,9 +2,8 @@
 //! Aim IK Job.
 //!
 
+use core::simd::prelude::*;
 use glam::{Mat4, Quat, Vec3A};
-use std::simd::prelude::*;
-use std::simd::StdFloat;
 
 use crate::base::OzzError;
 use crate::math::*;
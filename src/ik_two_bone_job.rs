@@ -2,13 +2,16 @@
 //! Two bone IK job.
 //!
 
+use core::simd::prelude::*;
 use glam::{Mat4, Quat, Vec3A};
-use std::simd::prelude::*;
-use std::simd::StdFloat;
 
-use crate::base::OzzError;
+use crate::base::{OzzError, OzzIndex};
 use crate::math::*;
 
+const PLANE_NORMAL_EPSILON: f32x4 = f32x4::from_array([1e-12; 4]);
+const DEGENERATE_SCALE_EPSILON: f32 = 1e-12;
+const WEIGHT_LERP_EPSILON: f32 = 1e-16;
+
 #[derive(Debug)]
 struct IKConstantSetup {
     inv_start_joint: AosMat4,
@@ -18,10 +21,18 @@ struct IKConstantSetup {
     start_mid_ss_len2: f32x4,
     mid_end_ss_len2: f32x4,
     start_end_ss_len2: f32x4,
+    degenerate_scale: bool,
 }
 
 impl IKConstantSetup {
     fn new(job: &IKTwoBoneJob) -> IKConstantSetup {
+        // Determinant of the 3x3 part, near zero if the joint is (nearly) zero scaled on any axis.
+        let ill_conditioned = |joint: &AosMat4| {
+            let det = vec3_dot_s(vec3_cross(joint.cols[0], joint.cols[1]), joint.cols[2])[0];
+            det.is_nan() || det.abs() < DEGENERATE_SCALE_EPSILON
+        };
+        let degenerate_scale = ill_conditioned(&job.start_joint) || ill_conditioned(&job.mid_joint);
+
         let inv_start_joint = job.start_joint.invert();
         let inv_mid_joint = job.mid_joint.invert();
 
@@ -43,6 +54,7 @@ impl IKConstantSetup {
             start_mid_ss_len2: vec3_length2_s(start_mid_ss), // [x]
             mid_end_ss_len2: vec3_length2_s(mid_end_ss),     // [x]
             start_end_ss_len2: vec3_length2_s(start_end_ss), // [x]
+            degenerate_scale,
         }
     }
 }
@@ -64,8 +76,10 @@ pub struct IKTwoBoneJob {
     mid_axis: f32x4,
     pole_vector: f32x4,
     twist_angle: f32,
+    twist_limits: (f32, f32),
     soften: f32,
     weight: f32,
+    stretch: f32,
     start_joint: AosMat4,
     mid_joint: AosMat4,
     end_joint: AosMat4,
@@ -73,6 +87,12 @@ pub struct IKTwoBoneJob {
     start_joint_correction: f32x4,
     mid_joint_correction: f32x4,
     reached: bool,
+    geometric_reached: bool,
+    degenerate_scale: bool,
+    reach_delta: f32,
+    stretch_factor: f32,
+    corrected_end_position: f32x4,
+    effective_target: f32x4,
 }
 
 impl Default for IKTwoBoneJob {
@@ -82,14 +102,22 @@ impl Default for IKTwoBoneJob {
             mid_axis: Z_AXIS,
             pole_vector: Y_AXIS,
             twist_angle: 0.0,
+            twist_limits: (f32::NEG_INFINITY, f32::INFINITY),
             soften: 1.0,
             weight: 1.0,
+            stretch: 1.0,
             start_joint: AosMat4::identity(),
             mid_joint: AosMat4::identity(),
             end_joint: AosMat4::identity(),
             start_joint_correction: QUAT_UNIT,
             mid_joint_correction: QUAT_UNIT,
             reached: false,
+            geometric_reached: false,
+            degenerate_scale: false,
+            reach_delta: 0.0,
+            stretch_factor: 1.0,
+            corrected_end_position: ZERO,
+            effective_target: ZERO,
         }
     }
 }
@@ -109,6 +137,20 @@ impl IKTwoBoneJob {
         self.target = fx4_from_vec3a(target);
     }
 
+    /// Gets target of `IKTwoBoneJob` as `f32x4`, w component is undefined.
+    #[inline]
+    pub fn target_simd(&self) -> f32x4 {
+        self.target
+    }
+
+    /// Sets target of `IKTwoBoneJob` from `f32x4`, w component is ignored.
+    ///
+    /// See `set_target()`.
+    #[inline]
+    pub fn set_target_simd(&mut self, target: f32x4) {
+        self.target = target;
+    }
+
     /// Gets mid axis of `IKTwoBoneJob`
     #[inline]
     pub fn mid_axis(&self) -> Vec3A {
@@ -129,6 +171,42 @@ impl IKTwoBoneJob {
         self.mid_axis = fx4_from_vec3a(mid_axis);
     }
 
+    /// Sets mid axis of `IKTwoBoneJob`, normalizing it eagerly.
+    ///
+    /// Returns `OzzError::InvalidJob` and leaves mid axis unchanged if `mid_axis` is zero, near-zero or not
+    /// finite. See `set_mid_axis()`.
+    #[inline]
+    pub fn try_set_mid_axis(&mut self, mid_axis: Vec3A) -> Result<(), OzzError> {
+        let mid_axis = mid_axis
+            .try_normalize()
+            .filter(|axis| axis.is_normalized())
+            .ok_or(OzzError::InvalidJob)?;
+        self.mid_axis = fx4_from_vec3a(mid_axis);
+        Ok(())
+    }
+
+    /// Sets mid axis of `IKTwoBoneJob`, normalizing it silently.
+    ///
+    /// A zero or non finite `mid_axis` is set as zero, so job validation will fail. See `set_mid_axis()`.
+    #[inline]
+    pub fn set_mid_axis_normalized(&mut self, mid_axis: Vec3A) {
+        self.mid_axis = fx4_from_vec3a(mid_axis.normalize_or_zero());
+    }
+
+    /// Gets mid axis of `IKTwoBoneJob` as `f32x4`, w component is undefined.
+    #[inline]
+    pub fn mid_axis_simd(&self) -> f32x4 {
+        self.mid_axis
+    }
+
+    /// Sets mid axis of `IKTwoBoneJob` from `f32x4`, w component is ignored.
+    ///
+    /// See `set_mid_axis()`.
+    #[inline]
+    pub fn set_mid_axis_simd(&mut self, mid_axis: f32x4) {
+        self.mid_axis = mid_axis;
+    }
+
     /// Gets pole vector of `IKTwoBoneJob`.
     #[inline]
     pub fn pole_vector(&self) -> Vec3A {
@@ -148,6 +226,20 @@ impl IKTwoBoneJob {
         self.pole_vector = fx4_from_vec3a(pole_vector);
     }
 
+    /// Gets pole vector of `IKTwoBoneJob` as `f32x4`, w component is undefined.
+    #[inline]
+    pub fn pole_vector_simd(&self) -> f32x4 {
+        self.pole_vector
+    }
+
+    /// Sets pole vector of `IKTwoBoneJob` from `f32x4`, w component is ignored.
+    ///
+    /// See `set_pole_vector()`.
+    #[inline]
+    pub fn set_pole_vector_simd(&mut self, pole_vector: f32x4) {
+        self.pole_vector = pole_vector;
+    }
+
     /// Gets twist angle of `IKTwoBoneJob`.
     #[inline]
     pub fn twist_angle(&self) -> f32 {
@@ -162,6 +254,21 @@ impl IKTwoBoneJob {
         self.twist_angle = twist_angle;
     }
 
+    /// Gets twist limits of `IKTwoBoneJob`, as `(min, max)`.
+    #[inline]
+    pub fn twist_limits(&self) -> (f32, f32) {
+        self.twist_limits
+    }
+
+    /// Sets twist limits of `IKTwoBoneJob`.
+    ///
+    /// Twist angle is clamped in range `min`-`max` when the job runs, to avoid twisting the chain too far.
+    /// Limits are swapped if `min` is greater than `max`. Default is `-inf`-`inf`, which disables clamping.
+    #[inline]
+    pub fn set_twist_limits(&mut self, min: f32, max: f32) {
+        self.twist_limits = if min <= max { (min, max) } else { (max, min) };
+    }
+
     /// Gets soften of `IKTwoBoneJob`.
     #[inline]
     pub fn soften(&self) -> f32 {
@@ -174,9 +281,15 @@ impl IKTwoBoneJob {
     /// joint chain from snapping into the final position, softening the final degrees before the joint
     /// chain becomes flat. This ratio represents the distance to the end, from which softening is
     /// starting.
+    ///
+    /// Soften is clamped in range 0.0-1.0, NaN is set to 0.0. 1.0 (the default) disables softening, as it
+    /// starts at the end of the chain. 0.0 softens fully, from the start of the chain.
+    ///
+    /// Corrections are continuous across the softening onset, only `reached()` switches to false there, as
+    /// the chain falls behind the target.
     #[inline]
     pub fn set_soften(&mut self, soften: f32) {
-        self.soften = soften;
+        self.soften = f32_clamp_or_min(soften, 0.0, 1.0);
     }
 
     /// Gets weight of `IKTwoBoneJob`.
@@ -194,6 +307,25 @@ impl IKTwoBoneJob {
         self.weight = weight;
     }
 
+    /// Gets stretch of `IKTwoBoneJob`.
+    #[inline]
+    pub fn stretch(&self) -> f32 {
+        self.stretch
+    }
+
+    /// Sets stretch of `IKTwoBoneJob`.
+    ///
+    /// Maximum ratio the two bones can be stretched by to reach a target beyond chain length. Default is 1.0,
+    /// which disables stretching. Values less than 1.0 are considered as 1.0.
+    ///
+    /// The job doesn't modify the bones, the required scale is outputted as `stretch_factor()`. Both bone
+    /// lengths must be scaled by this factor for the end joint to reach the target.
+    /// Soften ratio is applied to the stretched chain, so the target is reached only if soften is 1.0.
+    #[inline]
+    pub fn set_stretch(&mut self, max_ratio: f32) {
+        self.stretch = max_ratio;
+    }
+
     /// Gets start joint of `IKTwoBoneJob`
     #[inline]
     pub fn start_joint(&self) -> Mat4 {
@@ -239,6 +371,24 @@ impl IKTwoBoneJob {
         self.end_joint = end_joint.into();
     }
 
+    /// Gets model-space position of start joint of `IKTwoBoneJob`.
+    #[inline]
+    pub fn start_position(&self) -> Vec3A {
+        fx4_to_vec3a(self.start_joint.cols[3])
+    }
+
+    /// Gets model-space position of mid joint of `IKTwoBoneJob`.
+    #[inline]
+    pub fn mid_position(&self) -> Vec3A {
+        fx4_to_vec3a(self.mid_joint.cols[3])
+    }
+
+    /// Gets model-space position of end joint of `IKTwoBoneJob`, before correction.
+    #[inline]
+    pub fn end_position(&self) -> Vec3A {
+        fx4_to_vec3a(self.end_joint.cols[3])
+    }
+
     /// Gets **output** start joint correction of `IKTwoBoneJob`.
     ///
     /// Local-space corrections to apply to start joints in order for end joint to reach target position.
@@ -249,6 +399,12 @@ impl IKTwoBoneJob {
         fx4_to_quat(self.start_joint_correction)
     }
 
+    /// Gets **output** start joint correction of `IKTwoBoneJob` as `f32x4` quaternion (x, y, z, w).
+    #[inline]
+    pub fn start_joint_correction_simd(&self) -> f32x4 {
+        self.start_joint_correction
+    }
+
     /// Clears start joint correction of `IKTwoBoneJob`.
     #[inline]
     pub fn clear_start_joint_correction(&mut self) {
@@ -265,6 +421,12 @@ impl IKTwoBoneJob {
         fx4_to_quat(self.mid_joint_correction)
     }
 
+    /// Gets **output** mid joint correction of `IKTwoBoneJob` as `f32x4` quaternion (x, y, z, w).
+    #[inline]
+    pub fn mid_joint_correction_simd(&self) -> f32x4 {
+        self.mid_joint_correction
+    }
+
     /// Clears mid joint correction of `IKTwoBoneJob`.
     #[inline]
     pub fn clear_mid_joint_correction(&mut self) {
@@ -277,6 +439,12 @@ impl IKTwoBoneJob {
     ///
     /// Reachability is driven by bone chain length, soften ratio and target distance.
     /// Target is considered unreached if weight is less than 1.
+    ///
+    /// As softening slows down the chain before it's fully extended, a target within chain length can be
+    /// unreached if soften is less than 1. See `geometric_reached()` to ignore softening.
+    ///
+    /// An unreached target is still used for orientation: the (straightened) chain points toward it, so
+    /// `corrected_end_position()` lies on the start joint to target line.
     #[inline]
     pub fn reached(&self) -> bool {
         self.reached
@@ -288,12 +456,155 @@ impl IKTwoBoneJob {
         self.reached = false;
     }
 
+    /// Gets **output** geometric reached of `IKTwoBoneJob`.
+    ///
+    /// True if start to target distance is within what the bone chain can cover, that is between the
+    /// difference and the sum of the bone lengths (the sum being scaled by `stretch()` if stretching is
+    /// enabled). Unlike `reached()`, soften ratio and weight aren't taken into account, which suits gameplay
+    /// decisions, like whether a ledge can be grabbed, independently of how the chain is visually softened.
+    #[inline]
+    pub fn geometric_reached(&self) -> bool {
+        self.geometric_reached
+    }
+
+    /// Clears geometric reached of `IKTwoBoneJob`.
+    #[inline]
+    pub fn clear_geometric_reached(&mut self) {
+        self.geometric_reached = false;
+    }
+
+    /// Gets **output** degenerate scale flag of `IKTwoBoneJob`.
+    ///
+    /// True if start or middle joint matrix is (nearly) zero scaled on an axis, so it can't be inverted reliably
+    /// and corrections may be meaningless. Callers can skip applying corrections in this case, which are
+    /// computed as usual anyway. Negative scales aren't degenerate. It's false if weight is less or equal than 0.
+    #[inline]
+    pub fn had_degenerate_scale(&self) -> bool {
+        self.degenerate_scale
+    }
+
+    /// Clears degenerate scale flag of `IKTwoBoneJob`.
+    #[inline]
+    pub fn clear_degenerate_scale(&mut self) {
+        self.degenerate_scale = false;
+    }
+
+    /// Gets **output** reach delta of `IKTwoBoneJob`.
+    ///
+    /// Signed distance between the furthest position the end joint can reach and the target, along the
+    /// start-to-target direction. Zero or negative means the target is within reach, positive is the distance
+    /// the chain falls short of the target (either too far, or too close for the chain to fold).
+    ///
+    /// Distance is expressed in start joint space, it's also model-space distance if start joint isn't scaled.
+    /// Soften ratio is taken into account. It's 0 if weight is less or equal than 0.
+    #[inline]
+    pub fn reach_delta(&self) -> f32 {
+        self.reach_delta
+    }
+
+    /// Clears reach delta of `IKTwoBoneJob`.
+    #[inline]
+    pub fn clear_reach_delta(&mut self) {
+        self.reach_delta = 0.0;
+    }
+
+    /// Gets **output** stretch factor of `IKTwoBoneJob`.
+    ///
+    /// Uniform scale to apply to both bone lengths (start to middle, and middle to end joints) in order for end
+    /// joint to reach target position. It's 1.0 if target is within chain length or if stretching is disabled,
+    /// and never exceeds `stretch()`. Weight is taken into account.
+    #[inline]
+    pub fn stretch_factor(&self) -> f32 {
+        self.stretch_factor
+    }
+
+    /// Clears stretch factor of `IKTwoBoneJob`.
+    #[inline]
+    pub fn clear_stretch_factor(&mut self) {
+        self.stretch_factor = 1.0;
+    }
+
+    /// Gets **output** corrected end position of `IKTwoBoneJob`.
+    ///
+    /// Model-space position of the end joint once start and mid joint corrections (and stretch factor) are
+    /// applied, which is the target if it's reached. Weight is taken into account. Useful to draw the actual
+    /// reach of the chain.
+    #[inline]
+    pub fn corrected_end_position(&self) -> Vec3A {
+        fx4_to_vec3a(self.corrected_end_position)
+    }
+
+    /// Gets **output** corrected end position of `IKTwoBoneJob` as `f32x4`, w component is undefined.
+    #[inline]
+    pub fn corrected_end_position_simd(&self) -> f32x4 {
+        self.corrected_end_position
+    }
+
+    /// Clears corrected end position of `IKTwoBoneJob`.
+    #[inline]
+    pub fn clear_corrected_end_position(&mut self) {
+        self.corrected_end_position = ZERO;
+    }
+
+    /// Gets **output** effective target of `IKTwoBoneJob`.
+    ///
+    /// Model-space position the chain aimed at: the target pulled towards the start joint by softening, or the
+    /// target itself when softening isn't active. It's the target too if weight is 0, as nothing is solved.
+    /// Useful to draw both the requested and the softened targets.
+    #[inline]
+    pub fn effective_target(&self) -> Vec3A {
+        fx4_to_vec3a(self.effective_target)
+    }
+
+    /// Gets **output** effective target of `IKTwoBoneJob` as `f32x4`, w component is undefined.
+    #[inline]
+    pub fn effective_target_simd(&self) -> f32x4 {
+        self.effective_target
+    }
+
+    /// Clears effective target of `IKTwoBoneJob`.
+    #[inline]
+    pub fn clear_effective_target(&mut self) {
+        self.effective_target = ZERO;
+    }
+
     /// Clears all outputs of `IKTwoBoneJob`.
     #[inline]
     pub fn clear_outs(&mut self) {
         self.clear_start_joint_correction();
         self.clear_mid_joint_correction();
         self.clear_reached();
+        self.clear_geometric_reached();
+        self.clear_degenerate_scale();
+        self.clear_reach_delta();
+        self.clear_stretch_factor();
+        self.clear_corrected_end_position();
+        self.clear_effective_target();
+    }
+
+    /// Creates an `IKTwoBoneJobBuilder`, to set up a job fluently.
+    #[inline]
+    pub fn builder() -> IKTwoBoneJobBuilder {
+        IKTwoBoneJobBuilder::new()
+    }
+
+    /// Creates an `IKTwoBoneJob` with start, mid and end joints matrices taken from a skeleton model-space pose.
+    ///
+    /// `model_matrices` is usually the output of `LocalToModelJob`, indexed by skeleton joint indices. Other
+    /// parameters are the defaults, so mid axis and target still need to be set.
+    /// Returns `OzzError::InvalidIndex` if any joint index is out of `model_matrices` range.
+    pub fn from_skeleton(
+        model_matrices: &[Mat4],
+        start: impl OzzIndex,
+        mid: impl OzzIndex,
+        end: impl OzzIndex,
+    ) -> Result<IKTwoBoneJob, OzzError> {
+        let joint = |idx: &dyn OzzIndex| model_matrices.get(idx.usize()).copied().ok_or(OzzError::InvalidIndex);
+        let mut job = IKTwoBoneJob::default();
+        job.set_start_joint(joint(&start)?);
+        job.set_mid_joint(joint(&mid)?);
+        job.set_end_joint(joint(&end)?);
+        Ok(job)
     }
 
     /// Validates `IKTwoBoneJob` parameters.
@@ -302,32 +613,216 @@ impl IKTwoBoneJob {
         vec3_is_normalized(self.mid_axis)
     }
 
+    /// Validates `IKTwoBoneJob` parameters, with a custom tolerance on mid axis normalization.
+    ///
+    /// `epsilon` is the tolerance on the squared length of mid axis, `run()` uses `NORMALIZATION_TOLERANCE_SQ`.
+    /// Useful to check axes computed from low precision data, before normalizing them if needed.
+    #[inline]
+    pub fn validate_with_epsilon(&self, epsilon: f32) -> bool {
+        vec3_is_normalized_with(self.mid_axis, epsilon)
+    }
+
+    /// Applies **output** corrections to local-space rotations of start and middle joints.
+    ///
+    /// Corrections are local-space, so they are multiplied on the right, as ozz does:
+    /// `*local_start = *local_start * start_joint_correction()`, and likewise for mid joint.
+    #[inline]
+    pub fn apply_corrections(&self, local_start: &mut Quat, local_mid: &mut Quat) {
+        *local_start *= self.start_joint_correction();
+        *local_mid *= self.mid_joint_correction();
+    }
+
+    /// Checks if the target can be reached, without computing corrections.
+    ///
+    /// Returns the same value as `reached()` after `run()`, with the current inputs. Only the bones chain
+    /// length, stretching and softening are compared to the start to target distance. Returns false if the
+    /// job is invalid.
+    pub fn can_reach(&self) -> bool {
+        if !self.validate() || self.weight < 1.0 {
+            return false;
+        }
+        let setup = IKConstantSetup::new(self);
+        let (_, reached, _, _, _) = self.stretch_soften_target(&setup);
+        reached
+    }
+
+    /// Gets the inner and outer radii of the reachable targets, as `(min, max)` distances from the start joint.
+    ///
+    /// Reachable targets lie in the spherical shell centered on the start joint, from `|l1 - l2|` to `l1 + l2`,
+    /// `l1` and `l2` being the start to mid and mid to end bones lengths, in start joint space (so model-space
+    /// unless start joint is scaled). Outer radius is scaled by `stretch()` when stretching is enabled. It's
+    /// pure geometry from the current joints matrices, targets in the shell set `geometric_reached()`, while
+    /// `reached()` also accounts for softening and weight.
+    pub fn reach_bounds(&self) -> (f32, f32) {
+        self.compute_reach_bounds(&IKConstantSetup::new(self))
+    }
+
     /// Runs two bone IK job's task.
     /// The validate job before any operation is performed.
     pub fn run(&mut self) -> Result<(), OzzError> {
         if !self.validate() {
             return Err(OzzError::InvalidJob);
         }
+        self.solve();
+        Ok(())
+    }
+
+    /// Runs the tasks of a batch of jobs, like many characters' arm or leg chains.
+    ///
+    /// All jobs are validated before any is run, so that no job is run if any is invalid, in which case
+    /// `OzzError::InvalidJob` is returned. Results are the same as calling `run()` on each job.
+    pub fn run_batch(jobs: &mut [IKTwoBoneJob]) -> Result<(), OzzError> {
+        if !jobs.iter().all(|job| job.validate()) {
+            return Err(OzzError::InvalidJob);
+        }
+        for job in jobs.iter_mut() {
+            job.solve();
+        }
+        Ok(())
+    }
+
+    /// Runs the tasks of an ordered chain of jobs, like a spine made of several two bone segments, with
+    /// weights distributed along the chain.
+    ///
+    /// Each job's weight is set to `falloff(t)`, `t` being the position of the segment end along the chain,
+    /// in range (0.0, 1.0]: `(idx + 1) / jobs.len()`. A linear falloff `|t| t` spreads the bend over all
+    /// segments, the last one getting full weight.
+    ///
+    /// Jobs are run from the first to the last. Once a job is run, its corrections are applied to the joint
+    /// matrices of all following jobs, so each segment is solved from the pose left by the previous ones. This
+    /// requires joints of a segment to be descendants of (or equal to) the previous segment end joint. Stretch
+    /// factor isn't propagated, so stretching should be disabled. Apply each job corrections as usual (see
+    /// `apply_corrections()`) to get the final pose.
+    ///
+    /// All jobs are validated before any is run, `OzzError::InvalidJob` is returned if any is invalid.
+    pub fn run_chain(jobs: &mut [IKTwoBoneJob], falloff: impl Fn(f32) -> f32) -> Result<(), OzzError> {
+        if !jobs.iter().all(|job| job.validate()) {
+            return Err(OzzError::InvalidJob);
+        }
+        let count = jobs.len();
+        for idx in 0..count {
+            let (job, rest) = jobs[idx..].split_first_mut().unwrap();
+            job.weight = falloff((idx + 1) as f32 / count as f32);
+            job.solve();
+
+            // Rigid transformation of mid joint descendants, start then mid corrections in their own spaces.
+            let (start, mid): (Mat4, Mat4) = (job.start_joint(), job.mid_joint());
+            let delta = start
+                * Mat4::from_quat(job.start_joint_correction())
+                * start.inverse()
+                * mid
+                * Mat4::from_quat(job.mid_joint_correction())
+                * mid.inverse();
+            for next in rest.iter_mut() {
+                next.start_joint = (delta * next.start_joint()).into();
+                next.mid_joint = (delta * next.mid_joint()).into();
+                next.end_joint = (delta * next.end_joint()).into();
+            }
+        }
+        Ok(())
+    }
+
+    fn solve(&mut self) {
+        let setup = IKConstantSetup::new(self);
+        self.geometric_reached = self.geometric_reach(&setup);
 
         if self.weight <= 0.0 {
             self.start_joint_correction = QUAT_UNIT;
             self.mid_joint_correction = QUAT_UNIT;
             self.reached = false;
-            return Ok(());
+            self.degenerate_scale = false;
+            self.reach_delta = 0.0;
+            self.stretch_factor = 1.0;
+            self.corrected_end_position = self.end_joint.cols[3];
+            self.effective_target = self.target;
+            return;
         }
 
-        let setup = IKConstantSetup::new(self);
-        let (lreached, start_target_ss, start_target_ss_len2) = self.soften_target(&setup);
+        self.degenerate_scale = setup.degenerate_scale;
+        let (stretch, lreached, start_target_ss, start_target_ss_len2, reach_delta) =
+            self.stretch_soften_target(&setup);
         self.reached = lreached && self.weight >= 1.0;
+        self.reach_delta = reach_delta;
+        self.stretch_factor = 1.0 + (stretch - 1.0) * f32::min(self.weight, 1.0);
+        // Softened target is computed in the stretched down start joint space.
+        self.effective_target = self
+            .start_joint
+            .transform_point(start_target_ss * f32x4::splat(stretch));
 
         let mid_rot_ms = self.compute_mid_joint(&setup, start_target_ss_len2);
         let start_rot_ss = self.compute_start_joint(&setup, mid_rot_ms, start_target_ss, start_target_ss_len2);
         self.weight_output(start_rot_ss, mid_rot_ms);
-        Ok(())
+        self.corrected_end_position = self.compute_corrected_end(&setup);
+    }
+
+    // Applies output corrections and stretch factor to the chain: end position in mid joint space is stretched
+    // and rotated by mid correction, then mid joint position and end position are moved to start joint space,
+    // and rotated by start correction.
+    fn compute_corrected_end(&self, setup: &IKConstantSetup) -> f32x4 {
+        let stretch = f32x4::splat(self.stretch_factor);
+        let mid_end_ms = quat_transform_vector(self.mid_joint_correction, setup.mid_end_ms * stretch);
+        let mid_end_ss = setup
+            .inv_start_joint
+            .transform_vector(self.mid_joint.transform_vector(mid_end_ms));
+        let start_end_ss =
+            quat_transform_vector(self.start_joint_correction, setup.start_mid_ss * stretch + mid_end_ss);
+        self.start_joint.transform_point(start_end_ss)
+    }
+
+    fn geometric_reach(&self, setup: &IKConstantSetup) -> bool {
+        let start_target_ss = setup.inv_start_joint.transform_point(self.target);
+        let start_target_ss_len = vec3_length2_s(start_target_ss)[0].sqrt();
+        let (min, max) = self.compute_reach_bounds(setup);
+        start_target_ss_len >= min && start_target_ss_len <= max
+    }
+
+    fn compute_reach_bounds(&self, setup: &IKConstantSetup) -> (f32, f32) {
+        let lengths = fx4_set_y(setup.start_mid_ss_len2, setup.mid_end_ss_len2).sqrt(); // [x y]
+        let bones_chain_len = (lengths[0] + lengths[1]) * f32::max(self.stretch, 1.0);
+        ((lengths[0] - lengths[1]).abs(), bones_chain_len)
+    }
+
+    // Returns stretch ratio, and whether the stretched chain spans the target (ratio isn't capped by max ratio).
+    fn compute_stretch(&self, setup: &IKConstantSetup) -> (f32, bool) {
+        if self.stretch <= 1.0 {
+            return (1.0, false);
+        }
+        let start_target_ss = setup.inv_start_joint.transform_point(self.target);
+        let lengths = fx4_set_z(
+            fx4_set_y(setup.start_mid_ss_len2, setup.mid_end_ss_len2),
+            vec3_length2_s(start_target_ss),
+        )
+        .sqrt(); // [x y z]
+        let bones_chain_len = lengths[0] + lengths[1];
+        if bones_chain_len <= 0.0 || lengths[2] <= bones_chain_len {
+            return (1.0, false);
+        }
+        let ratio = lengths[2] / bones_chain_len;
+        (f32::min(ratio, self.stretch), ratio <= self.stretch)
     }
 
-    fn soften_target(&self, setup: &IKConstantSetup) -> (bool, f32x4, f32x4) {
-        let start_target_original_ss = setup.inv_start_joint.transform_point(self.target);
+    // Softens the target on the stretched chain. Returns stretch ratio, reached, softened target and its length
+    // squared (in stretched down start joint space), and reach delta in start joint space.
+    fn stretch_soften_target(&self, setup: &IKConstantSetup) -> (f32, bool, f32x4, f32x4, f32) {
+        let (stretch, spanned) = self.compute_stretch(setup);
+        let (lreached, start_target_ss, start_target_ss_len2, reach_delta) = self.soften_target(setup, stretch);
+        // A spanning stretched chain is fully extended onto the target, only softening can hold it back.
+        if spanned && self.soften >= 1.0 {
+            return (stretch, true, start_target_ss, start_target_ss_len2, 0.0);
+        }
+        (
+            stretch,
+            lreached,
+            start_target_ss,
+            start_target_ss_len2,
+            reach_delta * stretch,
+        )
+    }
+
+    fn soften_target(&self, setup: &IKConstantSetup, stretch: f32) -> (bool, f32x4, f32x4, f32) {
+        // Scaling target down is equivalent to stretching bones.
+        let inv_stretch = f32x4::from_array([stretch.recip(), stretch.recip(), stretch.recip(), 1.0]);
+        let start_target_original_ss = setup.inv_start_joint.transform_point(self.target) * inv_stretch;
         let start_target_original_ss_len2 = vec3_length2_s(start_target_original_ss); // [x]
         let lengths = fx4_set_z(
             fx4_set_y(setup.start_mid_ss_len2, setup.mid_end_ss_len2),
@@ -348,6 +843,7 @@ impl IKTwoBoneJob {
 
         let start_target_ss;
         let start_target_ss_len2;
+        let reach_delta;
 
         // xyw all 1, z is untested.
         if (comp_mask & 0xb) == 0xb {
@@ -362,13 +858,24 @@ impl IKTwoBoneJob {
             start_target_ss_len2 = start_target_ss_len * start_target_ss_len; // [x]
             start_target_ss =
                 start_target_original_ss * fx4_splat_x(start_target_ss_len * start_target_original_ss_len.recip());
-        // [x y z]
+            // [x y z]
+            reach_delta = start_target_original_ss_len[0] - start_target_ss_len[0];
         } else {
             start_target_ss = start_target_original_ss; // [x y z]
             start_target_ss_len2 = start_target_original_ss_len2; // [x]
+            reach_delta = if (comp_mask & 0x4) == 0 {
+                bone_len_diff_abs[0] - start_target_original_ss_len[0]
+            } else {
+                start_target_original_ss_len[0] - da[0]
+            };
         }
 
-        ((comp_mask & 0x5) == 0x4, start_target_ss, start_target_ss_len2)
+        (
+            (comp_mask & 0x5) == 0x4,
+            start_target_ss,
+            start_target_ss_len2,
+            reach_delta,
+        )
     }
 
     fn compute_mid_joint(&self, setup: &IKConstantSetup, start_target_ss_len2: f32x4) -> f32x4 {
@@ -428,22 +935,37 @@ impl IKTwoBoneJob {
             .sqrt()
             .recip(); // [x y z]
 
-            let rotate_plane_cos_angle = vec3_dot_s(
-                ref_plane_normal_ss * fx4_splat_y(rsqrts),
-                joint_plane_normal_ss * fx4_splat_z(rsqrts),
-            ); // [x]
-
             let rotate_plane_axis_ss = start_target_ss * fx4_splat_x(rsqrts);
-            let start_axis_flip = fx4_sign(fx4_splat_x(vec3_dot_s(joint_plane_normal_ss, pole_ss)));
-            let rotate_plane_axis_flipped_ss = fx4_xor(rotate_plane_axis_ss, start_axis_flip);
 
-            let rotate_plane_ss = quat_from_cos_angle(
-                rotate_plane_axis_flipped_ss,
-                rotate_plane_cos_angle.simd_clamp(NEG_ONE, ONE),
-            );
+            // Target and pole vector (or middle joint axis) are aligned, so planes normals are undefined.
+            // Planes rotation is skipped rather than producing NaN.
+            let degenerated = fx4_set_y(ref_plane_normal_ss_len2, joint_plane_normal_ss_len2)
+                .simd_le(PLANE_NORMAL_EPSILON)
+                .to_bitmask()
+                & 0x3
+                != 0;
 
-            if self.twist_angle != 0.0 {
-                let twist_ss = quat_from_axis_angle(rotate_plane_axis_ss, f32x4::splat(self.twist_angle));
+            let rotate_plane_ss = if degenerated {
+                QUAT_UNIT
+            } else {
+                let rotate_plane_cos_angle = vec3_dot_s(
+                    ref_plane_normal_ss * fx4_splat_y(rsqrts),
+                    joint_plane_normal_ss * fx4_splat_z(rsqrts),
+                ); // [x]
+
+                let start_axis_flip = fx4_sign(fx4_splat_x(vec3_dot_s(joint_plane_normal_ss, pole_ss)));
+                let rotate_plane_axis_flipped_ss = fx4_xor(rotate_plane_axis_ss, start_axis_flip);
+
+                quat_from_cos_angle(
+                    rotate_plane_axis_flipped_ss,
+                    rotate_plane_cos_angle.simd_clamp(NEG_ONE, ONE),
+                )
+            };
+
+            let (twist_min, twist_max) = self.twist_limits;
+            let twist_angle = self.twist_angle.min(twist_max).max(twist_min);
+            if twist_angle != 0.0 {
+                let twist_ss = quat_from_axis_angle(rotate_plane_axis_ss, f32x4::splat(twist_angle));
                 start_rot_ss = quat_mul(quat_mul(twist_ss, rotate_plane_ss), end_to_target_rot_ss);
             } else {
                 start_rot_ss = quat_mul(rotate_plane_ss, end_to_target_rot_ss);
@@ -462,17 +984,19 @@ impl IKTwoBoneJob {
             let start_lerp = fx4_lerp(QUAT_UNIT, start_rot_fu, simd_weight);
             let mid_lerp = fx4_lerp(QUAT_UNIT, mid_rot_fu, simd_weight);
 
-            let rsqrts = f32x4::from_array([
-                (start_lerp * start_lerp).reduce_sum(),
-                (mid_lerp * mid_lerp).reduce_sum(),
-                0.0,
-                0.0,
-            ])
-            .sqrt()
-            .recip();
-
-            self.start_joint_correction = start_lerp * fx4_splat_x(rsqrts);
-            self.mid_joint_correction = mid_lerp * fx4_splat_y(rsqrts);
+            let start_len2 = (start_lerp * start_lerp).reduce_sum();
+            let mid_len2 = (mid_lerp * mid_lerp).reduce_sum();
+            let rsqrts = f32x4::from_array([start_len2, mid_len2, 1.0, 1.0]).sqrt().recip();
+
+            // Falls back to identity if the lerped quaternion is too short to be normalized.
+            self.start_joint_correction = match start_len2 < WEIGHT_LERP_EPSILON {
+                true => QUAT_UNIT,
+                false => start_lerp * fx4_splat_x(rsqrts),
+            };
+            self.mid_joint_correction = match mid_len2 < WEIGHT_LERP_EPSILON {
+                true => QUAT_UNIT,
+                false => mid_lerp * fx4_splat_y(rsqrts),
+            };
         } else {
             self.start_joint_correction = start_rot_fu;
             self.mid_joint_correction = mid_rot_fu;
@@ -480,11 +1004,116 @@ impl IKTwoBoneJob {
     }
 }
 
+///
+/// Builder of `IKTwoBoneJob`, chaining the job setters.
+///
+/// `build()` validates the job, so an invalid setup (like a non normalized mid axis) fails once at build
+/// time rather than at every `run()`. Inputs not set keep `IKTwoBoneJob` default values.
+///
+#[derive(Debug, Default)]
+pub struct IKTwoBoneJobBuilder {
+    job: IKTwoBoneJob,
+}
+
+impl IKTwoBoneJobBuilder {
+    /// Creates a builder, with default `IKTwoBoneJob` inputs.
+    #[inline]
+    pub fn new() -> IKTwoBoneJobBuilder {
+        IKTwoBoneJobBuilder::default()
+    }
+
+    /// See `IKTwoBoneJob::set_target()`.
+    #[inline]
+    pub fn with_target(mut self, target: Vec3A) -> Self {
+        self.job.set_target(target);
+        self
+    }
+
+    /// See `IKTwoBoneJob::set_mid_axis()`.
+    #[inline]
+    pub fn with_mid_axis(mut self, mid_axis: Vec3A) -> Self {
+        self.job.set_mid_axis(mid_axis);
+        self
+    }
+
+    /// See `IKTwoBoneJob::set_pole_vector()`.
+    #[inline]
+    pub fn with_pole_vector(mut self, pole_vector: Vec3A) -> Self {
+        self.job.set_pole_vector(pole_vector);
+        self
+    }
+
+    /// See `IKTwoBoneJob::set_twist_angle()`.
+    #[inline]
+    pub fn with_twist_angle(mut self, twist_angle: f32) -> Self {
+        self.job.set_twist_angle(twist_angle);
+        self
+    }
+
+    /// See `IKTwoBoneJob::set_twist_limits()`.
+    #[inline]
+    pub fn with_twist_limits(mut self, min: f32, max: f32) -> Self {
+        self.job.set_twist_limits(min, max);
+        self
+    }
+
+    /// See `IKTwoBoneJob::set_soften()`.
+    #[inline]
+    pub fn with_soften(mut self, soften: f32) -> Self {
+        self.job.set_soften(soften);
+        self
+    }
+
+    /// See `IKTwoBoneJob::set_weight()`.
+    #[inline]
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.job.set_weight(weight);
+        self
+    }
+
+    /// See `IKTwoBoneJob::set_stretch()`.
+    #[inline]
+    pub fn with_stretch(mut self, max_ratio: f32) -> Self {
+        self.job.set_stretch(max_ratio);
+        self
+    }
+
+    /// See `IKTwoBoneJob::set_start_joint()`.
+    #[inline]
+    pub fn with_start_joint(mut self, start_joint: Mat4) -> Self {
+        self.job.set_start_joint(start_joint);
+        self
+    }
+
+    /// See `IKTwoBoneJob::set_mid_joint()`.
+    #[inline]
+    pub fn with_mid_joint(mut self, mid_joint: Mat4) -> Self {
+        self.job.set_mid_joint(mid_joint);
+        self
+    }
+
+    /// See `IKTwoBoneJob::set_end_joint()`.
+    #[inline]
+    pub fn with_end_joint(mut self, end_joint: Mat4) -> Self {
+        self.job.set_end_joint(end_joint);
+        self
+    }
+
+    /// Builds the job, returns `OzzError::InvalidJob` if it doesn't validate.
+    pub fn build(self) -> Result<IKTwoBoneJob, OzzError> {
+        if !self.job.validate() {
+            return Err(OzzError::InvalidJob);
+        }
+        Ok(self.job)
+    }
+}
+
 #[allow(clippy::excessive_precision)]
 #[cfg(test)]
 mod ik_two_bone_tests {
     use core::f32::consts;
     use glam::{Vec3, Vec4};
+    use rand::Rng;
     use wasm_bindgen_test::*;
 
     use super::*;
@@ -501,6 +1130,38 @@ mod ik_two_bone_tests {
         assert!(job.validate());
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_simd_accessors() {
+        let mut job = new_ik_two_bone_job();
+        job.set_target_simd(f32x4::from_array([0.0, 1.0, 1.0, 0.0]));
+        assert_eq!(job.target(), Vec3A::new(0.0, 1.0, 1.0));
+        job.set_target(Vec3A::new(1.0, 1.0, 0.0));
+        assert_eq!(fx4_to_vec3a(job.target_simd()), job.target());
+
+        job.set_mid_axis_simd(f32x4::from_array([0.0, 0.0, 1.0, 0.0]));
+        assert_eq!(job.mid_axis(), Vec3A::Z);
+        assert_eq!(fx4_to_vec3a(job.mid_axis_simd()), job.mid_axis());
+
+        job.set_pole_vector_simd(f32x4::from_array([0.0, 1.0, 0.0, 0.0]));
+        assert_eq!(job.pole_vector(), Vec3A::Y);
+        assert_eq!(fx4_to_vec3a(job.pole_vector_simd()), job.pole_vector());
+
+        job.set_target_simd(f32x4::from_array([0.0, 1.0, 1.0, 0.0]));
+        job.run().unwrap();
+        assert_eq!(
+            job.start_joint_correction_simd().to_array(),
+            job.start_joint_correction().to_array()
+        );
+        assert_eq!(
+            job.mid_joint_correction_simd().to_array(),
+            job.mid_joint_correction().to_array()
+        );
+        assert!(job
+            .start_joint_correction()
+            .abs_diff_eq(Quat::from_axis_angle(Vec3::Y, -consts::FRAC_PI_2), 2e-3));
+    }
+
     #[inline(always)]
     fn vec4_to_vec3a(v: Vec4) -> Vec3A {
         Vec3A::new(v[0], v[1], v[2])
@@ -602,14 +1263,210 @@ mod ik_two_bone_tests {
 
     #[test]
     #[wasm_bindgen_test]
-    fn test_pole() {
-        let mut job = new_ik_two_bone_job();
+    fn test_builder() {
+        let start = Mat4::IDENTITY;
+        let mid = Mat4::from_rotation_translation(Quat::from_axis_angle(Vec3::Z, consts::FRAC_PI_2), Vec3::Y);
+        let end = Mat4::from_translation(Vec3::X + Vec3::Y);
 
-        {
-            // pole Y
-            job.set_pole_vector(Vec3A::Y);
-            job.set_target(Vec3A::new(1.0, 1.0, 0.0));
-            job.run().unwrap();
+        let mut expected = new_ik_two_bone_job();
+        expected.set_target(Vec3A::new(0.5, 1.2, 0.7));
+        expected.set_pole_vector(Vec3A::Z);
+        expected.set_twist_angle(0.3);
+        expected.set_soften(0.8);
+        expected.set_weight(0.7);
+        expected.set_stretch(1.5);
+        expected.run().unwrap();
+
+        let mut job = IKTwoBoneJob::builder()
+            .with_start_joint(start)
+            .with_mid_joint(mid)
+            .with_end_joint(end)
+            .with_mid_axis(expected.mid_axis())
+            .with_target(Vec3A::new(0.5, 1.2, 0.7))
+            .with_pole_vector(Vec3A::Z)
+            .with_twist_angle(0.3)
+            .with_soften(0.8)
+            .with_weight(0.7)
+            .with_stretch(1.5)
+            .build()
+            .unwrap();
+        job.run().unwrap();
+        assert_eq!(job.start_joint_correction(), expected.start_joint_correction());
+        assert_eq!(job.mid_joint_correction(), expected.mid_joint_correction());
+        assert_eq!(job.reached(), expected.reached());
+        assert_eq!(job.stretch_factor(), expected.stretch_factor());
+
+        // defaults
+        let job = IKTwoBoneJobBuilder::new().build().unwrap();
+        assert_eq!(job.mid_axis(), IKTwoBoneJob::default().mid_axis());
+        assert_eq!(job.soften(), 1.0);
+
+        // validation fails at build time
+        assert!(IKTwoBoneJob::builder()
+            .with_mid_axis(Vec3A::new(1.0, 2.0, 3.0))
+            .build()
+            .unwrap_err()
+            .is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_from_skeleton() {
+        let mut expected = new_ik_two_bone_job();
+        let models = [
+            Mat4::from_translation(Vec3::Z),
+            expected.end_joint(),
+            expected.start_joint(),
+            Mat4::from_translation(-Vec3::Z),
+            expected.mid_joint(),
+        ];
+
+        let mut job = IKTwoBoneJob::from_skeleton(&models, 2i16, 4usize, 1i32).unwrap();
+        assert_eq!(job.start_joint(), expected.start_joint());
+        assert_eq!(job.mid_joint(), expected.mid_joint());
+        assert_eq!(job.end_joint(), expected.end_joint());
+
+        for job in [&mut job, &mut expected] {
+            job.set_mid_axis(Vec3A::Z);
+            job.set_target(Vec3A::new(0.5, 1.2, 0.7));
+            job.set_pole_vector(Vec3A::Z);
+            job.run().unwrap();
+        }
+        assert_eq!(job.start_joint_correction(), expected.start_joint_correction());
+        assert_eq!(job.mid_joint_correction(), expected.mid_joint_correction());
+        assert_eq!(job.reached(), expected.reached());
+
+        assert!(IKTwoBoneJob::from_skeleton(&models, 2, 4, 5)
+            .unwrap_err()
+            .is_invalid_index());
+        assert!(IKTwoBoneJob::from_skeleton(&[], 0, 0, 0)
+            .unwrap_err()
+            .is_invalid_index());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_run_chain() {
+        // 4 segments along y, 9 joints 0.25 apart, each segment bending around z
+        let joints: ::alloc::vec::Vec<Mat4> = (0..9)
+            .map(|idx| Mat4::from_translation(Vec3::new(0.0, idx as f32 * 0.25, 0.0)))
+            .collect();
+        let target = Vec3A::new(1.2, 1.2, 0.0);
+        let mut jobs: ::alloc::vec::Vec<IKTwoBoneJob> = (0..4)
+            .map(|idx| {
+                IKTwoBoneJob::builder()
+                    .with_start_joint(joints[idx * 2])
+                    .with_mid_joint(joints[idx * 2 + 1])
+                    .with_end_joint(joints[idx * 2 + 2])
+                    .with_mid_axis(Vec3A::Z)
+                    .with_pole_vector(Vec3A::X)
+                    .with_target(target)
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+
+        IKTwoBoneJob::run_chain(&mut jobs, |t| t).unwrap();
+        let weights: ::alloc::vec::Vec<f32> = jobs.iter().map(|job| job.weight()).collect();
+        assert_eq!(weights, [0.25, 0.5, 0.75, 1.0]);
+
+        // every segment bends toward the target, the chain curls more and more from the root to the end
+        let headings: ::alloc::vec::Vec<f32> = jobs
+            .iter()
+            .map(|job| {
+                let start = Vec3A::from(job.start_joint().w_axis.truncate());
+                (job.corrected_end_position() - start).angle_between(Vec3A::Y)
+            })
+            .collect();
+        assert!(headings[0] > 1e-3, "{:?}", headings);
+        assert!(headings.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", headings);
+
+        // last segment fully weighted, chain end points at the target
+        let start = Vec3A::from(jobs[3].start_joint().w_axis.truncate());
+        let direction = (jobs[3].corrected_end_position() - start).normalize();
+        assert!(direction.abs_diff_eq((target - start).normalize(), 1e-4));
+
+        // following jobs joints are moved by previous corrections
+        assert_ne!(jobs[1].start_joint(), joints[2]);
+        assert!(jobs[1]
+            .start_joint()
+            .w_axis
+            .abs_diff_eq(jobs[0].corrected_end_position().extend(1.0), 1e-5));
+
+        assert!(IKTwoBoneJob::run_chain(&mut [], |t| t).is_ok());
+        jobs[2].set_mid_axis(Vec3A::ZERO);
+        assert!(IKTwoBoneJob::run_chain(&mut jobs, |t| t).unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_run_batch() {
+        let mut rng = rand::thread_rng();
+        let mut jobs: Vec<IKTwoBoneJob> = (0..13)
+            .map(|idx| {
+                let mut job = new_ik_two_bone_job();
+                job.set_target(Vec3A::new(
+                    rng.gen_range(-2.0..2.0),
+                    rng.gen_range(-2.0..2.0),
+                    rng.gen_range(-2.0..2.0),
+                ));
+                job.set_pole_vector(Vec3A::new(rng.gen_range(-1.0..1.0), 1.0, rng.gen_range(-1.0..1.0)));
+                job.set_twist_angle(rng.gen_range(-1.0..1.0));
+                job.set_soften(rng.gen_range(0.5..1.0));
+                job.set_stretch(rng.gen_range(1.0..1.5));
+                job.set_weight([0.0, 0.5, 1.0][idx % 3]);
+                job
+            })
+            .collect();
+
+        let mut expected: Vec<IKTwoBoneJob> = Vec::new();
+        for job in jobs.iter() {
+            let mut single = IKTwoBoneJob::builder()
+                .with_start_joint(job.start_joint())
+                .with_mid_joint(job.mid_joint())
+                .with_end_joint(job.end_joint())
+                .with_mid_axis(job.mid_axis())
+                .with_target(job.target())
+                .with_pole_vector(job.pole_vector())
+                .with_twist_angle(job.twist_angle())
+                .with_soften(job.soften())
+                .with_weight(job.weight())
+                .with_stretch(job.stretch())
+                .build()
+                .unwrap();
+            single.run().unwrap();
+            expected.push(single);
+        }
+
+        IKTwoBoneJob::run_batch(&mut jobs).unwrap();
+        for (job, expected) in jobs.iter().zip(expected.iter()) {
+            assert_eq!(job.start_joint_correction(), expected.start_joint_correction());
+            assert_eq!(job.mid_joint_correction(), expected.mid_joint_correction());
+            assert_eq!(job.reached(), expected.reached());
+            assert_eq!(job.reach_delta(), expected.reach_delta());
+            assert_eq!(job.stretch_factor(), expected.stretch_factor());
+            assert_eq!(job.corrected_end_position(), expected.corrected_end_position());
+        }
+
+        // no job is run if any is invalid
+        let mut jobs = [new_ik_two_bone_job(), new_ik_two_bone_job()];
+        jobs[0].set_target(Vec3A::new(0.0, 1.0, 1.0));
+        jobs[1].set_mid_axis(Vec3A::ONE);
+        assert!(IKTwoBoneJob::run_batch(&mut jobs).unwrap_err().is_invalid_job());
+        assert_eq!(jobs[0].start_joint_correction(), Quat::IDENTITY);
+        assert!(IKTwoBoneJob::run_batch(&mut []).is_ok());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_pole() {
+        let mut job = new_ik_two_bone_job();
+
+        {
+            // pole Y
+            job.set_pole_vector(Vec3A::Y);
+            job.set_target(Vec3A::new(1.0, 1.0, 0.0));
+            job.run().unwrap();
             assert!(job.start_joint_correction().abs_diff_eq(Quat::IDENTITY, 2e-3));
             assert!(job.mid_joint_correction().abs_diff_eq(Quat::IDENTITY, 2e-3));
         }
@@ -672,6 +1529,317 @@ mod ik_two_bone_tests {
         assert!(job.mid_joint_correction().abs_diff_eq(Quat::IDENTITY, 2e-3));
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_degenerate_scale() {
+        let mut job = new_ik_two_bone_job();
+        job.set_target(Vec3A::new(0.0, 1.0, 1.0));
+        job.run().unwrap();
+        assert!(!job.had_degenerate_scale());
+
+        // negative scale is fine
+        job.set_start_joint(Mat4::from_scale(Vec3::new(-1.0, 1.0, 1.0)));
+        job.run().unwrap();
+        assert!(!job.had_degenerate_scale());
+
+        job.set_start_joint(Mat4::from_scale(Vec3::new(1.0, 0.0, 1.0)));
+        job.run().unwrap();
+        assert!(job.had_degenerate_scale());
+
+        job.set_start_joint(Mat4::ZERO);
+        job.run().unwrap();
+        assert!(job.had_degenerate_scale());
+        assert!(job.start_joint_correction().abs_diff_eq(Quat::IDENTITY, 2e-3));
+        assert!(job.mid_joint_correction().abs_diff_eq(Quat::IDENTITY, 2e-3));
+
+        job.set_weight(0.0);
+        job.run().unwrap();
+        assert!(!job.had_degenerate_scale());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_reach_delta() {
+        let mut job = new_ik_two_bone_job();
+
+        {
+            // reachable
+            job.set_target(Vec3A::new(0.0, 1.0, 1.0));
+            job.run().unwrap();
+            assert!(job.reached());
+            assert!((job.reach_delta() - (consts::SQRT_2 - 2.0)).abs() < 2e-3);
+        }
+
+        {
+            // fully extended
+            job.set_target(Vec3A::new(0.0, 2.0, 0.0));
+            job.run().unwrap();
+            assert!(job.reach_delta().abs() < 2e-3);
+        }
+
+        {
+            // too far
+            job.set_target(Vec3A::new(0.0, 3.0, 0.0));
+            job.run().unwrap();
+            assert!(!job.reached());
+            assert!((job.reach_delta() - 1.0).abs() < 2e-3);
+        }
+
+        {
+            // too far, softened
+            job.set_soften(0.5);
+            job.set_target(Vec3A::new(0.0, 3.0, 0.0));
+            job.run().unwrap();
+            assert!(!job.reached());
+            assert!(job.reach_delta() > 1.0);
+            job.set_soften(1.0);
+        }
+
+        {
+            // too close, bones have different lengths
+            job.set_end_joint(Mat4::from_translation(Vec3::new(0.5, 1.0, 0.0)));
+            job.set_target(Vec3A::new(0.0, 0.25, 0.0));
+            job.run().unwrap();
+            assert!(!job.reached());
+            assert!((job.reach_delta() - 0.25).abs() < 2e-3);
+        }
+
+        {
+            job.set_weight(0.0);
+            job.run().unwrap();
+            assert_eq!(job.reach_delta(), 0.0);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_stretch() {
+        let mut job = new_ik_two_bone_job();
+        job.set_pole_vector(Vec3A::Z);
+        let chain_end = |job: &IKTwoBoneJob| {
+            let mid_local = job.mid_joint();
+            let end_local = job.mid_joint().inverse() * job.end_joint();
+            let scale = |m: Mat4, rot: Quat| {
+                let (_, r, t) = m.to_scale_rotation_translation();
+                Mat4::from_rotation_translation(r * rot, t * job.stretch_factor())
+            };
+            let start = job.start_joint() * Mat4::from_quat(job.start_joint_correction());
+            let end = start * scale(mid_local, job.mid_joint_correction()) * scale(end_local, Quat::IDENTITY);
+            Vec3A::from(end.w_axis.truncate())
+        };
+
+        {
+            // disabled by default
+            assert_eq!(job.stretch(), 1.0);
+            job.set_target(Vec3A::new(0.0, 3.0, 0.0));
+            job.run().unwrap();
+            assert!(!job.reached());
+            assert_eq!(job.stretch_factor(), 1.0);
+        }
+
+        {
+            // reachable without stretching
+            job.set_stretch(2.0);
+            job.set_target(Vec3A::new(0.0, 1.0, 1.0));
+            job.run().unwrap();
+            assert!(job.reached());
+            assert_eq!(job.stretch_factor(), 1.0);
+        }
+
+        {
+            // 1.5 x chain length
+            job.set_target(Vec3A::new(0.0, 3.0, 0.0));
+            job.run().unwrap();
+            assert!(job.reached());
+            assert_eq!(job.reach_delta(), 0.0);
+            assert!((job.stretch_factor() - 1.5).abs() < 1e-5);
+            assert!(chain_end(&job).abs_diff_eq(job.target(), 2e-3), "{}", chain_end(&job));
+
+            job.set_target(Vec3A::new(2.0, 1.0, 2.0));
+            job.run().unwrap();
+            assert!(job.reached());
+            assert!((job.stretch_factor() - 1.5).abs() < 1e-5);
+            assert!(chain_end(&job).abs_diff_eq(job.target(), 2e-3), "{}", chain_end(&job));
+        }
+
+        {
+            // exactly at max ratio
+            job.set_target(Vec3A::new(0.0, 4.0, 0.0));
+            job.run().unwrap();
+            assert!(job.reached());
+            assert_eq!(job.reach_delta(), 0.0);
+            assert_eq!(job.stretch_factor(), 2.0);
+        }
+
+        {
+            // softened, the stretched chain falls behind the target
+            job.set_soften(0.5);
+            job.set_target(Vec3A::new(0.0, 3.0, 0.0));
+            job.run().unwrap();
+            assert!(!job.reached());
+            assert!((job.stretch_factor() - 1.5).abs() < 1e-5);
+            let end = job.corrected_end_position();
+            assert!(end.abs_diff_eq(Vec3A::new(0.0, 2.525, 0.0), 2e-3), "{}", end);
+            assert!(
+                (job.reach_delta() - (3.0 - end.y)).abs() < 2e-3,
+                "{}",
+                job.reach_delta()
+            );
+            job.set_soften(1.0);
+        }
+
+        {
+            // capped by max ratio
+            job.set_target(Vec3A::new(0.0, 5.0, 0.0));
+            job.run().unwrap();
+            assert!(!job.reached());
+            assert_eq!(job.stretch_factor(), 2.0);
+            assert!((job.reach_delta() - 1.0).abs() < 2e-3);
+        }
+
+        {
+            job.set_weight(0.5);
+            job.set_target(Vec3A::new(0.0, 3.0, 0.0));
+            job.run().unwrap();
+            assert!((job.stretch_factor() - 1.25).abs() < 1e-5);
+            job.set_weight(0.0);
+            job.run().unwrap();
+            assert_eq!(job.stretch_factor(), 1.0);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_corrected_end_position() {
+        let mut job = new_ik_two_bone_job();
+        assert_eq!(job.start_position(), Vec3A::ZERO);
+        assert_eq!(job.mid_position(), Vec3A::new(0.0, 1.0, 0.0));
+        assert_eq!(job.end_position(), Vec3A::new(1.0, 1.0, 0.0));
+
+        for target in [
+            Vec3A::new(0.0, 1.0, 1.0),
+            Vec3A::new(0.5, -1.0, 0.3),
+            Vec3A::new(1.0, 1.0, 0.0),
+            Vec3A::new(-1.2, 0.4, 0.9),
+        ] {
+            job.set_target(target);
+            job.run().unwrap();
+            assert!(job.reached());
+            assert!(
+                job.corrected_end_position().abs_diff_eq(target, 2e-3),
+                "{} {}",
+                job.corrected_end_position(),
+                target
+            );
+        }
+
+        // stretched
+        job.set_stretch(2.0);
+        job.set_target(Vec3A::new(0.0, 3.0, 0.0));
+        job.run().unwrap();
+        assert!(job.reached());
+        assert!(job.corrected_end_position().abs_diff_eq(job.target(), 2e-3));
+
+        // unreached, the chain is fully extended towards the target
+        job.set_stretch(1.0);
+        job.run().unwrap();
+        assert!(!job.reached());
+        assert!(job
+            .corrected_end_position()
+            .abs_diff_eq(Vec3A::new(0.0, 2.0, 0.0), 2e-3));
+
+        // no correction
+        job.set_weight(0.0);
+        job.run().unwrap();
+        assert_eq!(job.corrected_end_position(), job.end_position());
+        job.clear_outs();
+        assert_eq!(job.corrected_end_position(), Vec3A::ZERO);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_apply_corrections() {
+        // start (rotated) -> mid -> end, each joint translated in its parent space
+        let (start_t, mid_t, end_t) = (
+            Vec3::new(0.5, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+        );
+        let start_r = Quat::from_axis_angle(Vec3::new(1.0, 0.5, 0.2).normalize(), 0.4);
+        let mid_r = Quat::from_rotation_z(consts::FRAC_PI_3);
+        let model = |start_r: Quat, mid_r: Quat| {
+            let start = Mat4::from_rotation_translation(start_r, start_t);
+            let mid = start * Mat4::from_rotation_translation(mid_r, mid_t);
+            let end = mid * Mat4::from_translation(end_t);
+            (start, mid, end)
+        };
+
+        let (start, mid, end) = model(start_r, mid_r);
+        let mut job = IKTwoBoneJob::default();
+        job.set_start_joint(start);
+        job.set_mid_joint(mid);
+        job.set_end_joint(end);
+        job.set_mid_axis(Vec3A::Z);
+        let target = Vec3A::new(1.5, 1.2, 0.5);
+        job.set_target(target);
+        job.run().unwrap();
+        assert!(job.reached());
+
+        let (mut local_start, mut local_mid) = (start_r, mid_r);
+        job.apply_corrections(&mut local_start, &mut local_mid);
+        assert_eq!(local_start, start_r * job.start_joint_correction());
+        assert_eq!(local_mid, mid_r * job.mid_joint_correction());
+
+        let (_, _, end) = model(local_start, local_mid);
+        let end_position = Vec3A::from(end.w_axis.truncate());
+        assert!(end_position.abs_diff_eq(target, 2e-3), "{}", end_position);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_can_reach() {
+        let mut job = new_ik_two_bone_job();
+        let targets = [
+            Vec3A::new(1.0, 1.0, 0.0),
+            Vec3A::new(2.0, 0.0, 0.0),
+            Vec3A::new(3.0, 0.0, 0.0),
+            Vec3A::new(0.0, 0.0, 0.0),
+            Vec3A::new(0.5, -0.5, 0.3),
+            Vec3A::new(-1.0, 1.5, 0.5),
+            Vec3A::new(0.0, 4.0, 0.0),
+        ];
+        for (soften, stretch, weight) in [
+            (1.0, 1.0, 1.0),
+            (0.5, 1.0, 1.0),
+            (1.0, 1.2, 1.0),
+            (1.0, 2.0, 1.0),
+            (0.5, 2.0, 1.0),
+            (1.0, 1.0, 0.5),
+        ] {
+            job.set_soften(soften);
+            job.set_stretch(stretch);
+            job.set_weight(weight);
+            for target in targets {
+                job.set_target(target);
+                let can_reach = job.can_reach();
+                job.run().unwrap();
+                assert_eq!(can_reach, job.reached(), "{} {} {} {}", target, soften, stretch, weight);
+            }
+        }
+
+        // stretched chain, softened
+        job.set_weight(1.0);
+        job.set_stretch(2.0);
+        job.set_target(Vec3A::new(0.0, 3.0, 0.0));
+        job.set_soften(1.0);
+        assert!(job.can_reach());
+        job.set_soften(0.5);
+        assert!(!job.can_reach());
+
+        job.set_mid_axis(Vec3A::ONE);
+        assert!(!job.can_reach());
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_soften() {
@@ -739,6 +1907,215 @@ mod ik_two_bone_tests {
         }
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_reach_bounds() {
+        // bones of length 1 and 2
+        let mut job = new_ik_two_bone_job();
+        job.set_end_joint(Mat4::from_translation(Vec3::new(2.0, 1.0, 0.0)));
+        let (min, max) = job.reach_bounds();
+        assert!((min - 1.0).abs() < 1e-6);
+        assert!((max - 3.0).abs() < 1e-6);
+
+        for (target, reachable) in [
+            (Vec3A::new(1.5, 0.0, 0.0), true),
+            (Vec3A::new(0.0, -2.0, 1.0), true),
+            (Vec3A::new(0.0, 0.0, 2.999), true),
+            (Vec3A::new(0.5, 0.5, 0.0), false),
+            (Vec3A::new(2.5, 2.5, 0.0), false),
+        ] {
+            job.set_target(target);
+            job.run().unwrap();
+            assert_eq!(job.geometric_reached(), reachable, "target={}", target);
+            assert_eq!(job.reached(), reachable, "target={}", target);
+        }
+
+        job.set_stretch(1.5);
+        assert!((job.reach_bounds().1 - 4.5).abs() < 1e-6);
+        job.set_target(Vec3A::new(2.5, 2.5, 0.0));
+        job.run().unwrap();
+        assert!(job.geometric_reached());
+
+        // bounds are relative to the start joint
+        job.set_start_joint(Mat4::from_translation(Vec3::new(0.0, 0.0, 1.0)));
+        let (min, max) = job.reach_bounds();
+        assert!((min - (2.0 - 2f32.sqrt())).abs() < 1e-5);
+        assert!((max - (2.0 + 2f32.sqrt()) * 1.5).abs() < 1e-5);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_effective_target() {
+        let mut job = new_ik_two_bone_job();
+        job.set_start_joint(Mat4::from_translation(Vec3::new(0.5, -1.0, 2.0)) * job.start_joint());
+        job.set_mid_joint(Mat4::from_translation(Vec3::new(0.5, -1.0, 2.0)) * job.mid_joint());
+        job.set_end_joint(Mat4::from_translation(Vec3::new(0.5, -1.0, 2.0)) * job.end_joint());
+        let start = job.start_position();
+
+        // not softened, the target itself
+        job.set_target(start + Vec3A::new(0.5, 1.0, 0.3));
+        job.run().unwrap();
+        assert!(job.effective_target().abs_diff_eq(job.target(), 1e-5));
+
+        // softened, out of range target, pulled towards start along the start to target line
+        job.set_soften(0.5);
+        job.set_target(start + Vec3A::new(3.0, 1.0, 0.0));
+        job.run().unwrap();
+        assert!(!job.reached());
+        let effective = job.effective_target() - start;
+        let target = job.target() - start;
+        assert!(effective.length() < target.length());
+        assert!(effective.length() < 2.0); // bones chain length
+        assert!(effective.normalize().abs_diff_eq(target.normalize(), 1e-5));
+        assert!((target.length() - effective.length() - job.reach_delta()).abs() < 1e-4);
+        assert!(job.corrected_end_position().abs_diff_eq(job.effective_target(), 2e-3));
+
+        // stretched, the effective target is back in model space
+        job.set_soften(1.0);
+        job.set_stretch(2.0);
+        job.run().unwrap();
+        assert!(job.stretch_factor() > 1.0);
+        assert!(job.effective_target().abs_diff_eq(job.target(), 1e-4));
+
+        job.set_weight(0.0);
+        job.run().unwrap();
+        assert_eq!(job.effective_target(), job.target());
+        job.clear_outs();
+        assert_eq!(job.effective_target(), Vec3A::ZERO);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_soften_continuity() {
+        // soften onset is at half the bones chain length, 1.0
+        let mut job = new_ik_two_bone_job();
+        job.set_pole_vector(Vec3A::Y);
+        job.set_soften(0.5);
+
+        let mut prev: Option<(Quat, Quat)> = None;
+        for step in 0..=400 {
+            let distance = 0.8 + step as f32 * 1e-3;
+            job.set_target(Vec3A::new(distance, 0.0, 0.0));
+            job.run().unwrap();
+            assert_eq!(job.reached(), distance <= 1.0, "distance={}", distance);
+            let (start, mid) = (job.start_joint_correction(), job.mid_joint_correction());
+            if let Some((prev_start, prev_mid)) = prev {
+                assert!(start.angle_between(prev_start) < 5e-3, "distance={}", distance);
+                assert!(mid.angle_between(prev_mid) < 5e-3, "distance={}", distance);
+            }
+            prev = Some((start, mid));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validate_with_epsilon() {
+        let mut job = new_ik_two_bone_job();
+        job.set_mid_axis(Vec3A::Z * (1.0 + 1e-4));
+        assert!(job.validate_with_epsilon(1e-3));
+        assert!(!job.validate_with_epsilon(1e-4));
+        assert_eq!(job.validate_with_epsilon(NORMALIZATION_TOLERANCE_SQ), job.validate());
+        assert!(job.run().is_ok());
+
+        job.set_mid_axis(Vec3A::Z * 1.01);
+        assert!(!job.validate_with_epsilon(NORMALIZATION_TOLERANCE_SQ));
+        assert!(job.validate_with_epsilon(0.05));
+        assert!(job.run().unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_unreached_orientation() {
+        let mut job = new_ik_two_bone_job();
+        for target in [
+            Vec3A::new(5.0, 3.0, -2.0),
+            Vec3A::new(-4.0, 0.5, 1.0),
+            Vec3A::new(0.0, 0.0, 9.0),
+        ] {
+            for soften in [1.0, 0.5] {
+                job.set_soften(soften);
+                job.set_target(target);
+                job.run().unwrap();
+                assert!(!job.reached());
+                let end = job.corrected_end_position();
+                assert!(
+                    end.normalize().abs_diff_eq(target.normalize(), 1e-5),
+                    "{} {}",
+                    end,
+                    target
+                );
+                assert!(end.length() <= 2.0 + 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_geometric_reached() {
+        let mut job = new_ik_two_bone_job();
+        job.set_pole_vector(Vec3A::Y);
+
+        // within chain length, but softened
+        job.set_soften(0.5);
+        job.set_target(Vec3A::new(1.8, 0.0, 0.0));
+        job.run().unwrap();
+        assert!(!job.reached());
+        assert!(job.geometric_reached());
+
+        // weight doesn't matter
+        job.set_weight(0.5);
+        job.run().unwrap();
+        assert!(!job.reached());
+        assert!(job.geometric_reached());
+        job.set_weight(0.0);
+        job.run().unwrap();
+        assert!(job.geometric_reached());
+        job.set_weight(1.0);
+
+        job.set_soften(1.0);
+        job.set_target(Vec3A::new(2.0, 0.0, 0.0));
+        job.run().unwrap();
+        assert!(job.reached());
+        assert!(job.geometric_reached());
+
+        // too far
+        job.set_target(Vec3A::new(2.1, 0.0, 0.0));
+        job.run().unwrap();
+        assert!(!job.geometric_reached());
+
+        // covered by stretching
+        job.set_stretch(1.1);
+        job.run().unwrap();
+        assert!(job.geometric_reached());
+        job.set_target(Vec3A::new(2.3, 0.0, 0.0));
+        job.run().unwrap();
+        assert!(!job.geometric_reached());
+
+        job.clear_outs();
+        assert!(!job.geometric_reached());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_soften_clamp() {
+        let mut job = new_ik_two_bone_job();
+        job.set_target(Vec3A::new(2.0, 0.0, 0.0));
+
+        for (soften, clamped) in [(2.0, 1.0), (-1.0, 0.0), (f32::NAN, 0.0), (0.5, 0.5)] {
+            job.set_soften(soften);
+            assert_eq!(job.soften(), clamped);
+            job.run().unwrap();
+            let result = (job.start_joint_correction(), job.mid_joint_correction(), job.reached());
+
+            job.set_soften(clamped);
+            job.run().unwrap();
+            assert_eq!(
+                result,
+                (job.start_joint_correction(), job.mid_joint_correction(), job.reached())
+            );
+        }
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_twist() {
@@ -791,6 +2168,41 @@ mod ik_two_bone_tests {
         }
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_twist_limits() {
+        let mut job = new_ik_two_bone_job();
+        job.set_pole_vector(Vec3A::Y);
+        job.set_target(Vec3A::new(1.0, 1.0, 0.0));
+        assert_eq!(job.twist_limits(), (f32::NEG_INFINITY, f32::INFINITY));
+
+        let twist_axis = Vec3::new(consts::FRAC_1_SQRT_2, consts::FRAC_1_SQRT_2, 0.0);
+        job.set_twist_limits(consts::FRAC_PI_2, -consts::FRAC_PI_2);
+        assert_eq!(job.twist_limits(), (-consts::FRAC_PI_2, consts::FRAC_PI_2));
+
+        // saturates at 90 degree
+        job.set_twist_angle(consts::PI);
+        job.run().unwrap();
+        assert!(job
+            .start_joint_correction()
+            .abs_diff_eq(Quat::from_axis_angle(twist_axis, consts::FRAC_PI_2), 2e-3));
+        assert!(job.mid_joint_correction().abs_diff_eq(Quat::IDENTITY, 2e-3));
+        assert_eq!(job.twist_angle(), consts::PI);
+
+        job.set_twist_angle(-consts::PI);
+        job.run().unwrap();
+        assert!(job
+            .start_joint_correction()
+            .abs_diff_eq(Quat::from_axis_angle(twist_axis, -consts::FRAC_PI_2), 2e-3));
+
+        // within limits
+        job.set_twist_angle(0.5);
+        job.run().unwrap();
+        assert!(job
+            .start_joint_correction()
+            .abs_diff_eq(Quat::from_axis_angle(twist_axis, 0.5), 2e-3));
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_weight() {
@@ -859,6 +2271,30 @@ mod ik_two_bone_tests {
         }
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_weight_output_degenerate() {
+        let mut job = new_ik_two_bone_job();
+        let half_turn = f32x4::from_array([0.0, 0.0, 1.0, 0.0]);
+
+        // ~180 degrees correction at a tiny weight
+        for weight in [1e-30, 1e-7, 0.0, -1.0] {
+            job.set_weight(weight);
+            job.weight_output(half_turn, -half_turn);
+            assert!(job.start_joint_correction().is_finite());
+            assert!(job.mid_joint_correction().is_finite());
+            assert!(job.start_joint_correction().abs_diff_eq(Quat::IDENTITY, 1e-6));
+            assert!(job.mid_joint_correction().abs_diff_eq(Quat::IDENTITY, 1e-6));
+        }
+
+        // lerped quaternion nearly cancels out
+        job.set_weight(1.0 - f32::EPSILON / 2.0);
+        job.weight_output(ZERO, half_turn);
+        assert_eq!(job.start_joint_correction(), Quat::IDENTITY);
+        assert!(job.mid_joint_correction().is_finite());
+        assert!(job.mid_joint_correction().is_normalized());
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_pole_target_alignment() {
@@ -869,7 +2305,10 @@ mod ik_two_bone_tests {
             job.set_target(Vec3A::new(0.0, consts::SQRT_2, 0.0));
             job.run().unwrap();
             assert!(job.reached());
-            assert!(job.start_joint_correction().is_nan());
+            assert!(job.start_joint_correction().is_finite());
+            assert!(job
+                .start_joint_correction()
+                .abs_diff_eq(Quat::from_axis_angle(Vec3::Z, consts::FRAC_PI_4), 2e-3));
             assert!(job.mid_joint_correction().abs_diff_eq(Quat::IDENTITY, 2e-3));
         }
 
@@ -889,7 +2328,8 @@ mod ik_two_bone_tests {
             job.set_target(Vec3A::new(0.0, 3.0, 0.0));
             job.run().unwrap();
             assert!(!job.reached());
-            assert!(job.start_joint_correction().is_nan());
+            assert!(job.start_joint_correction().is_finite());
+            assert!(job.start_joint_correction().abs_diff_eq(Quat::IDENTITY, 2e-3));
             assert!(job
                 .mid_joint_correction()
                 .abs_diff_eq(Quat::from_axis_angle(Vec3::Z, consts::FRAC_PI_2), 2e-3));
@@ -940,6 +2380,33 @@ mod ik_two_bone_tests {
         }
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_try_set_mid_axis() {
+        let mut job = new_ik_two_bone_job();
+        let mid_axis = job.mid_axis();
+
+        assert!(job.try_set_mid_axis(Vec3A::ZERO).unwrap_err().is_invalid_job());
+        assert!(job.try_set_mid_axis(Vec3A::splat(1e-30)).unwrap_err().is_invalid_job());
+        assert!(job.try_set_mid_axis(Vec3A::NAN).unwrap_err().is_invalid_job());
+        assert_eq!(job.mid_axis(), mid_axis);
+
+        job.try_set_mid_axis(mid_axis * 3.0).unwrap();
+        assert!(job.mid_axis().abs_diff_eq(mid_axis, 1e-6));
+        job.set_target(Vec3A::new(1.0, 1.0, 0.0));
+        assert!(job.validate());
+        job.run().unwrap();
+        assert!(job.reached());
+
+        job.set_mid_axis_normalized(Vec3A::new(0.0, 0.0, 0.5));
+        assert!(job.mid_axis().abs_diff_eq(Vec3A::Z, 1e-6));
+        assert!(job.run().is_ok());
+
+        job.set_mid_axis_normalized(Vec3A::ZERO);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_aligned_joints_and_target() {
@@ -46,44 +46,98 @@
 //!
 
 #![feature(portable_simd)]
+#![cfg_attr(not(feature = "std"), no_std)]
 #![allow(unexpected_cfgs)] // TODO: Upgrade rkyv to 0.8
 
+extern crate alloc;
+
 pub mod animation;
+pub mod animation_builder;
+#[cfg(feature = "std")]
 pub mod archive;
 pub mod base;
 pub mod blending_job;
+pub mod bounds;
+pub mod buffer_pool;
+#[cfg(feature = "std")]
 mod endian;
+pub mod foot_ik;
+#[cfg(feature = "gltf")]
+pub mod gltf;
 pub mod ik_aim_job;
 pub mod ik_two_bone_job;
+pub mod local_to_aos_job;
 pub mod local_to_model_job;
 pub mod math;
+pub mod mirror_job;
+#[cfg(feature = "std")]
+pub mod name_interner;
 #[cfg(all(feature = "wasm", feature = "nodejs"))]
 pub mod nodejs;
+pub mod raw_animation;
 pub mod sampling_job;
 pub mod skeleton;
 pub mod skinning_job;
+pub mod skinning_matrices_job;
 pub mod track;
 pub mod track_sampling_job;
 pub mod track_triggering_job;
+pub mod transition_job;
+pub mod transpose;
 
-pub use animation::Animation;
+pub use animation::{Animation, Ratio, Seconds};
+pub use animation_builder::AnimationBuilder;
+#[cfg(feature = "std")]
 pub use archive::{Archive, ArchiveRead};
+#[cfg(feature = "std")]
+pub use base::{ozz_arc_buf, OzzArcBuf};
 pub use base::{
-    ozz_arc_buf, ozz_rc_buf, OzzArcBuf, OzzBuf, OzzError, OzzMutBuf, OzzObj, OzzRcBuf, SKELETON_MAX_JOINTS,
-    SKELETON_MAX_SOA_JOINTS, SKELETON_NO_PARENT,
+    ozz_rc_buf, OzzBuf, OzzError, OzzMutBuf, OzzObj, OzzRcBuf, SKELETON_MAX_JOINTS, SKELETON_MAX_SOA_JOINTS,
+    SKELETON_NO_PARENT,
 };
-pub use blending_job::{BlendingContext, BlendingJob, BlendingJobArc, BlendingJobRc, BlendingJobRef, BlendingLayer};
+#[cfg(feature = "std")]
+pub use blending_job::BlendingJobArc;
+pub use blending_job::{BlendingContext, BlendingJob, BlendingJobRc, BlendingJobRef, BlendingLayer};
+pub use bounds::{compute_bounds, compute_bounds_soa, compute_bounds_with};
+pub use buffer_pool::BufferPool;
+pub use foot_ik::{foot_ik, FootIK};
+#[cfg(feature = "gltf")]
+pub use gltf::{GltfChannels, GltfInterpolation, GltfSampler};
 pub use ik_aim_job::IKAimJob;
-pub use ik_two_bone_job::IKTwoBoneJob;
-pub use local_to_model_job::{LocalToModelJob, LocalToModelJobArc, LocalToModelJobRc, LocalToModelJobRef};
-pub use math::{SoaQuat, SoaTransform, SoaVec3};
+pub use ik_two_bone_job::{IKTwoBoneJob, IKTwoBoneJobBuilder};
+#[cfg(feature = "std")]
+pub use local_to_aos_job::LocalToAosJobArc;
+pub use local_to_aos_job::{AosTransform, LocalToAosJob, LocalToAosJobRc, LocalToAosJobRef};
+#[cfg(feature = "std")]
+pub use local_to_model_job::LocalToModelJobArc;
+pub use local_to_model_job::{LocalToModelJob, LocalToModelJobRc, LocalToModelJobRef};
+pub use math::{SoaQuat, SoaTransform, SoaVec3, NORMALIZATION_TOLERANCE_SQ};
+#[cfg(feature = "std")]
+pub use mirror_job::MirrorJobArc;
+pub use mirror_job::{MirrorAxis, MirrorJob, MirrorJobRc, MirrorJobRef};
+#[cfg(feature = "std")]
+pub use name_interner::NameInterner;
+pub use raw_animation::{RawAnimation, RawJointTrack};
+#[cfg(feature = "std")]
+pub use sampling_job::SamplingJobArc;
 pub use sampling_job::{
-    InterpSoaFloat3, InterpSoaQuaternion, SamplingContext, SamplingJob, SamplingJobArc, SamplingJobRc, SamplingJobRef,
+    InterpSoaFloat3, InterpSoaQuaternion, Interpolation, RotationInterp, SamplingContext, SamplingJob, SamplingJobRc,
+    SamplingJobRef, SamplingStats, TRS_INTERLEAVED_STRIDE,
 };
 pub use skeleton::{JointHashMap, Skeleton};
-pub use skinning_job::{SkinningJob, SkinningJobArc, SkinningJobRc, SkinningJobRef};
+#[cfg(feature = "std")]
+pub use skinning_job::SkinningJobArc;
+pub use skinning_job::{skin_vertices, SkinningJob, SkinningJobRc, SkinningJobRef, SkinningMatrices};
+#[cfg(feature = "std")]
+pub use skinning_matrices_job::SkinningMatricesJobArc;
+pub use skinning_matrices_job::{
+    SkinningMatricesJob, SkinningMatricesJobRc, SkinningMatricesJobRef, AFFINE_MATRIX_STRIDE,
+};
 pub use track::Track;
 pub use track_sampling_job::{TrackSamplingJob, TrackSamplingJobArc, TrackSamplingJobRc, TrackSamplingJobRef};
 pub use track_triggering_job::{
     Edge, TrackTriggeringJob, TrackTriggeringJobArc, TrackTriggeringJobRc, TrackTriggeringJobRef,
 };
+#[cfg(feature = "std")]
+pub use transition_job::TransitionJobArc;
+pub use transition_job::{TransitionEase, TransitionJob, TransitionJobRc, TransitionJobRef};
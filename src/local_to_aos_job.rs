@@ -0,0 +1,297 @@
+//!
+//! Local to AoS Job.
+//!
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use glam::{Quat, Vec3};
+#[cfg(feature = "std")]
+use std::sync::{Arc, RwLock};
+
+use crate::base::{OzzBuf, OzzError, OzzMutBuf, OzzObj};
+use crate::math::SoaTransform;
+use crate::skeleton::Skeleton;
+
+/// Local-space transform of a single joint, as (translation, rotation, scale).
+pub type AosTransform = (Vec3, Quat, Vec3);
+
+///
+/// Converts local-space `SoaTransform` to per-joint `AosTransform`.
+///
+/// Engines (like Bevy) usually consume a translation, rotation and scale per joint entity, rather than SoA
+/// buffers. This job does the SoA to AoS transpose once for the whole skeleton.
+///
+/// Job input is an array of SoaTransform objects (in local-space), ordered like skeleton's joints. Job output is
+/// an array of `AosTransform` (in local-space), ordered like skeleton's joints. Padding lanes of the last
+/// SoaTransform are skipped.
+///
+#[derive(Debug)]
+pub struct LocalToAosJob<S = Rc<Skeleton>, I = Rc<RefCell<Vec<SoaTransform>>>, O = Rc<RefCell<Vec<AosTransform>>>>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<AosTransform>,
+{
+    skeleton: Option<S>,
+    input: Option<I>,
+    output: Option<O>,
+}
+
+pub type LocalToAosJobRef<'t> = LocalToAosJob<&'t Skeleton, &'t [SoaTransform], &'t mut [AosTransform]>;
+pub type LocalToAosJobRc = LocalToAosJob<Rc<Skeleton>, Rc<RefCell<Vec<SoaTransform>>>, Rc<RefCell<Vec<AosTransform>>>>;
+#[cfg(feature = "std")]
+pub type LocalToAosJobArc =
+    LocalToAosJob<Arc<Skeleton>, Arc<RwLock<Vec<SoaTransform>>>, Arc<RwLock<Vec<AosTransform>>>>;
+
+impl<S, I, O> Default for LocalToAosJob<S, I, O>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<AosTransform>,
+{
+    fn default() -> LocalToAosJob<S, I, O> {
+        LocalToAosJob {
+            skeleton: None,
+            input: None,
+            output: None,
+        }
+    }
+}
+
+impl<S, I, O> LocalToAosJob<S, I, O>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<AosTransform>,
+{
+    /// Gets skeleton of `LocalToAosJob`.
+    #[inline]
+    pub fn skeleton(&self) -> Option<&S> {
+        self.skeleton.as_ref()
+    }
+
+    /// Sets skeleton of `LocalToAosJob`.
+    ///
+    /// The Skeleton object defining the number of joints to convert.
+    #[inline]
+    pub fn set_skeleton(&mut self, skeleton: S) {
+        self.skeleton = Some(skeleton);
+    }
+
+    /// Clears skeleton of `LocalToAosJob`.
+    #[inline]
+    pub fn clear_skeleton(&mut self) {
+        self.skeleton = None;
+    }
+
+    /// Gets input of `LocalToAosJob`.
+    #[inline]
+    pub fn input(&self) -> Option<&I> {
+        self.input.as_ref()
+    }
+
+    /// Sets input of `LocalToAosJob`.
+    ///
+    /// The input range that store local transforms.
+    #[inline]
+    pub fn set_input(&mut self, input: I) {
+        self.input = Some(input);
+    }
+
+    /// Clears input of `LocalToAosJob`.
+    #[inline]
+    pub fn clear_input(&mut self) {
+        self.input = None;
+    }
+
+    /// Gets output of `LocalToAosJob`.
+    #[inline]
+    pub fn output(&self) -> Option<&O> {
+        self.output.as_ref()
+    }
+
+    /// Sets output of `LocalToAosJob`.
+    ///
+    /// The output range to be filled with local-space transforms, one for each joint.
+    #[inline]
+    pub fn set_output(&mut self, output: O) {
+        self.output = Some(output);
+    }
+
+    /// Clears output of `LocalToAosJob`.
+    #[inline]
+    pub fn clear_output(&mut self) {
+        self.output = None;
+    }
+
+    /// Validates `LocalToAosJob` parameters.
+    pub fn validate(&self) -> bool {
+        (|| {
+            let skeleton = self.skeleton.as_ref()?.obj();
+            let input = self.input.as_ref()?.buf().ok()?;
+            let output = self.output.as_ref()?.buf().ok()?;
+
+            let mut ok = input.len() >= skeleton.num_soa_joints();
+            ok &= output.len() >= skeleton.num_joints();
+            Some(ok)
+        })()
+        .unwrap_or(false)
+    }
+
+    /// Runs local to AoS job's task.
+    /// The validate job before any operation is performed.
+    pub fn run(&mut self) -> Result<(), OzzError> {
+        let skeleton = self.skeleton.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let input = self.input.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+
+        let mut ok = input.len() >= skeleton.num_soa_joints();
+        ok &= output.len() >= skeleton.num_joints();
+        if !ok {
+            return Err(OzzError::InvalidJob);
+        }
+
+        for (idx, out) in output.iter_mut().take(skeleton.num_joints()).enumerate() {
+            let transform = &input[idx / 4];
+            let lane = idx & 3;
+            *out = (
+                transform.translation.vec3(lane),
+                transform.rotation.quat(lane),
+                transform.scale.vec3(lane),
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod local_to_aos_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::base::DeterministicState;
+    use crate::skeleton::{JointHashMap, SkeletonRaw};
+
+    fn new_skeleton() -> Rc<Skeleton> {
+        // 5 joints
+        //   j0
+        //  /  \
+        // j1  j2
+        //  |   |
+        // j3  j4
+        Rc::new(Skeleton::from_raw(&SkeletonRaw {
+            joint_rest_poses: vec![SoaTransform::IDENTITY; 2],
+            joint_names: {
+                let mut map = JointHashMap::with_hashers(DeterministicState::new(), DeterministicState::new());
+                map.insert("j0".into(), 0);
+                map.insert("j1".into(), 1);
+                map.insert("j2".into(), 2);
+                map.insert("j3".into(), 3);
+                map.insert("j4".into(), 4);
+                map
+            },
+            joint_parents: vec![-1, 0, 0, 1, 2],
+        }))
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validity() {
+        let skeleton = new_skeleton();
+
+        // empty skeleton
+        let mut job: LocalToAosJob = LocalToAosJob::default();
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        // empty input
+        let mut job: LocalToAosJob = LocalToAosJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_output(Rc::new(RefCell::new(vec![(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE); 5])));
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        // invalid input
+        let mut job: LocalToAosJob = LocalToAosJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(Rc::new(RefCell::new(vec![SoaTransform::IDENTITY; 1])));
+        job.set_output(Rc::new(RefCell::new(vec![(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE); 5])));
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        // invalid output
+        let mut job: LocalToAosJob = LocalToAosJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(Rc::new(RefCell::new(vec![SoaTransform::IDENTITY; 2])));
+        job.set_output(Rc::new(RefCell::new(vec![(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE); 4])));
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        // valid
+        let mut job: LocalToAosJob = LocalToAosJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(Rc::new(RefCell::new(vec![SoaTransform::IDENTITY; 2])));
+        job.set_output(Rc::new(RefCell::new(vec![(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE); 5])));
+        assert!(job.validate());
+        assert!(job.run().is_ok());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_run() {
+        let skeleton = new_skeleton();
+        let mut input = vec![SoaTransform::IDENTITY; 2];
+        for joint in 0..8 {
+            let f = joint as f32;
+            let transform = &mut input[joint / 4];
+            transform.translation.set_vec3(joint & 3, Vec3::new(f, f + 0.5, -f));
+            transform.rotation.set_quat(
+                joint & 3,
+                Quat::from_axis_angle(Vec3::new(1.0, f, 0.5).normalize(), f * 0.3),
+            );
+            transform.scale.set_vec3(joint & 3, Vec3::splat(1.0 + f));
+        }
+        let input = Rc::new(RefCell::new(input));
+
+        // padding output entry is left untouched
+        let sentinel = (Vec3::NAN, Quat::IDENTITY, Vec3::NAN);
+        let output = Rc::new(RefCell::new(vec![sentinel; 6]));
+
+        let mut job: LocalToAosJob = LocalToAosJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(input.clone());
+        job.set_output(output.clone());
+        job.run().unwrap();
+
+        let input = input.borrow();
+        let output = output.borrow();
+        for joint in 0..skeleton.num_joints() {
+            let soa = &input[joint / 4];
+            let lane = joint % 4;
+            let translation = Vec3::new(
+                soa.translation.x[lane],
+                soa.translation.y[lane],
+                soa.translation.z[lane],
+            );
+            let rotation = Quat::from_xyzw(
+                soa.rotation.x[lane],
+                soa.rotation.y[lane],
+                soa.rotation.z[lane],
+                soa.rotation.w[lane],
+            );
+            let scale = Vec3::new(soa.scale.x[lane], soa.scale.y[lane], soa.scale.z[lane]);
+            assert_eq!(output[joint], (translation, rotation, scale));
+        }
+        assert!(output[5].0.is_nan() && output[5].2.is_nan());
+
+        // same result with reference buffers
+        let mut aos = vec![sentinel; 5];
+        let mut job: LocalToAosJobRef = LocalToAosJob::default();
+        job.set_skeleton(&skeleton);
+        job.set_input(&input);
+        job.set_output(&mut aos);
+        job.run().unwrap();
+        assert_eq!(&aos[..], &output[..5]);
+    }
+}
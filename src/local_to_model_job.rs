@@ -2,9 +2,11 @@
 //! Local to Model Job.
 //!
 
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
 use glam::Mat4;
-use std::cell::RefCell;
-use std::rc::Rc;
+#[cfg(feature = "std")]
 use std::sync::{Arc, RwLock};
 
 use crate::base::{OzzBuf, OzzError, OzzIndex, OzzMutBuf, OzzObj, SKELETON_MAX_JOINTS, SKELETON_NO_PARENT};
@@ -42,6 +44,7 @@ where
 
 pub type LocalToModelJobRef<'t> = LocalToModelJob<&'t Skeleton, &'t [SoaTransform], &'t mut [Mat4]>;
 pub type LocalToModelJobRc = LocalToModelJob<Rc<Skeleton>, Rc<RefCell<Vec<SoaTransform>>>, Rc<RefCell<Vec<Mat4>>>>;
+#[cfg(feature = "std")]
 pub type LocalToModelJobArc = LocalToModelJob<Arc<Skeleton>, Arc<RwLock<Vec<SoaTransform>>>, Arc<RwLock<Vec<Mat4>>>>;
 
 impl<S, I, O> Default for LocalToModelJob<S, I, O>
@@ -216,6 +219,37 @@ where
         .unwrap_or(false)
     }
 
+    /// Computes the model-space matrix of a single joint.
+    ///
+    /// Only the parent chain of `joint` is composed, from the job's input local-space transforms and root
+    /// matrix. It doesn't need an output, nor take `from` and `to` into account. This is cheaper than `run()`
+    /// when only one or a few joints are needed, like the head for targeting.
+    pub fn joint_model_matrix(&self, joint: usize) -> Result<Mat4, OzzError> {
+        let skeleton = self.skeleton.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let input = self.input.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        if input.len() < skeleton.num_soa_joints() {
+            return Err(OzzError::InvalidJob);
+        }
+        if joint >= skeleton.num_joints() {
+            return Err(OzzError::InvalidIndex);
+        }
+
+        let mut chain = Vec::new();
+        let mut idx = joint as i32;
+        while idx != SKELETON_NO_PARENT {
+            chain.push(idx as usize);
+            idx = skeleton.joint_parent(idx as usize) as i32;
+        }
+
+        let mut matrix = self.root;
+        for &idx in chain.iter().rev() {
+            let transform = &input[idx / 4];
+            let soa_matrices = SoaMat4::from_affine(&transform.translation, &transform.rotation, &transform.scale);
+            matrix = AosMat4::mul(&matrix, &soa_matrices.to_aos()[idx & 3]);
+        }
+        Ok(matrix.into())
+    }
+
     /// Runs local to model job's task.
     /// The validate job before any operation is performed.
     pub fn run(&mut self) -> Result<(), OzzError> {
@@ -483,6 +517,34 @@ mod local_to_model_tests {
         ], "transformation root");
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_joint_model_matrix() {
+        let skeleton = new_skeleton2();
+        let input = new_input2();
+        let output = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+
+        let mut job = LocalToModelJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(input.clone());
+        job.set_root(&Mat4::from_translation(Vec3::new(4.0, 3.0, 2.0)));
+        job.set_output(output.clone());
+        job.run().unwrap();
+
+        // j5 is the deepest joint, j0 -> j3 -> j4 -> j5
+        for joint in [5, 0, 2, 6, 7] {
+            let matrix = job.joint_model_matrix(joint).unwrap();
+            assert_eq!(matrix, output.borrow()[joint], "joint={}", joint);
+        }
+        assert!(job.joint_model_matrix(8).unwrap_err().is_invalid_index());
+
+        let mut job: LocalToModelJob = LocalToModelJob::default();
+        job.set_skeleton(skeleton.clone());
+        assert!(job.joint_model_matrix(5).unwrap_err().is_invalid_job());
+        job.set_input(Rc::new(RefCell::new(vec![SoaTransform::IDENTITY; 1])));
+        assert!(job.joint_model_matrix(5).unwrap_err().is_invalid_job());
+    }
+
     #[test]
     #[wasm_bindgen_test]
     #[rustfmt::skip]
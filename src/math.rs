@@ -6,20 +6,105 @@
 
 #![allow(dead_code)]
 
-use glam::{Mat4, Quat, Vec3, Vec3A, Vec4};
+use core::fmt::Debug;
+use core::simd::prelude::*;
+use core::{mem, slice};
+use glam::{EulerRot, Mat4, Quat, Vec3, Vec3A, Vec4};
 #[cfg(feature = "glam-ext")]
 use glam_ext::Transform3A;
 use static_assertions::const_assert_eq;
-use std::fmt::Debug;
+#[cfg(feature = "std")]
 use std::io::Read;
-use std::mem;
-use std::simd::prelude::*;
-use std::simd::*;
+#[cfg(feature = "std")]
+pub(crate) use std::simd::StdFloat;
 
+#[cfg(feature = "std")]
 use crate::archive::{Archive, ArchiveRead};
+#[cfg(feature = "std")]
 use crate::base::OzzError;
+#[cfg(feature = "std")]
 use crate::math;
 
+/// Public SIMD helpers, to build custom jobs (like IK ones) on `f32x4` values as this crate does.
+///
+/// Vectors are `f32x4` with xyz components, quaternions are `f32x4` in xyzw order. `_s` suffixed functions
+/// return a scalar in the x lane, other lanes are undefined.
+///
+/// ```
+/// #![feature(portable_simd)]
+/// use std::simd::prelude::*;
+/// use ozz_animation_rs::math::prelude::*;
+///
+/// let x = f32x4::from_array([2.0, 0.0, 0.0, 0.0]);
+/// let y = f32x4::from_array([0.0, 3.0, 0.0, 0.0]);
+/// assert_eq!(vec3_length2_s(x)[0], 4.0);
+/// assert_eq!(vec3_length_s(y)[0], 3.0);
+/// assert_eq!(vec3_dot_s(x, y)[0], 0.0);
+/// assert_eq!(vec3_cross(x, y).to_array()[..3], [0.0, 0.0, 6.0]);
+///
+/// let unit = vec3_normalize(x);
+/// assert!(vec3_is_normalized(unit));
+/// assert!(!vec3_is_normalized(x));
+/// assert!(vec3_is_normalized_with(x * f32x4::splat(0.51), 0.05));
+///
+/// // quarter turn around z
+/// let q = quat_from_vectors(x, y);
+/// let half = std::f32::consts::FRAC_1_SQRT_2;
+/// assert!((q - f32x4::from_array([0.0, 0.0, half, half])).abs().reduce_max() < 1e-6);
+/// ```
+pub mod prelude {
+    pub use super::{
+        quat_from_vectors, vec3_cross, vec3_dot_s, vec3_is_normalized, vec3_is_normalized_with, vec3_length2_s,
+        vec3_length_s, vec3_normalize, NORMALIZATION_TOLERANCE_SQ,
+    };
+}
+
+/// Replaces `std::simd::StdFloat` without std, values are computed by `libm`.
+///
+/// Also implemented for `f32`, whose float methods are provided by std as well.
+#[cfg(not(feature = "std"))]
+pub(crate) trait StdFloat {
+    fn sqrt(self) -> Self;
+    fn trunc(self) -> Self;
+    fn floor(self) -> Self;
+}
+
+#[cfg(not(feature = "std"))]
+impl StdFloat for f32x4 {
+    #[inline]
+    fn sqrt(self) -> f32x4 {
+        f32x4::from_array(self.to_array().map(libm::sqrtf))
+    }
+
+    #[inline]
+    fn trunc(self) -> f32x4 {
+        f32x4::from_array(self.to_array().map(libm::truncf))
+    }
+
+    #[inline]
+    fn floor(self) -> f32x4 {
+        f32x4::from_array(self.to_array().map(libm::floorf))
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl StdFloat for f32 {
+    #[inline]
+    fn sqrt(self) -> f32 {
+        libm::sqrtf(self)
+    }
+
+    #[inline]
+    fn trunc(self) -> f32 {
+        libm::truncf(self)
+    }
+
+    #[inline]
+    fn floor(self) -> f32 {
+        libm::floorf(self)
+    }
+}
+
 pub(crate) const ZERO: f32x4 = f32x4::from_array([0.0; 4]);
 pub(crate) const ONE: f32x4 = f32x4::from_array([1.0; 4]);
 pub(crate) const TWO: f32x4 = f32x4::from_array([2.0; 4]);
@@ -35,6 +120,10 @@ pub(crate) const Z_AXIS: f32x4 = f32x4::from_array([0.0, 0.0, 1.0, 0.0]);
 
 pub(crate) const QUAT_UNIT: f32x4 = f32x4::from_array([0.0, 0.0, 0.0, 1.0]);
 
+/// Tolerance on the squared length of a vector for it to be considered normalized, as used by jobs
+/// validation (e.g. `IKTwoBoneJob` mid axis, `IKAimJob` forward).
+pub const NORMALIZATION_TOLERANCE_SQ: f32 = 0.002;
+
 const SIGN: i32x4 = i32x4::from_array([i32::MIN; 4]);
 const SIGN_W: i32x4 = i32x4::from_array([0, 0, 0, i32::MIN]);
 
@@ -103,6 +192,17 @@ impl SoaVec3 {
         self.z[idx] = v.z;
     }
 
+    /// Returns true if the absolute difference of all lanes between `self` and `other` is less than or equal to
+    /// `max_abs_diff`.
+    #[inline]
+    pub fn abs_diff_eq(&self, other: &SoaVec3, max_abs_diff: f32) -> bool {
+        let max = f32x4::splat(max_abs_diff);
+        let mut ok = (self.x - other.x).abs().simd_le(max);
+        ok &= (self.y - other.y).abs().simd_le(max);
+        ok &= (self.z - other.z).abs().simd_le(max);
+        ok.all()
+    }
+
     #[inline]
     pub fn neg(&self) -> SoaVec3 {
         SoaVec3 {
@@ -148,6 +248,27 @@ impl SoaVec3 {
         }
     }
 
+    #[inline]
+    pub fn cross(&self, other: &SoaVec3) -> SoaVec3 {
+        SoaVec3 {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    /// Rotates each lane's vector by the corresponding lane of `rotation`, which must be normalized.
+    #[inline]
+    pub fn transform_by(&self, rotation: &SoaQuat) -> SoaVec3 {
+        let axis = SoaVec3 {
+            x: rotation.x,
+            y: rotation.y,
+            z: rotation.z,
+        };
+        let t = axis.cross(self).mul_num(f32x4::splat(2.0));
+        self.add(&t.mul_num(rotation.w)).add(&axis.cross(&t))
+    }
+
     #[inline]
     pub fn and_num(&self, i: i32x4) -> SoaVec3 {
         SoaVec3 {
@@ -301,6 +422,20 @@ impl SoaQuat {
         self.w[idx] = q.w;
     }
 
+    /// Returns true if the absolute difference of all lanes between `self` and `other` is less than or equal to
+    /// `max_abs_diff`.
+    ///
+    /// Components are compared as is, `q` and `-q` are not considered equal.
+    #[inline]
+    pub fn abs_diff_eq(&self, other: &SoaQuat, max_abs_diff: f32) -> bool {
+        let max = f32x4::splat(max_abs_diff);
+        let mut ok = (self.x - other.x).abs().simd_le(max);
+        ok &= (self.y - other.y).abs().simd_le(max);
+        ok &= (self.z - other.z).abs().simd_le(max);
+        ok &= (self.w - other.w).abs().simd_le(max);
+        ok.all()
+    }
+
     #[inline]
     pub fn conjugate(&self) -> SoaQuat {
         SoaQuat {
@@ -357,6 +492,21 @@ impl SoaQuat {
         self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
     }
 
+    /// Renormalizes all lanes of all quaternions in place, to fix drift accumulated by successive operations.
+    ///
+    /// Lanes too close to zero length to be normalized are set to identity.
+    pub fn normalize_slice(rotations: &mut [SoaQuat]) {
+        for rotation in rotations.iter_mut() {
+            let len2 = rotation.dot(rotation);
+            let valid = len2.simd_gt(f32x4::splat(f32::EPSILON));
+            let inv_len = len2.sqrt().recip();
+            rotation.x = valid.select(rotation.x * inv_len, ZERO);
+            rotation.y = valid.select(rotation.y * inv_len, ZERO);
+            rotation.z = valid.select(rotation.z * inv_len, ZERO);
+            rotation.w = valid.select(rotation.w * inv_len, ONE);
+        }
+    }
+
     #[inline]
     pub fn nlerp(&self, other: &SoaQuat, f: f32x4) -> SoaQuat {
         let lerp_x = (other.x - self.x) * f + self.x;
@@ -373,6 +523,41 @@ impl SoaQuat {
         }
     }
 
+    /// Spherical linear interpolation, rotates at constant angular velocity from `self` to `other`.
+    ///
+    /// Like `nlerp`, quaternions are not flipped to the same hemisphere. Lanes with nearly identical
+    /// quaternions (cos > 0.9995) fall back to `nlerp`. Nearly opposite quaternions are interpolated along the
+    /// long arc, except exactly opposite ones (cos < -0.9999995), which have no defined arc and fall back to
+    /// `nlerp` as well.
+    #[inline]
+    pub fn slerp(&self, other: &SoaQuat, f: f32x4) -> SoaQuat {
+        const NLERP_THRESHOLD: f32x4 = f32x4::from_array([0.9995; 4]);
+        const OPPOSITE_THRESHOLD: f32x4 = f32x4::from_array([-0.9999995; 4]);
+
+        let cos = self.dot(other).simd_clamp(NEG_ONE, ONE);
+        let theta = fx4_acos(cos);
+        let (sin, _) = fx4_sin_cos(theta);
+        let (sin0, _) = fx4_sin_cos((ONE - f) * theta);
+        let (sin1, _) = fx4_sin_cos(f * theta);
+        let inv_sin = sin.recip();
+        let (f0, f1) = (sin0 * inv_sin, sin1 * inv_sin);
+        let slerp = SoaQuat {
+            x: self.x * f0 + other.x * f1,
+            y: self.y * f0 + other.y * f1,
+            z: self.z * f0 + other.z * f1,
+            w: self.w * f0 + other.w * f1,
+        };
+
+        let nlerp = self.nlerp(other, f);
+        let mask = cos.simd_gt(NLERP_THRESHOLD) | cos.simd_lt(OPPOSITE_THRESHOLD);
+        SoaQuat {
+            x: mask.select(nlerp.x, slerp.x),
+            y: mask.select(nlerp.y, slerp.y),
+            z: mask.select(nlerp.z, slerp.z),
+            w: mask.select(nlerp.w, slerp.w),
+        }
+    }
+
     #[inline]
     pub fn and_num(&self, i: i32x4) -> SoaQuat {
         SoaQuat {
@@ -496,6 +681,7 @@ pub struct SoaTransform {
     pub scale: SoaVec3,
 }
 
+#[cfg(feature = "std")]
 impl ArchiveRead<SoaTransform> for SoaTransform {
     #[inline]
     fn read<R: Read>(archive: &mut Archive<R>) -> Result<SoaTransform, OzzError> {
@@ -521,6 +707,35 @@ impl SoaTransform {
             scale,
         }
     }
+
+    /// Returns true if the absolute difference of all lanes of translation, rotation and scale between `self` and
+    /// `other` is less than or equal to `max_abs_diff`.
+    #[inline]
+    pub fn abs_diff_eq(&self, other: &SoaTransform, max_abs_diff: f32) -> bool {
+        self.translation.abs_diff_eq(&other.translation, max_abs_diff)
+            && self.rotation.abs_diff_eq(&other.rotation, max_abs_diff)
+            && self.scale.abs_diff_eq(&other.scale, max_abs_diff)
+    }
+
+    /// Renormalizes rotations of all transforms of `pose` in place, see `SoaQuat::normalize_slice()`.
+    ///
+    /// Translations and scales are left unchanged, as they have no normalized form to go back to.
+    pub fn normalize_pose(pose: &mut [SoaTransform]) {
+        for transform in pose.iter_mut() {
+            SoaQuat::normalize_slice(slice::from_mut(&mut transform.rotation));
+        }
+    }
+
+    /// Returns true if both poses have the same length, and all their transforms are `abs_diff_eq()`.
+    ///
+    /// Padding lanes are compared as well.
+    pub fn slice_abs_diff_eq(pose: &[SoaTransform], other: &[SoaTransform], max_abs_diff: f32) -> bool {
+        pose.len() == other.len()
+            && pose
+                .iter()
+                .zip(other.iter())
+                .all(|(a, b)| a.abs_diff_eq(b, max_abs_diff))
+    }
 }
 
 #[cfg(feature = "glam-ext")]
@@ -779,7 +994,7 @@ impl AosMat4 {
         let mut det = c0 * minor0;
         det = simd_swizzle!(det, I4E) + det;
         det = simd_swizzle!(det, IB1) + det; // first
-        let invertible: i32x4 = det.simd_ne(ZERO).to_int(); // first
+        let invertible: i32x4 = det.simd_ne(ZERO).to_simd(); // first
 
         let det_recip = det.recip(); // first
                                      // det_recip = (det_recip + det_recip) - det_recip * det_recip * det; // first
@@ -921,6 +1136,32 @@ pub(crate) fn f16_to_f32(n: u16) -> f32 {
     f32::from_bits((sign << 16) | scaled)
 }
 
+#[inline]
+pub(crate) fn f32_to_f16(f: f32) -> u16 {
+    const F32_INFTY: u32 = 255 << 23;
+    const F16_INFTY: u32 = 31 << 23;
+    const ROUND_MASK: u32 = !0xFFF;
+    let magic = f32::from_bits(15 << 23);
+
+    let n = f.to_bits();
+    let sign = n & 0x80000000;
+    let no_sign = n ^ sign;
+    let half = if no_sign >= F32_INFTY {
+        // NaN -> qNaN and Inf -> Inf
+        if no_sign > F32_INFTY {
+            0x7E00
+        } else {
+            0x7C00
+        }
+    } else {
+        let rounded = f32::from_bits(no_sign & ROUND_MASK) * magic;
+        let scaled = rounded.to_bits().wrapping_sub(ROUND_MASK);
+        // clamp to signed infinity if overflowed
+        u32::min(scaled, F16_INFTY) >> 13
+    };
+    (half | (sign >> 16)) as u16
+}
+
 #[inline]
 pub(crate) fn simd_f16_to_f32(half4: [u16; 4]) -> f32x4 {
     const MASK_NO_SIGN: i32x4 = i32x4::from_array([0x7FFF; 4]);
@@ -932,7 +1173,7 @@ pub(crate) fn simd_f16_to_f32(half4: [u16; 4]) -> f32x4 {
     let expmant = MASK_NO_SIGN & int4;
     let shifted = expmant << 13;
     let scaled = fx4(shifted) * MAGIC;
-    let was_infnan = i32x4::simd_ge(expmant, WAS_INFNAN).to_int();
+    let was_infnan = i32x4::simd_gt(expmant, WAS_INFNAN).to_simd();
     let sign = (int4 ^ expmant) << 16;
     let infnanexp = was_infnan & EXP_INFNAN;
     let sign_inf = sign | infnanexp;
@@ -1046,7 +1287,7 @@ pub(crate) fn fx4_sign(v: f32x4) -> i32x4 {
     // In some case, x86_64 and aarch64 may produce different sign NaN (+/-NaN) in same command.
     // So the result of `NaN & SIGN` may be different.
     // For cross-platform deterministic, we want to make sure the sign of NaN is always 0(+).
-    v.simd_lt(ZERO).to_int() & SIGN
+    v.simd_lt(ZERO).to_simd() & SIGN
 }
 
 #[inline(always)]
@@ -1239,31 +1480,69 @@ pub fn f32_acos(x: f32) -> f32 {
     fx4_acos(f32x4::splat(x))[0]
 }
 
+/// Converts a quaternion to Euler angles (in radians), for display or edition in tools.
+///
+/// Angles are returned in `order` sequence, e.g. `(x, y, z)` for `EulerRot::XYZ` and `(z, y, x)` for
+/// `EulerRot::ZYX`, following glam conventions. At gimbal lock, the result is one of the equivalent solutions.
+/// Not deterministic across platforms, so it shouldn't be used by the runtime.
+#[inline]
+pub fn quat_to_euler(q: Quat, order: EulerRot) -> Vec3 {
+    let (a, b, c) = q.to_euler(order);
+    Vec3::new(a, b, c)
+}
+
+/// Converts Euler angles (in radians, in `order` sequence) to a quaternion. See `quat_to_euler()`.
+#[inline]
+pub fn euler_to_quat(angles: Vec3, order: EulerRot) -> Quat {
+    Quat::from_euler(order, angles.x, angles.y, angles.z)
+}
+
 #[inline]
 pub(crate) fn fx4_lerp(from: f32x4, to: f32x4, alpha: f32x4) -> f32x4 {
     alpha * (to - from) + from
 }
 
+/// Checks if the xyz components of `v` are normalized, within `NORMALIZATION_TOLERANCE_SQ` on the squared
+/// length.
+#[inline]
+pub fn vec3_is_normalized(v: f32x4) -> bool {
+    vec3_is_normalized_with(v, NORMALIZATION_TOLERANCE_SQ)
+}
+
+/// Checks if the xyz components of `v` are normalized, within `epsilon` on the squared length.
 #[inline]
-pub(crate) fn vec3_is_normalized(v: f32x4) -> bool {
-    const MAX: f32 = 1.0 + 0.002;
-    const MIN: f32 = 1.0 - 0.002;
+pub fn vec3_is_normalized_with(v: f32x4, epsilon: f32) -> bool {
     let len2 = v[0] * v[0] + v[1] * v[1] + v[2] * v[2];
-    (MIN < len2) & (len2 < MAX)
+    (1.0 - epsilon < len2) & (len2 < 1.0 + epsilon)
 }
 
+/// Computes the squared length of the xyz components of `v`, in the x lane of the result.
 #[inline]
-pub(crate) fn vec3_length2_s(v: f32x4) -> f32x4 {
+pub fn vec3_length2_s(v: f32x4) -> f32x4 {
     vec3_dot_s(v, v)
 }
 
+/// Computes the length of the xyz components of `v`, in the x lane of the result.
 #[inline]
-pub(crate) fn vec3_dot_s(a: f32x4, b: f32x4) -> f32x4 {
+pub fn vec3_length_s(v: f32x4) -> f32x4 {
+    vec3_length2_s(v).sqrt()
+}
+
+/// Normalizes the xyz components of `v`, w is scaled too. `v` must not be zero, or the result is NaN.
+#[inline]
+pub fn vec3_normalize(v: f32x4) -> f32x4 {
+    v * fx4_splat_x(vec3_length_s(v)).recip()
+}
+
+/// Computes the dot product of the xyz components of `a` and `b`, in the x lane of the result.
+#[inline]
+pub fn vec3_dot_s(a: f32x4, b: f32x4) -> f32x4 {
     let tmp = a * b;
     tmp + simd_swizzle!(tmp, [1; 4]) + simd_swizzle!(tmp, [2; 4])
 }
 
-pub(crate) fn vec3_cross(a: f32x4, b: f32x4) -> f32x4 {
+/// Computes the cross product of the xyz components of `a` and `b`, w is 0.
+pub fn vec3_cross(a: f32x4, b: f32x4) -> f32x4 {
     let shufa = simd_swizzle!(a, [1, 2, 0, 3]);
     let shufb = simd_swizzle!(b, [1, 2, 0, 3]);
     let shufc = a * shufb - b * shufa;
@@ -1285,7 +1564,11 @@ pub(crate) fn quat_from_cos_angle(axis: f32x4, cos: f32x4) -> f32x4 {
     fx4_set_w(axis * half_sin, half_cossin)
 }
 
-pub(crate) fn quat_from_vectors(from: f32x4, to: f32x4) -> f32x4 {
+/// Computes the quaternion (xyzw) rotating `from` direction to `to` direction, vectors don't need to be normalized.
+///
+/// Returns identity if either vector is zero. Opposite vectors are rotated by half a turn around an axis
+/// orthogonal to `from`.
+pub fn quat_from_vectors(from: f32x4, to: f32x4) -> f32x4 {
     let norm_from_norm_to = (vec3_length2_s(from) * vec3_length2_s(to)).sqrt();
     let norm_from_norm_to_x = norm_from_norm_to[0];
     if norm_from_norm_to_x < 1.0e-6 {
@@ -1341,7 +1624,7 @@ pub(crate) fn quat_mul(a: f32x4, b: f32x4) -> f32x4 {
 
 #[inline]
 pub(crate) fn quat_positive_w(q: f32x4) -> f32x4 {
-    let s = fx4_splat_w(q).simd_lt(ZERO).to_int() & SIGN;
+    let s = fx4_splat_w(q).simd_lt(ZERO).to_simd() & SIGN;
     fx4_xor(q, s)
 }
 
@@ -1366,6 +1649,22 @@ mod tests {
         assert!(f16_to_f32(0xFFFF).is_nan());
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_f32_to_f16() {
+        assert_eq!(f32_to_f16(1.0), 0b00111100_00000000);
+        assert_eq!(f32_to_f16(-1.0), 0b10111100_00000000);
+        assert_eq!(f32_to_f16(3.5), 0b01000011_00000000);
+        assert_eq!(f32_to_f16(f32::INFINITY), 0b01111100_00000000);
+        assert_eq!(f32_to_f16(f32::NEG_INFINITY), 0b11111100_00000000);
+        assert_eq!(f32_to_f16(1e6), 0b01111100_00000000);
+        assert_eq!(f32_to_f16(0.0), 0);
+        assert_eq!(f32_to_f16(-0.0), 0x8000);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+        assert_eq!(f16_to_f32(f32_to_f16(-1.37090683e-06)), -1.37090683e-06);
+        assert_eq!(f16_to_f32(f32_to_f16(0.0711059570)), 0.0711059570);
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_simd_f16_to_f32() {
@@ -1388,6 +1687,134 @@ mod tests {
         let half4 = [0xFFFF, 0, 0, 0];
         let float4 = simd_f16_to_f32(half4);
         assert!(float4[0].is_nan());
+
+        let half4 = [0x7BFF, 0xFBFF, 0, 0];
+        let float4 = simd_f16_to_f32(half4);
+        assert_eq!(float4, f32x4::from_array([65504.0, -65504.0, 0.0, 0.0]));
+    }
+
+    fn random_soa_quat(rng: &mut impl rand::Rng) -> (SoaQuat, [Quat; 4]) {
+        let mut soa = SoaQuat::IDENTITY;
+        let quats = [(); 4].map(|_| {
+            let axis = Vec3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(0.1..1.0),
+            );
+            Quat::from_axis_angle(axis.normalize(), rng.gen_range(-3.0..3.0))
+        });
+        for (idx, quat) in quats.iter().enumerate() {
+            soa.set_quat(idx, *quat);
+        }
+        (soa, quats)
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_soa_quat_ops() {
+        const EPSILON: f32 = 1e-5;
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..100 {
+            let (a, qa) = random_soa_quat(&mut rng);
+            let (b, qb) = random_soa_quat(&mut rng);
+            let f = f32x4::from_array([0.0, 0.25, 0.7, 1.0]);
+
+            let dot = a.dot(&b);
+            let mul = a.mul(&b);
+            let conjugate = a.conjugate();
+            let normalize = a.mul_num(f32x4::splat(3.0)).normalize();
+            let nlerp = a.nlerp(&b, f);
+            for idx in 0..4 {
+                assert!((dot[idx] - qa[idx].dot(qb[idx])).abs() < EPSILON);
+                assert!(mul.quat(idx).abs_diff_eq(qa[idx] * qb[idx], EPSILON));
+                assert!(conjugate.quat(idx).abs_diff_eq(qa[idx].conjugate(), EPSILON));
+                assert!(normalize.quat(idx).abs_diff_eq(qa[idx], EPSILON));
+                // SoA nlerp doesn't take the shortest path, unlike glam lerp.
+                let expected = (qa[idx] * (1.0 - f[idx]) + qb[idx] * f[idx]).normalize();
+                assert!(nlerp.quat(idx).abs_diff_eq(expected, EPSILON));
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_soa_quat_slerp() {
+        // nearly identical, orthogonal, nearly opposite and wide angle lanes
+        let qa = [
+            Quat::from_rotation_x(0.3),
+            Quat::from_rotation_y(0.5),
+            Quat::from_rotation_x(0.3),
+            Quat::from_rotation_z(-1.0),
+        ];
+        let qb = [
+            Quat::from_rotation_x(0.31),
+            Quat::from_rotation_y(0.5 + core::f32::consts::PI),
+            -Quat::from_rotation_x(0.32),
+            Quat::from_rotation_z(2.0),
+        ];
+        let (mut a, mut b) = (SoaQuat::splat_quat(Quat::IDENTITY), SoaQuat::splat_quat(Quat::IDENTITY));
+        for idx in 0..4 {
+            a.set_quat(idx, qa[idx]);
+            b.set_quat(idx, qb[idx]);
+        }
+        assert!(a.dot(&b)[2] < -0.9999);
+
+        for f in [0.0, 0.25, 0.5, 0.9, 1.0] {
+            let slerp = a.slerp(&b, f32x4::splat(f));
+            for idx in 0..4 {
+                // reference slerp, without hemisphere flip
+                let theta = qa[idx].dot(qb[idx]).clamp(-1.0, 1.0).acos();
+                let expected =
+                    (qa[idx] * ((1.0 - f) * theta).sin() + qb[idx] * (f * theta).sin()) * theta.sin().recip();
+                assert!(slerp.quat(idx).abs_diff_eq(expected, 1e-3), "f={} idx={}", f, idx);
+                assert!(slerp.quat(idx).is_normalized());
+            }
+        }
+    }
+
+    fn random_soa_vec3(rng: &mut impl rand::Rng) -> (SoaVec3, [Vec3; 4]) {
+        let mut soa = SoaVec3::splat(0.0);
+        let vecs = [(); 4].map(|_| {
+            Vec3::new(
+                rng.gen_range(-10.0..10.0),
+                rng.gen_range(-10.0..10.0),
+                rng.gen_range(-10.0..10.0),
+            )
+        });
+        for (idx, vec) in vecs.iter().enumerate() {
+            soa.set_vec3(idx, *vec);
+        }
+        (soa, vecs)
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_soa_vec3_ops() {
+        const EPSILON: f32 = 1e-4;
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..100 {
+            let (a, va) = random_soa_vec3(&mut rng);
+            let (b, vb) = random_soa_vec3(&mut rng);
+            let (q, qs) = random_soa_quat(&mut rng);
+            let f = f32x4::from_array([0.0, 0.25, 0.7, 1.0]);
+
+            let add = a.add(&b);
+            let sub = a.sub(&b);
+            let mul = a.mul_num(f);
+            let lerp = SoaVec3::lerp(&a, &b, f);
+            let cross = a.cross(&b);
+            let transformed = a.transform_by(&q);
+            for idx in 0..4 {
+                assert!(add.vec3(idx).abs_diff_eq(va[idx] + vb[idx], EPSILON));
+                assert!(sub.vec3(idx).abs_diff_eq(va[idx] - vb[idx], EPSILON));
+                assert!(mul.vec3(idx).abs_diff_eq(va[idx] * f[idx], EPSILON));
+                assert!(lerp.vec3(idx).abs_diff_eq(va[idx].lerp(vb[idx], f[idx]), EPSILON));
+                assert!(cross.vec3(idx).abs_diff_eq(va[idx].cross(vb[idx]), EPSILON));
+                assert!(transformed.vec3(idx).abs_diff_eq(qs[idx] * va[idx], EPSILON));
+            }
+        }
     }
 
     #[test]
@@ -1538,6 +1965,130 @@ mod tests {
         assert!(ma < 3.5e-7);
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_abs_diff_eq() {
+        let pose = vec![
+            SoaTransform::new(
+                SoaVec3::new([0.0, 1.0, -2.0, 0.5], [0.0; 4], [3.0; 4]),
+                SoaQuat::splat_quat(Quat::from_rotation_y(0.7)),
+                SoaVec3::ONE,
+            ),
+            SoaTransform::IDENTITY,
+        ];
+
+        let mut translation = pose.clone();
+        translation[0].translation.x[0] += 1e-7;
+        let mut rotation = pose.clone();
+        rotation[1].rotation.z[3] = 1e-7;
+        let mut scale = pose.clone();
+        scale[1].scale.y[2] = 1.0 - 1e-7;
+
+        for other in [&translation, &rotation, &scale] {
+            assert_ne!(&pose, other);
+            assert!(SoaTransform::slice_abs_diff_eq(&pose, other, 1e-5));
+            assert!(!SoaTransform::slice_abs_diff_eq(&pose, other, 1e-9));
+        }
+        assert!(pose[0].translation.abs_diff_eq(&translation[0].translation, 1e-5));
+        assert!(!pose[0].translation.abs_diff_eq(&translation[0].translation, 1e-9));
+        assert!(pose[1].rotation.abs_diff_eq(&rotation[1].rotation, 1e-5));
+        assert!(!pose[1].rotation.abs_diff_eq(&rotation[1].rotation, 1e-9));
+
+        assert!(SoaTransform::slice_abs_diff_eq(&pose, &pose, 0.0));
+        assert!(!SoaTransform::slice_abs_diff_eq(&pose, &pose[..1], 1e-5));
+        assert!(!SoaTransform::slice_abs_diff_eq(
+            &[SoaTransform::NAN],
+            &[SoaTransform::NAN],
+            1e-5
+        ));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_normalize_pose() {
+        let mut pose = [
+            SoaTransform {
+                translation: SoaVec3::splat(1.5),
+                rotation: SoaQuat {
+                    x: f32x4::from_array([0.0, 1.1, 0.3, 0.0]),
+                    y: f32x4::from_array([0.0, 0.0, -0.4, 0.0]),
+                    z: f32x4::from_array([0.0, 0.0, 0.5, 1e-30]),
+                    w: f32x4::from_array([1.01, 0.0, 0.9, 0.0]),
+                },
+                scale: SoaVec3::splat(2.0),
+            },
+            SoaTransform {
+                rotation: SoaQuat::splat_quat(Quat::from_xyzw(0.1, 0.2, 0.3, 0.4)),
+                ..SoaTransform::IDENTITY
+            },
+        ];
+        SoaTransform::normalize_pose(&mut pose);
+        for transform in pose.iter() {
+            let len2 = transform.rotation.dot(&transform.rotation);
+            assert!(
+                len2.as_array().iter().all(|len2| (len2 - 1.0).abs() < 1e-6),
+                "{:?}",
+                len2
+            );
+        }
+        assert_eq!(pose[0].translation, SoaVec3::splat(1.5));
+        assert_eq!(pose[0].scale, SoaVec3::splat(2.0));
+        assert!(pose[0].rotation.quat(0).abs_diff_eq(Quat::IDENTITY, 1e-6));
+        assert!(pose[0]
+            .rotation
+            .quat(1)
+            .abs_diff_eq(Quat::from_xyzw(1.0, 0.0, 0.0, 0.0), 1e-6));
+        assert_eq!(pose[0].rotation.quat(3), Quat::IDENTITY);
+        let expected = Quat::from_xyzw(0.1, 0.2, 0.3, 0.4).normalize();
+        assert!(pose[1].rotation.quat(2).abs_diff_eq(expected, 1e-6));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_euler() {
+        use core::f32::consts::FRAC_PI_2;
+
+        let rotations = [
+            Quat::IDENTITY,
+            Quat::from_rotation_x(0.7),
+            Quat::from_rotation_y(-2.5),
+            Quat::from_axis_angle(Vec3::new(1.0, 2.0, -3.0).normalize(), 1.3),
+            Quat::from_axis_angle(Vec3::new(-0.3, 0.1, 0.9).normalize(), -3.0),
+            // near gimbal lock
+            Quat::from_rotation_y(FRAC_PI_2) * Quat::from_rotation_x(0.4),
+            Quat::from_rotation_y(-FRAC_PI_2 + 1e-4) * Quat::from_rotation_z(1.1),
+            Quat::from_rotation_z(0.3) * Quat::from_rotation_y(FRAC_PI_2 - 1e-4) * Quat::from_rotation_x(-0.8),
+        ];
+        for order in [EulerRot::XYZ, EulerRot::ZYX] {
+            for rotation in rotations {
+                let angles = quat_to_euler(rotation, order);
+                let round_trip = euler_to_quat(angles, order);
+                assert!(
+                    rotation.dot(round_trip).abs() > 1.0 - 1e-5,
+                    "{:?} {} {}",
+                    order,
+                    rotation,
+                    round_trip
+                );
+                // rotated vectors match, precision drops near gimbal lock
+                let v = Vec3::new(0.3, -1.0, 2.0);
+                assert!(
+                    (rotation * v).abs_diff_eq(round_trip * v, 5e-3),
+                    "{:?} {}",
+                    order,
+                    rotation
+                );
+            }
+        }
+
+        let angles = Vec3::new(0.1, 0.2, 0.3);
+        assert!(quat_to_euler(euler_to_quat(angles, EulerRot::XYZ), EulerRot::XYZ).abs_diff_eq(angles, 1e-5));
+        assert!(quat_to_euler(euler_to_quat(angles, EulerRot::ZYX), EulerRot::ZYX).abs_diff_eq(angles, 1e-5));
+        let q = euler_to_quat(angles, EulerRot::ZYX);
+        let expected = Quat::from_rotation_z(0.1) * Quat::from_rotation_y(0.2) * Quat::from_rotation_x(0.3);
+        assert!(q.abs_diff_eq(expected, 1e-6));
+    }
+
     #[cfg(feature = "rkyv")]
     #[test]
     #[wasm_bindgen_test]
@@ -0,0 +1,402 @@
+//!
+//! Mirror Job.
+//!
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+#[cfg(feature = "std")]
+use std::sync::{Arc, RwLock};
+
+use crate::base::{OzzBuf, OzzError, OzzMutBuf, OzzObj};
+use crate::math::SoaTransform;
+use crate::skeleton::Skeleton;
+
+/// Normal axis of the mirror plane, in local-space.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MirrorAxis {
+    /// Mirrors across YZ plane, left and right are usually on X axis.
+    #[default]
+    X,
+    /// Mirrors across XZ plane.
+    Y,
+    /// Mirrors across XY plane.
+    Z,
+}
+
+impl MirrorAxis {
+    // Signs of translation (x, y, z) and rotation (x, y, z, w) components.
+    fn signs(&self) -> ([f32; 3], [f32; 4]) {
+        match self {
+            MirrorAxis::X => ([-1.0, 1.0, 1.0], [1.0, -1.0, -1.0, 1.0]),
+            MirrorAxis::Y => ([1.0, -1.0, 1.0], [-1.0, 1.0, -1.0, 1.0]),
+            MirrorAxis::Z => ([1.0, 1.0, -1.0], [-1.0, -1.0, 1.0, 1.0]),
+        }
+    }
+}
+
+///
+/// Mirrors a local-space pose across a plane, like turning a left-handed clip into a right-handed one.
+///
+/// Each joint of the output receives the local transform of its paired joint in the input, reflected across
+/// the plane of normal `axis`: translation component along the axis is negated, and so are the rotation
+/// components of the 2 other axes. Scales are copied as is.
+///
+/// This supposes the skeleton rest pose is symmetric, with paired joints local-space frames mirroring each
+/// other. Joints without pair (spine, head...) are paired with themselves.
+///
+#[derive(Debug)]
+pub struct MirrorJob<S = Rc<Skeleton>, I = Rc<RefCell<Vec<SoaTransform>>>, O = Rc<RefCell<Vec<SoaTransform>>>>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    skeleton: Option<S>,
+    input: Option<I>,
+    axis: MirrorAxis,
+    joint_pairs: Vec<i16>,
+    output: Option<O>,
+}
+
+pub type MirrorJobRef<'t> = MirrorJob<&'t Skeleton, &'t [SoaTransform], &'t mut [SoaTransform]>;
+pub type MirrorJobRc = MirrorJob<Rc<Skeleton>, Rc<RefCell<Vec<SoaTransform>>>, Rc<RefCell<Vec<SoaTransform>>>>;
+#[cfg(feature = "std")]
+pub type MirrorJobArc = MirrorJob<Arc<Skeleton>, Arc<RwLock<Vec<SoaTransform>>>, Arc<RwLock<Vec<SoaTransform>>>>;
+
+impl<S, I, O> Default for MirrorJob<S, I, O>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    fn default() -> MirrorJob<S, I, O> {
+        MirrorJob {
+            skeleton: None,
+            input: None,
+            axis: MirrorAxis::default(),
+            joint_pairs: Vec::new(),
+            output: None,
+        }
+    }
+}
+
+impl<S, I, O> MirrorJob<S, I, O>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    /// Gets skeleton of `MirrorJob`.
+    #[inline]
+    pub fn skeleton(&self) -> Option<&S> {
+        self.skeleton.as_ref()
+    }
+
+    /// Sets skeleton of `MirrorJob`.
+    ///
+    /// The skeleton defines the number of joints to mirror.
+    #[inline]
+    pub fn set_skeleton(&mut self, skeleton: S) {
+        self.skeleton = Some(skeleton);
+    }
+
+    /// Clears skeleton of `MirrorJob`.
+    #[inline]
+    pub fn clear_skeleton(&mut self) {
+        self.skeleton = None;
+    }
+
+    /// Gets input of `MirrorJob`.
+    #[inline]
+    pub fn input(&self) -> Option<&I> {
+        self.input.as_ref()
+    }
+
+    /// Sets input of `MirrorJob`.
+    ///
+    /// The input range that store local transforms to mirror.
+    #[inline]
+    pub fn set_input(&mut self, input: I) {
+        self.input = Some(input);
+    }
+
+    /// Clears input of `MirrorJob`.
+    #[inline]
+    pub fn clear_input(&mut self) {
+        self.input = None;
+    }
+
+    /// Gets axis of `MirrorJob`.
+    #[inline]
+    pub fn axis(&self) -> MirrorAxis {
+        self.axis
+    }
+
+    /// Sets axis of `MirrorJob`.
+    ///
+    /// Normal of the mirror plane, in joints local-space. Default is `MirrorAxis::X`.
+    #[inline]
+    pub fn set_axis(&mut self, axis: MirrorAxis) {
+        self.axis = axis;
+    }
+
+    /// Gets joint pairs of `MirrorJob`.
+    #[inline]
+    pub fn joint_pairs(&self) -> &[i16] {
+        &self.joint_pairs
+    }
+
+    /// Sets joint pairs of `MirrorJob`.
+    ///
+    /// For each joint, the index of the joint on the other side (e.g. left arm for right arm). Pairing must be
+    /// symmetric, and joints without pair must be paired with themselves. Length must match the number of
+    /// skeleton joints. If empty, every joint is paired with itself.
+    #[inline]
+    pub fn set_joint_pairs(&mut self, joint_pairs: Vec<i16>) {
+        self.joint_pairs = joint_pairs;
+    }
+
+    /// Clears joint pairs of `MirrorJob`.
+    #[inline]
+    pub fn clear_joint_pairs(&mut self) {
+        self.joint_pairs.clear();
+    }
+
+    /// Gets output of `MirrorJob`.
+    #[inline]
+    pub fn output(&self) -> Option<&O> {
+        self.output.as_ref()
+    }
+
+    /// Sets output of `MirrorJob`.
+    ///
+    /// The output range to be filled with mirrored local transforms. Must not be the input range.
+    #[inline]
+    pub fn set_output(&mut self, output: O) {
+        self.output = Some(output);
+    }
+
+    /// Clears output of `MirrorJob`.
+    #[inline]
+    pub fn clear_output(&mut self) {
+        self.output = None;
+    }
+
+    /// Validates `MirrorJob` parameters.
+    pub fn validate(&self) -> bool {
+        (|| {
+            let skeleton = self.skeleton.as_ref()?.obj();
+            let input = self.input.as_ref()?.buf().ok()?;
+            let output = self.output.as_ref()?.buf().ok()?;
+
+            let mut ok = input.len() >= skeleton.num_soa_joints();
+            ok &= output.len() >= skeleton.num_soa_joints();
+            ok &= validate_joint_pairs(&self.joint_pairs, skeleton.num_joints());
+            Some(ok)
+        })()
+        .unwrap_or(false)
+    }
+
+    /// Runs mirror job's task.
+    /// The validate job before any operation is performed.
+    pub fn run(&mut self) -> Result<(), OzzError> {
+        let skeleton = self.skeleton.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let input = self.input.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+
+        let mut ok = input.len() >= skeleton.num_soa_joints();
+        ok &= output.len() >= skeleton.num_soa_joints();
+        ok &= validate_joint_pairs(&self.joint_pairs, skeleton.num_joints());
+        if !ok {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let (ts, rs) = self.axis.signs();
+        for joint in 0..skeleton.num_joints() {
+            let pair = self.joint_pairs.get(joint).map_or(joint, |&pair| pair as usize);
+            let (src, sl) = (&input[pair / 4], pair % 4);
+            let (dst, dl) = (&mut output[joint / 4], joint % 4);
+
+            dst.translation.x[dl] = src.translation.x[sl] * ts[0];
+            dst.translation.y[dl] = src.translation.y[sl] * ts[1];
+            dst.translation.z[dl] = src.translation.z[sl] * ts[2];
+            dst.rotation.x[dl] = src.rotation.x[sl] * rs[0];
+            dst.rotation.y[dl] = src.rotation.y[sl] * rs[1];
+            dst.rotation.z[dl] = src.rotation.z[sl] * rs[2];
+            dst.rotation.w[dl] = src.rotation.w[sl] * rs[3];
+            dst.scale.x[dl] = src.scale.x[sl];
+            dst.scale.y[dl] = src.scale.y[sl];
+            dst.scale.z[dl] = src.scale.z[sl];
+        }
+        Ok(())
+    }
+}
+
+fn validate_joint_pairs(joint_pairs: &[i16], num_joints: usize) -> bool {
+    if joint_pairs.is_empty() {
+        return true;
+    }
+    if joint_pairs.len() != num_joints {
+        return false;
+    }
+    joint_pairs
+        .iter()
+        .enumerate()
+        .all(|(joint, &pair)| pair >= 0 && (pair as usize) < num_joints && joint_pairs[pair as usize] as usize == joint)
+}
+
+#[cfg(test)]
+mod mirror_tests {
+    use glam::{Mat4, Quat, Vec3};
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::base::DeterministicState;
+    use crate::local_to_model_job::LocalToModelJob;
+    use crate::math::SoaVec3;
+    use crate::skeleton::{JointHashMap, SkeletonRaw};
+
+    fn new_skeleton() -> Rc<Skeleton> {
+        // 5 joints
+        //        spine
+        //       /     \
+        //  left_arm  right_arm
+        //     |          |
+        //  left_hand  right_hand
+        let mut rest_poses = vec![SoaTransform::IDENTITY; 2];
+        rest_poses[0].translation = SoaVec3::new([0.0, 1.0, -1.0, 1.0], [1.0, 0.0, 0.0, 0.0], [0.0; 4]);
+        rest_poses[1].translation = SoaVec3::new([-1.0, 0.0, 0.0, 0.0], [0.0; 4], [0.0; 4]);
+        Rc::new(Skeleton::from_raw(&SkeletonRaw {
+            joint_rest_poses: rest_poses,
+            joint_names: {
+                let mut map = JointHashMap::with_hashers(DeterministicState::new(), DeterministicState::new());
+                map.insert("spine".into(), 0);
+                map.insert("left_arm".into(), 1);
+                map.insert("right_arm".into(), 2);
+                map.insert("left_hand".into(), 3);
+                map.insert("right_hand".into(), 4);
+                map
+            },
+            joint_parents: vec![-1, 0, 0, 1, 2],
+        }))
+    }
+
+    fn model_space(skeleton: &Rc<Skeleton>, input: &Rc<RefCell<Vec<SoaTransform>>>) -> Vec<Mat4> {
+        let output = Rc::new(RefCell::new(vec![Mat4::IDENTITY; skeleton.num_joints()]));
+        let mut job: LocalToModelJob = LocalToModelJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(input.clone());
+        job.set_output(output.clone());
+        job.run().unwrap();
+        let matrices = output.borrow().clone();
+        matrices
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validity() {
+        let skeleton = new_skeleton();
+        let buf = || Rc::new(RefCell::new(vec![SoaTransform::IDENTITY; 2]));
+
+        let mut job: MirrorJob = MirrorJob::default();
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        job.set_skeleton(skeleton.clone());
+        job.set_input(buf());
+        job.set_output(buf());
+        assert!(job.validate());
+
+        job.set_joint_pairs(vec![0, 2, 1, 4, 3]);
+        assert!(job.validate());
+
+        // not symmetric
+        job.set_joint_pairs(vec![0, 2, 1, 4, 4]);
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        // out of range
+        job.set_joint_pairs(vec![0, 2, 1, 4, 5]);
+        assert!(!job.validate());
+
+        // bad length
+        job.set_joint_pairs(vec![0, 2, 1]);
+        assert!(!job.validate());
+
+        job.set_joint_pairs(vec![0, 2, 1, 4, 3]);
+        job.set_output(Rc::new(RefCell::new(vec![SoaTransform::IDENTITY; 1])));
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_mirror() {
+        let skeleton = new_skeleton();
+        let input = Rc::new(RefCell::new(skeleton.joint_rest_poses().to_vec()));
+        {
+            // raises left arm
+            let raise = Quat::from_rotation_z(1.0);
+            let mut input = input.borrow_mut();
+            let rotation = &mut input[0].rotation;
+            (rotation.x[1], rotation.y[1], rotation.z[1], rotation.w[1]) = (raise.x, raise.y, raise.z, raise.w);
+        }
+        let output = Rc::new(RefCell::new(vec![SoaTransform::IDENTITY; 2]));
+
+        let mut job: MirrorJob = MirrorJob::default();
+        job.set_skeleton(skeleton.clone());
+        job.set_input(input.clone());
+        job.set_output(output.clone());
+        job.set_joint_pairs(vec![0, 2, 1, 4, 3]);
+        job.run().unwrap();
+
+        // right arm is raised, left arm is at rest
+        let expected = Quat::from_rotation_z(-1.0);
+        let rotation = output.borrow()[0].rotation;
+        assert_eq!(
+            (rotation.x[2], rotation.y[2], rotation.z[2], rotation.w[2]),
+            (expected.x, expected.y, expected.z, expected.w)
+        );
+        assert_eq!(
+            (rotation.x[1], rotation.y[1], rotation.z[1], rotation.w[1]),
+            (0.0, 0.0, 0.0, 1.0)
+        );
+
+        let before = model_space(&skeleton, &input);
+        let after = model_space(&skeleton, &output);
+        let mirror = Vec3::new(-1.0, 1.0, 1.0);
+        for (joint, pair) in [(0, 0), (1, 2), (2, 1), (3, 4), (4, 3)] {
+            let expected = before[pair].w_axis.truncate() * mirror;
+            let actual = after[joint].w_axis.truncate();
+            assert!(
+                actual.abs_diff_eq(expected, 1e-6),
+                "{}: {} != {}",
+                joint,
+                actual,
+                expected
+            );
+        }
+        assert!(after[4].w_axis.y > 1.0);
+        assert_eq!(after[3].w_axis.y, 1.0);
+
+        // mirroring twice restores the pose
+        let restored = Rc::new(RefCell::new(vec![SoaTransform::IDENTITY; 2]));
+        job.set_input(output.clone());
+        job.set_output(restored.clone());
+        job.run().unwrap();
+        assert_eq!(*restored.borrow(), *input.borrow());
+
+        // other axes
+        job.set_input(input.clone());
+        job.set_output(output.clone());
+        job.clear_joint_pairs();
+        job.set_axis(MirrorAxis::Z);
+        job.run().unwrap();
+        assert_eq!(output.borrow()[0].rotation.z, input.borrow()[0].rotation.z);
+        assert_eq!(output.borrow()[0].rotation.w, input.borrow()[0].rotation.w);
+        assert_eq!(output.borrow()[0].rotation.x, -input.borrow()[0].rotation.x);
+        assert_eq!(output.borrow()[0].translation.z, -input.borrow()[0].translation.z);
+        assert_eq!(output.borrow()[0].scale, input.borrow()[0].scale);
+    }
+}
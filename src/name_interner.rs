@@ -0,0 +1,128 @@
+//!
+//! Name interning, shares joint names storage across skeletons.
+//!
+
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+use crate::base::DeterministicState;
+
+///
+/// Interning table of names.
+///
+/// Banks of skeletons and animations usually repeat the same long joint names. Interning them returns a
+/// shared `Arc<str>` for equal names, so that each distinct name is stored once. See `Skeleton::intern_names()`.
+/// The table is thread safe, and usually shared through an `Arc<NameInterner>` by all loaders.
+///
+#[derive(Debug, Default)]
+pub struct NameInterner {
+    names: RwLock<HashSet<Arc<str>, DeterministicState>>,
+}
+
+impl NameInterner {
+    /// Creates an empty `NameInterner`.
+    pub fn new() -> NameInterner {
+        NameInterner::default()
+    }
+
+    /// Gets the interned `Arc<str>` of `name`, inserting it if it's not interned yet.
+    pub fn intern(&self, name: &str) -> Arc<str> {
+        if let Some(interned) = self.names.read().unwrap().get(name) {
+            return interned.clone();
+        }
+        let mut names = self.names.write().unwrap();
+        if let Some(interned) = names.get(name) {
+            return interned.clone();
+        }
+        let interned: Arc<str> = Arc::from(name);
+        names.insert(interned.clone());
+        interned
+    }
+
+    /// Gets the number of interned names.
+    pub fn len(&self) -> usize {
+        self.names.read().unwrap().len()
+    }
+
+    /// Returns true if no name is interned.
+    pub fn is_empty(&self) -> bool {
+        self.names.read().unwrap().is_empty()
+    }
+
+    /// Removes names that are only referenced by the table, once skeletons using them are dropped.
+    pub fn purge(&self) {
+        self.names.write().unwrap().retain(|name| Arc::strong_count(name) > 1);
+    }
+}
+
+#[cfg(test)]
+mod name_interner_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::math::SoaTransform;
+    use crate::skeleton::{JointHashMap, Skeleton, SkeletonRaw};
+
+    fn new_skeleton(names: &[&str]) -> Skeleton {
+        let mut joint_names = JointHashMap::with_hashers(DeterministicState::new(), DeterministicState::new());
+        for (idx, name) in names.iter().enumerate() {
+            joint_names.insert((*name).into(), idx as i16);
+        }
+        let mut joint_parents = vec![-1i16];
+        joint_parents.extend((1..names.len()).map(|idx| idx as i16 - 1));
+        Skeleton::from_raw(&SkeletonRaw {
+            joint_rest_poses: vec![SoaTransform::IDENTITY; names.len().div_ceil(4)],
+            joint_names,
+            joint_parents,
+        })
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_intern_names() {
+        let interner = Arc::new(NameInterner::new());
+        assert!(interner.is_empty());
+
+        let mut skeleton1 = new_skeleton(&["root", "spine", "neck", "head"]);
+        let mut skeleton2 = new_skeleton(&["root", "spine", "tail"]);
+        skeleton1.intern_names(&interner);
+        skeleton2.intern_names(&interner);
+        assert_eq!(interner.len(), 5);
+
+        assert_eq!(skeleton1.name_by_joint(3), Some("head"));
+        assert_eq!(skeleton1.joint_by_name("head"), Some(3));
+        assert_eq!(skeleton2.name_by_joint(2), Some("tail"));
+        assert_eq!(skeleton2.name_by_joint(3), None);
+
+        // overlapping names share storage
+        let name = |skeleton: &Skeleton, joint: i16| skeleton.joint_names().get_by_right(&joint).unwrap().clone();
+        assert!(Arc::ptr_eq(&name(&skeleton1, 0), &name(&skeleton2, 0)));
+        assert!(Arc::ptr_eq(&name(&skeleton1, 1), &name(&skeleton2, 1)));
+        assert!(Arc::ptr_eq(&name(&skeleton1, 1), &interner.intern("spine")));
+        assert!(!Arc::ptr_eq(&name(&skeleton1, 2), &name(&skeleton2, 2)));
+
+        // names of dropped skeletons are purged
+        drop(skeleton1);
+        interner.purge();
+        assert_eq!(interner.len(), 3);
+        drop(skeleton2);
+        interner.purge();
+        assert!(interner.is_empty());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_intern_names_from_file() {
+        let interner = NameInterner::new();
+        let mut skeleton1 = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let mut skeleton2 = Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap();
+        let expected = skeleton1.joint_names().clone();
+        skeleton1.intern_names(&interner);
+        skeleton2.intern_names(&interner);
+        assert_eq!(interner.len(), skeleton1.num_joints());
+        assert_eq!(*skeleton1.joint_names(), expected);
+        for (name, joint) in skeleton1.joint_names().iter() {
+            assert!(Arc::ptr_eq(name, skeleton2.joint_names().get_by_right(joint).unwrap()));
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//!
+//! Raw animation, the offline (uncompressed keyframes) animation format of ozz toolset.
+//!
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use glam::{Quat, Vec3};
+#[cfg(feature = "std")]
+use std::io::Read;
+
+#[cfg(feature = "std")]
+use crate::animation::{check_count, ANIMATION_MAX_NAME_LEN};
+use crate::animation::{Animation, Float3KeyF32, QuaternionKeyF32};
+use crate::animation_builder::AnimationBuilder;
+#[cfg(feature = "std")]
+use crate::archive::{Archive, ArchiveRead};
+use crate::base::OzzError;
+#[cfg(feature = "std")]
+use crate::base::SKELETON_MAX_JOINTS;
+
+/// Maximum number of keys of a raw track read from an `Archive`, the runtime limit on timepoints.
+#[cfg(feature = "std")]
+const RAW_TRACK_MAX_KEYS: usize = u16::MAX as usize + 1;
+
+/// Keyframes of a joint in a `RawAnimation`, as `(time, value)` pairs.
+///
+/// Times are in seconds, in range 0 to animation duration. Keys must be sorted by time, with unique times.
+/// An empty track is the identity transformation.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RawJointTrack {
+    pub translations: Vec<(f32, Vec3)>,
+    pub rotations: Vec<(f32, Quat)>,
+    pub scales: Vec<(f32, Vec3)>,
+}
+
+impl RawJointTrack {
+    /// Validates `RawJointTrack` keys against animation `duration`.
+    pub fn validate(&self, duration: f32) -> bool {
+        Self::validate_keys(&self.translations, duration)
+            && Self::validate_keys(&self.rotations, duration)
+            && Self::validate_keys(&self.scales, duration)
+    }
+
+    fn validate_keys<V>(keys: &[(f32, V)], duration: f32) -> bool {
+        let mut prev = -1.0;
+        for (time, _) in keys {
+            if !(*time >= 0.0 && *time <= duration && *time > prev) {
+                return false;
+            }
+            prev = *time;
+        }
+        true
+    }
+}
+
+///
+/// Offline animation, with uncompressed keyframes of each joint.
+///
+/// This is the format produced by ozz importers and consumed by ozz offline tools, where the runtime
+/// `Animation` is built from. Unlike `AnimationBuilder`, tracks don't need keys at the beginning and the end
+/// of the animation: the first and last keys are extended to the whole duration, as ozz does.
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RawAnimation {
+    pub duration: f32,
+    pub name: String,
+    pub tracks: Vec<RawJointTrack>,
+}
+
+impl RawAnimation {
+    /// `RawAnimation` resource file tag for `Archive`.
+    #[inline]
+    pub fn tag() -> &'static str {
+        "ozz-raw_animation"
+    }
+
+    /// `RawAnimation` resource file version for `Archive`.
+    #[inline]
+    pub fn version() -> u32 {
+        3
+    }
+
+    /// Gets the number of tracks of `RawAnimation`.
+    #[inline]
+    pub fn num_tracks(&self) -> usize {
+        self.tracks.len()
+    }
+
+    #[cfg(feature = "std")]
+    /// Reads a `RawAnimation` from an `Archive`.
+    pub fn from_archive(archive: &mut Archive<impl Read>) -> Result<RawAnimation, OzzError> {
+        if archive.tag() != Self::tag() {
+            return Err(OzzError::InvalidTag);
+        }
+        if archive.version() != Self::version() {
+            return Err(OzzError::InvalidVersion);
+        }
+
+        let duration: f32 = archive.read()?;
+        let num_tracks: u32 = archive.read()?;
+        let num_tracks = check_count("num_tracks", num_tracks, SKELETON_MAX_JOINTS as usize)?;
+        let mut tracks = Vec::with_capacity(num_tracks as usize);
+        for _ in 0..num_tracks {
+            tracks.push(RawJointTrack {
+                translations: Self::read_keys(archive, "translations_count")?,
+                rotations: Self::read_keys(archive, "rotations_count")?,
+                scales: Self::read_keys(archive, "scales_count")?,
+            });
+        }
+
+        let name_len: u32 = archive.read()?;
+        let name_len = check_count("name_len", name_len, ANIMATION_MAX_NAME_LEN)?;
+        let name = String::from_utf8(archive.read_vec(name_len as usize)?).map_err(|e| e.utf8_error())?;
+
+        Ok(RawAnimation { duration, name, tracks })
+    }
+
+    #[cfg(feature = "std")]
+    fn read_keys<V: ArchiveRead<V>>(
+        archive: &mut Archive<impl Read>,
+        field: &'static str,
+    ) -> Result<Vec<(f32, V)>, OzzError> {
+        let count: u32 = archive.read()?;
+        let count = check_count(field, count, RAW_TRACK_MAX_KEYS)?;
+        let mut keys = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let time: f32 = archive.read()?;
+            keys.push((time, archive.read()?));
+        }
+        Ok(keys)
+    }
+
+    /// Reads a `RawAnimation` from a file path.
+    #[cfg(all(feature = "std", not(feature = "wasm")))]
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<RawAnimation, OzzError> {
+        let mut archive = Archive::from_path(path)?;
+        RawAnimation::from_archive(&mut archive)
+    }
+
+    /// Reads a `RawAnimation` from a file path.
+    #[cfg(all(feature = "wasm", feature = "nodejs"))]
+    pub fn from_path(path: &str) -> Result<RawAnimation, OzzError> {
+        let mut archive = Archive::from_path(path)?;
+        RawAnimation::from_archive(&mut archive)
+    }
+
+    /// Validates `RawAnimation`.
+    ///
+    /// Duration must be positive, and keys of every track sorted by unique times in range 0 to duration.
+    pub fn validate(&self) -> bool {
+        if self.duration <= 0.0 || self.duration.is_nan() || self.tracks.len() > u16::MAX as usize {
+            return false;
+        }
+        self.tracks.iter().all(|track| track.validate(self.duration))
+    }
+
+    /// Builds the runtime `Animation`, quantizing keyframes to half precision floats.
+    /// The validate job before any operation is performed.
+    ///
+    /// Rotations are normalized. Empty tracks are filled with identity keys.
+    pub fn build(&self) -> Result<Animation, OzzError> {
+        if !self.validate() {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let mut builder = AnimationBuilder::new(self.duration, self.num_tracks());
+        builder.set_name(&self.name);
+        for (idx, track) in self.tracks.iter().enumerate() {
+            for (ratio, value) in self.extend_keys(&track.translations, Vec3::ZERO) {
+                builder.push_translation(idx, ratio, Float3KeyF32::from(value))?;
+            }
+            for (ratio, value) in self.extend_keys(&track.rotations, Quat::IDENTITY) {
+                builder.push_rotation(idx, ratio, QuaternionKeyF32::from(value.normalize()))?;
+            }
+            for (ratio, value) in self.extend_keys(&track.scales, Vec3::ONE) {
+                builder.push_scale(idx, ratio, Float3KeyF32::from(value))?;
+            }
+        }
+        builder.build()
+    }
+
+    // Converts keys times to ratios, adding keys at ratio 0 and 1 if missing.
+    fn extend_keys<V: Copy>(&self, keys: &[(f32, V)], identity: V) -> Vec<(f32, V)> {
+        let first = keys.first().map_or(identity, |(_, value)| *value);
+        let last = keys.last().map_or(identity, |(_, value)| *value);
+        let mut ratios = Vec::with_capacity(keys.len() + 2);
+        ratios.push((0.0, first));
+        for (time, value) in keys {
+            let ratio = (time / self.duration).min(1.0);
+            match ratios.last_mut() {
+                Some(prev) if prev.0 >= ratio => *prev = (ratio, *value),
+                _ => ratios.push((ratio, *value)),
+            }
+        }
+        if ratios.last().map(|(ratio, _)| *ratio) != Some(1.0) {
+            ratios.push((1.0, last));
+        }
+        ratios
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::math::SoaTransform;
+    use crate::sampling_job::{SamplingContext, SamplingJob};
+
+    fn new_raw_animation() -> RawAnimation {
+        let rot = Quat::from_rotation_y(1.0);
+        RawAnimation {
+            duration: 2.0,
+            name: "raw".into(),
+            tracks: vec![
+                RawJointTrack {
+                    translations: vec![(0.0, Vec3::ZERO), (1.0, Vec3::new(1.0, 2.0, 0.0)), (2.0, Vec3::X)],
+                    rotations: vec![(0.5, Quat::IDENTITY), (1.5, rot)],
+                    scales: vec![],
+                },
+                RawJointTrack {
+                    translations: vec![(0.5, Vec3::Y)],
+                    rotations: vec![],
+                    scales: vec![(0.0, Vec3::ONE), (2.0, Vec3::splat(3.0))],
+                },
+            ],
+        }
+    }
+
+    // Writes an ozz v3 little endian raw animation archive.
+    fn write_archive(raw: &RawAnimation) -> Vec<u8> {
+        fn write_keys<const N: usize>(buf: &mut Vec<u8>, keys: impl ExactSizeIterator<Item = (f32, [f32; N])>) {
+            buf.extend_from_slice(&(keys.len() as u32).to_le_bytes());
+            for (time, value) in keys {
+                buf.extend_from_slice(&time.to_le_bytes());
+                value.iter().for_each(|v| buf.extend_from_slice(&v.to_le_bytes()));
+            }
+        }
+
+        let mut buf = vec![1u8];
+        buf.extend_from_slice(b"ozz-raw_animation\0");
+        buf.extend_from_slice(&RawAnimation::version().to_le_bytes());
+        buf.extend_from_slice(&raw.duration.to_le_bytes());
+        buf.extend_from_slice(&(raw.tracks.len() as u32).to_le_bytes());
+        for track in &raw.tracks {
+            write_keys(&mut buf, track.translations.iter().map(|(t, v)| (*t, v.to_array())));
+            write_keys(&mut buf, track.rotations.iter().map(|(t, v)| (*t, v.to_array())));
+            write_keys(&mut buf, track.scales.iter().map(|(t, v)| (*t, v.to_array())));
+        }
+        buf.extend_from_slice(&(raw.name.len() as u32).to_le_bytes());
+        buf.extend_from_slice(raw.name.as_bytes());
+        buf
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_from_archive() {
+        let raw = new_raw_animation();
+        let mut archive = Archive::from_vec(write_archive(&raw)).unwrap();
+        assert_eq!(RawAnimation::from_archive(&mut archive).unwrap(), raw);
+
+        let mut archive = Archive::from_vec(write_archive(&raw)).unwrap();
+        assert!(matches!(
+            Animation::from_archive(&mut archive),
+            Err(OzzError::InvalidTag)
+        ));
+
+        let mut buf = write_archive(&raw);
+        buf[19] = 2; // version
+        let mut archive = Archive::from_vec(buf).unwrap();
+        assert!(matches!(
+            RawAnimation::from_archive(&mut archive),
+            Err(OzzError::InvalidVersion)
+        ));
+
+        let buf = write_archive(&raw);
+        let mut archive = Archive::from_vec(buf[..buf.len() - 2].to_vec()).unwrap();
+        assert!(RawAnimation::from_archive(&mut archive).is_err());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validate() {
+        assert!(new_raw_animation().validate());
+
+        let mut raw = new_raw_animation();
+        raw.duration = 0.0;
+        assert!(raw.build().unwrap_err().is_invalid_job());
+
+        let mut raw = new_raw_animation();
+        raw.tracks[0].rotations.reverse(); // unsorted
+        assert!(raw.build().unwrap_err().is_invalid_job());
+
+        let mut raw = new_raw_animation();
+        raw.tracks[1].translations.push((0.5, Vec3::Z)); // duplicated time
+        assert!(raw.build().unwrap_err().is_invalid_job());
+
+        let mut raw = new_raw_animation();
+        raw.tracks[1].scales[1].0 = 2.5; // out of duration
+        assert!(raw.build().unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_build_and_sample() {
+        let raw = new_raw_animation();
+        let animation = Rc::new(raw.build().unwrap());
+        assert_eq!(animation.duration(), 2.0);
+        assert_eq!(animation.num_tracks(), 2);
+        assert_eq!(animation.name(), "raw");
+
+        let mut job: SamplingJob = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::new(animation.num_tracks()));
+        let output = Rc::new(RefCell::new(vec![SoaTransform::default(); animation.num_soa_tracks()]));
+        job.set_output(output.clone());
+
+        let rot = Quat::from_rotation_y(1.0);
+        for (time, t0, r0, s1) in [
+            (0.0, Vec3::ZERO, Quat::IDENTITY, Vec3::ONE),
+            (0.25, Vec3::new(0.25, 0.5, 0.0), Quat::IDENTITY, Vec3::splat(1.25)),
+            (
+                1.0,
+                Vec3::new(1.0, 2.0, 0.0),
+                Quat::IDENTITY.lerp(rot, 0.5),
+                Vec3::splat(2.0),
+            ),
+            (1.5, Vec3::new(1.0, 1.0, 0.0), rot, Vec3::splat(2.5)),
+            (2.0, Vec3::X, rot, Vec3::splat(3.0)),
+        ] {
+            job.set_ratio(time / raw.duration);
+            job.run().unwrap();
+            let out = output.borrow()[0];
+            assert!(out.translation.vec3(0).abs_diff_eq(t0, 2e-3), "time={}", time);
+            assert!(out.rotation.quat(0).abs_diff_eq(r0, 1e-3), "time={}", time);
+            assert_eq!(out.scale.vec3(0), Vec3::ONE);
+            assert!(out.translation.vec3(1).abs_diff_eq(Vec3::Y, 1e-6), "time={}", time);
+            assert!(out.rotation.quat(1).abs_diff_eq(Quat::IDENTITY, 1e-4));
+            assert!(out.scale.vec3(1).abs_diff_eq(s1, 2e-3), "time={}", time);
+        }
+    }
+}
@@ -2,17 +2,24 @@
 //! Sampling Job.
 //!
 
-use std::alloc::{self, Layout};
-use std::cell::RefCell;
-use std::fmt::{Debug, Formatter};
-use std::rc::Rc;
-use std::simd::prelude::*;
+use ::alloc::alloc::{self, Layout};
+use ::alloc::rc::Rc;
+use ::alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::{Debug, Formatter};
+use core::simd::prelude::*;
+use core::{mem, ptr, slice};
+use glam::{Quat, Vec3};
+#[cfg(feature = "std")]
 use std::sync::{Arc, RwLock};
-use std::{mem, ptr, slice};
 
-use crate::animation::{Animation, Float3Key, KeyframesCtrl, QuaternionKey};
+use crate::animation::{Animation, Float3Key, KeyframesCtrl, QuaternionKey, Ratio, Seconds};
 use crate::base::{align_ptr, align_usize, OzzError, OzzMutBuf, OzzObj};
-use crate::math::{f32_clamp_or_max, SoaQuat, SoaTransform, SoaVec3};
+#[cfg(not(feature = "std"))]
+use crate::math::StdFloat;
+use crate::math::{f32_clamp_or_max, SoaQuat, SoaTransform, SoaVec3, ONE, ZERO};
+use crate::track::Track;
+use crate::track_sampling_job::{TrackSamplingJob, TrackSamplingJobRef};
 
 /// Soa hot `SoaVec3` data to interpolate.
 #[repr(C)]
@@ -150,6 +157,7 @@ struct SamplingContextInner {
 
     translations: *mut InterpSoaFloat3,
     translation_entries: *mut u32,
+    translation_neighbors: *mut u32,
     translation_outdated: *mut u8,
     translation_next: usize,
 
@@ -160,6 +168,7 @@ struct SamplingContextInner {
 
     scales: *mut InterpSoaFloat3,
     scale_entries: *mut u32,
+    scale_neighbors: *mut u32,
     scale_outdated: *mut u8,
     scale_next: usize,
 }
@@ -176,6 +185,7 @@ impl Default for SamplingContextInner {
 
             translations: ptr::null_mut(),
             translation_entries: ptr::null_mut(),
+            translation_neighbors: ptr::null_mut(),
             translation_outdated: ptr::null_mut(),
             translation_next: 0,
 
@@ -186,6 +196,7 @@ impl Default for SamplingContextInner {
 
             scales: ptr::null_mut(),
             scale_entries: ptr::null_mut(),
+            scale_neighbors: ptr::null_mut(),
             scale_outdated: ptr::null_mut(),
             scale_next: 0,
         }
@@ -200,7 +211,7 @@ unsafe impl Send for SamplingContext {}
 unsafe impl Sync for SamplingContext {}
 
 impl Debug for SamplingContext {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         if self.0.is_null() {
             return f.debug_struct("SamplingContext").finish();
         }
@@ -221,6 +232,8 @@ impl Clone for SamplingContext {
         ctx.translations_mut().copy_from_slice(self.translations());
         ctx.translation_entries_mut()
             .copy_from_slice(self.translation_entries());
+        ctx.translation_neighbors_mut()
+            .copy_from_slice(self.translation_neighbors());
         ctx.translation_outdated_mut()
             .copy_from_slice(self.translation_outdated());
         ctx.set_translation_next(self.translation_next());
@@ -232,6 +245,7 @@ impl Clone for SamplingContext {
 
         ctx.scales_mut().copy_from_slice(self.scales());
         ctx.scale_entries_mut().copy_from_slice(self.scale_entries());
+        ctx.scale_neighbors_mut().copy_from_slice(self.scale_neighbors());
         ctx.scale_outdated_mut().copy_from_slice(self.scale_outdated());
         ctx.set_scale_next(self.scale_next());
         ctx
@@ -267,7 +281,7 @@ impl Drop for SamplingContext {
                 let layout = Layout::from_size_align_unchecked(self.size(), mem::size_of::<f32x4>());
                 alloc::dealloc(self.0 as *mut u8, layout);
             }
-            self.0 = std::ptr::null_mut();
+            self.0 = core::ptr::null_mut();
         }
     }
 }
@@ -292,13 +306,13 @@ impl SamplingContext {
         let max_tracks = max_soa_tracks * 4;
         let max_outdated = max_soa_tracks.div_ceil(8);
         let translation_size = mem::size_of::<InterpSoaFloat3>() * max_soa_tracks
-            + mem::size_of::<i32>() * max_tracks
+            + mem::size_of::<i32>() * max_tracks * 2
             + mem::size_of::<u8>() * max_outdated;
         let rotation_size = mem::size_of::<InterpSoaQuaternion>() * max_soa_tracks
             + mem::size_of::<i32>() * max_tracks
             + mem::size_of::<u8>() * max_outdated;
         let scale_size = mem::size_of::<InterpSoaFloat3>() * max_soa_tracks
-            + mem::size_of::<i32>() * max_tracks
+            + mem::size_of::<i32>() * max_tracks * 2
             + mem::size_of::<u8>() * max_outdated;
         let size = align_usize(mem::size_of::<SamplingContextInner>(), ALIGN)
             + align_usize(translation_size, ALIGN)
@@ -325,6 +339,9 @@ impl SamplingContext {
             ptr = ptr.add(mem::size_of::<InterpSoaFloat3>() * inner.max_soa_tracks);
             inner.translation_entries = ptr as *mut u32;
             ptr = ptr.add(mem::size_of::<u32>() * inner.max_tracks);
+            inner.translation_neighbors = ptr as *mut u32;
+            ptr::write_bytes(inner.translation_neighbors, 0, inner.max_tracks);
+            ptr = ptr.add(mem::size_of::<u32>() * inner.max_tracks);
             inner.translation_outdated = ptr;
             ptr = ptr.add(inner.max_outdated);
             ptr = align_ptr(ptr, ALIGN);
@@ -341,6 +358,9 @@ impl SamplingContext {
             ptr = ptr.add(mem::size_of::<InterpSoaFloat3>() * inner.max_soa_tracks);
             inner.scale_entries = ptr as *mut u32;
             ptr = ptr.add(mem::size_of::<u32>() * inner.max_tracks);
+            inner.scale_neighbors = ptr as *mut u32;
+            ptr::write_bytes(inner.scale_neighbors, 0, inner.max_tracks);
+            ptr = ptr.add(mem::size_of::<u32>() * inner.max_tracks);
             inner.scale_outdated = ptr;
             ptr = ptr.add(inner.max_outdated);
             ptr = align_ptr(ptr, ALIGN);
@@ -360,6 +380,11 @@ impl SamplingContext {
     }
 
     /// Clear the `SamplingContext`.
+    ///
+    /// Forgets cached keyframe cursors and the bound animation, so the next sampling starts from scratch,
+    /// e.g. after seeking. It isn't needed when switching animations, as `SamplingJob::run()` already resets
+    /// the context when the sampled animation differs from the last one.
+    #[doc(alias = "reset")]
     #[inline]
     pub fn clear(&mut self) {
         self.set_animation_id(0);
@@ -447,6 +472,19 @@ impl SamplingContext {
         unsafe { slice::from_raw_parts_mut(inner.translation_entries, inner.max_tracks) }
     }
 
+    /// The keys following the entries, cached by cubic interpolation. Stale values are detected and refreshed.
+    #[inline]
+    pub fn translation_neighbors(&self) -> &[u32] {
+        let inner = self.inner();
+        unsafe { slice::from_raw_parts(inner.translation_neighbors, inner.max_tracks) }
+    }
+
+    #[inline]
+    fn translation_neighbors_mut(&mut self) -> &mut [u32] {
+        let inner = self.inner();
+        unsafe { slice::from_raw_parts_mut(inner.translation_neighbors, inner.max_tracks) }
+    }
+
     /// Outdated soa entries. One bit per soa entry (32 joints per byte).
     #[inline]
     pub fn translation_outdated(&self) -> &[u8] {
@@ -547,6 +585,19 @@ impl SamplingContext {
         unsafe { slice::from_raw_parts_mut(inner.scale_entries, inner.max_tracks) }
     }
 
+    /// The keys following the entries, cached by cubic interpolation. Stale values are detected and refreshed.
+    #[inline]
+    pub fn scale_neighbors(&self) -> &[u32] {
+        let inner = self.inner();
+        unsafe { slice::from_raw_parts(inner.scale_neighbors, inner.max_tracks) }
+    }
+
+    #[inline]
+    fn scale_neighbors_mut(&mut self) -> &mut [u32] {
+        let inner = self.inner();
+        unsafe { slice::from_raw_parts_mut(inner.scale_neighbors, inner.max_tracks) }
+    }
+
     /// Outdated soa entries. One bit per soa entry (32 joints per byte).
     #[inline]
     pub fn scale_outdated(&self) -> &[u8] {
@@ -832,7 +883,7 @@ const _: () = {
     impl<'de> Visitor<'de> for SamplingContextVisitor {
         type Value = SamplingContext;
 
-        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> core::fmt::Result {
             formatter.write_str("struct SamplingContext")
         }
 
@@ -945,6 +996,47 @@ struct DecompressArgs<'t, T> {
     values: &'t mut [T],
 }
 
+/// Interpolation mode of translation and scale keyframes, used by `SamplingJob`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Linear interpolation between the 2 keys bracketing the ratio, as ozz does.
+    #[default]
+    Linear,
+    /// Catmull-Rom interpolation through the 2 keys bracketing the ratio, using their neighbor keys as control
+    /// points. The first and last keys of a track are repeated as their missing neighbors.
+    Cubic,
+}
+
+/// Counters of a `SamplingJob::run_with_stats()` call, for profiling and budget tuning.
+///
+/// Cache counters are per soa track channel, a channel being the translations, rotations or scales of a soa
+/// track, so that `cache_hits + cache_misses` is 3 times `soa_tracks`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SamplingStats {
+    /// Number of soa tracks interpolated to the output.
+    pub soa_tracks: usize,
+    /// Number of keyframes decompressed by this call, 8 per missed soa track channel (2 keys for 4 tracks).
+    pub keys_decompressed: usize,
+    /// Number of soa track channels whose decompressed keys, cached in the context, still bracketed the ratio.
+    pub cache_hits: usize,
+    /// Number of soa track channels whose keys were decompressed, because one of their tracks moved to other
+    /// keys, or the context was reset.
+    pub cache_misses: usize,
+}
+
+/// Number of floats per joint written by `SamplingJob::write_trs_interleaved()`.
+pub const TRS_INTERLEAVED_STRIDE: usize = 10;
+
+/// Interpolation mode of rotation keyframes, used by `SamplingJob`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RotationInterp {
+    /// Normalized linear interpolation, as ozz does. Fast, but angular velocity isn't constant.
+    #[default]
+    Nlerp,
+    /// Spherical linear interpolation, at constant angular velocity.
+    Slerp,
+}
+
 ///
 /// Samples an animation at a given time ratio in the unit interval 0.0-1.0 (where 0.0 is the beginning of
 /// the animation, 1.0 is the end), to output the corresponding posture in local-space.
@@ -967,10 +1059,18 @@ where
     context: Option<C>,
     ratio: f32,
     output: Option<O>,
+    root_track: usize,
+    looping: bool,
+    speed: f32,
+    interpolation: Interpolation,
+    rotation_interp: RotationInterp,
+    track_mask: Vec<bool>,
+    step_tracks: Vec<bool>,
 }
 
 pub type SamplingJobRef<'t> = SamplingJob<&'t Animation, &'t mut [SoaTransform], &'t mut SamplingContext>;
 pub type SamplingJobRc = SamplingJob<Rc<Animation>, Rc<RefCell<Vec<SoaTransform>>>, SamplingContext>;
+#[cfg(feature = "std")]
 pub type SamplingJobArc = SamplingJob<Arc<Animation>, Arc<RwLock<Vec<SoaTransform>>>, SamplingContext>;
 
 impl<A, O, C> Default for SamplingJob<A, O, C>
@@ -985,6 +1085,13 @@ where
             context: None,
             ratio: 0.0,
             output: None,
+            root_track: 0,
+            looping: false,
+            speed: 1.0,
+            interpolation: Interpolation::Linear,
+            rotation_interp: RotationInterp::Nlerp,
+            track_mask: Vec::new(),
+            step_tracks: Vec::new(),
         }
     }
 }
@@ -1051,11 +1158,185 @@ where
     ///
     /// This ratio is clamped before job execution in order to resolves any approximation issue on range
     /// bounds.
+    ///
+    /// Animations with a zero duration are static poses, always sampled at ratio 0.
     #[inline]
     pub fn set_ratio(&mut self, ratio: f32) {
         self.ratio = f32_clamp_or_max(ratio, 0.0f32, 1.0f32);
     }
 
+    /// Sets the time ratio of `SamplingJob` from a typed `Ratio`, see `set_ratio()`.
+    #[inline]
+    pub fn set_typed_ratio(&mut self, ratio: Ratio) {
+        self.ratio = ratio.value();
+    }
+
+    /// Sets the time ratio of `SamplingJob`, wrapped in range 0.0-1.0 (excluded) for looping animations.
+    ///
+    /// Ratio 1.0 wraps to 0.0, 1.25 to 0.25 and -0.25 to 0.75. Animation tracks always have keys at ratio 0.0
+    /// and 1.0, so the wrapped ratio is bracketed by the first keys right after the loop seam.
+    #[inline]
+    pub fn set_ratio_looped(&mut self, ratio: f32) {
+        // Tiny negative ratios round up to 1.0, which wraps to 0.0 as well.
+        let looped = f32_clamp_or_max(ratio - ratio.floor(), 0.0f32, 1.0f32);
+        self.ratio = if looped >= 1.0 { 0.0 } else { looped };
+    }
+
+    /// Sets the time ratio of `SamplingJob` from a time in seconds.
+    ///
+    /// The time is divided by the animation duration. It's clamped in range 0.0-1.0, or wrapped if
+    /// `looping` is set. Requires an animation to be set.
+    pub fn set_time(&mut self, seconds: f32) -> Result<(), OzzError> {
+        self.set_typed_time(Seconds::new(seconds))
+    }
+
+    /// Sets the time ratio of `SamplingJob` from a typed `Seconds`, see `set_time()`.
+    pub fn set_typed_time(&mut self, seconds: Seconds) -> Result<(), OzzError> {
+        let seconds = seconds.value();
+        let animation = self.animation.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        self.ratio = if self.looping {
+            animation.ratio_at_looped_time(seconds)
+        } else {
+            animation.ratio_at_time(seconds)
+        };
+        Ok(())
+    }
+
+    /// Gets looping flag of `SamplingJob`.
+    #[inline]
+    pub fn looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Sets looping flag of `SamplingJob`.
+    ///
+    /// If set, `set_time()` and `advance()` wrap times outside of the animation duration instead of clamping
+    /// them.
+    #[inline]
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Gets playback speed of `SamplingJob`.
+    #[inline]
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Sets playback speed of `SamplingJob`.
+    ///
+    /// Multiplies the time step of `advance()`. Default is 1.0, negative values play the animation in reverse,
+    /// and 0.0 pauses it.
+    #[inline]
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    /// Advances the time ratio of `SamplingJob` by `dt` seconds, scaled by `speed`.
+    ///
+    /// The new time is clamped to the animation duration, or wrapped if `looping` is set. Returns the new ratio,
+    /// and whether a loop boundary (the end going forward, or the beginning in reverse) was crossed, which is
+    /// always false if not looping. Requires an animation to be set.
+    pub fn advance(&mut self, dt: f32) -> Result<(f32, bool), OzzError> {
+        let animation = self.animation.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let duration = animation.duration();
+        if duration <= 0.0 {
+            self.ratio = 0.0;
+            return Ok((0.0, false));
+        }
+
+        let time = self.ratio * duration + dt * self.speed;
+        let crossed = self.looping && (time >= duration || time < 0.0);
+        self.ratio = if self.looping {
+            animation.ratio_at_looped_time(time)
+        } else {
+            animation.ratio_at_time(time)
+        };
+        Ok((self.ratio, crossed))
+    }
+
+    /// Gets interpolation mode of `SamplingJob`.
+    #[inline]
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    /// Sets interpolation mode of `SamplingJob`.
+    ///
+    /// Applies to translations and scales, rotations are always normalized-lerped. Default is
+    /// `Interpolation::Linear`. `Interpolation::Cubic` gives smoother motion on sparse keyframes, but needs to
+    /// look up neighbor keys on every run, which is slower.
+    #[inline]
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+    }
+
+    /// Gets rotation interpolation mode of `SamplingJob`.
+    #[inline]
+    pub fn rotation_interp(&self) -> RotationInterp {
+        self.rotation_interp
+    }
+
+    /// Sets rotation interpolation mode of `SamplingJob`.
+    ///
+    /// Default is `RotationInterp::Nlerp`. `RotationInterp::Slerp` keeps a constant angular velocity between
+    /// keys, which matters for slow and wide rotations, at the cost of trigonometric functions.
+    #[inline]
+    pub fn set_rotation_interp(&mut self, rotation_interp: RotationInterp) {
+        self.rotation_interp = rotation_interp;
+    }
+
+    /// Gets track mask of `SamplingJob`.
+    #[inline]
+    pub fn track_mask(&self) -> &[bool] {
+        &self.track_mask
+    }
+
+    /// Sets track mask of `SamplingJob`.
+    ///
+    /// Only tracks whose mask entry is true are sampled, other tracks are left unchanged in the output
+    /// buffer. Tracks beyond the end of the mask aren't sampled. Keyframes are decompressed by groups of 4
+    /// tracks (a soa track), groups without any requested track are skipped.
+    ///
+    /// An empty mask (the default) samples all tracks.
+    #[inline]
+    pub fn set_track_mask(&mut self, track_mask: &[bool]) {
+        self.track_mask.clear();
+        self.track_mask.extend_from_slice(track_mask);
+    }
+
+    /// Clears track mask of `SamplingJob`, so that all tracks are sampled.
+    #[inline]
+    pub fn clear_track_mask(&mut self) {
+        self.track_mask.clear();
+    }
+
+    /// Gets step tracks of `SamplingJob`.
+    #[inline]
+    pub fn step_tracks(&self) -> &[bool] {
+        &self.step_tracks
+    }
+
+    /// Sets step tracks of `SamplingJob`.
+    ///
+    /// Tracks whose entry is true use step (constant) interpolation: they hold the value of a key until the
+    /// ratio reaches the next key, for translations, rotations and scales, whatever `interpolation()` and
+    /// `rotation_interp()`. Useful for values that shouldn't blend, like visibility or switches encoded as
+    /// animation. Tracks beyond the end of the slice are interpolated as usual.
+    ///
+    /// An empty slice (the default) interpolates all tracks.
+    #[inline]
+    pub fn set_step_tracks(&mut self, step_tracks: &[bool]) {
+        self.step_tracks.clear();
+        self.step_tracks.extend_from_slice(step_tracks);
+    }
+
+    /// Clears step tracks of `SamplingJob`, so that all tracks are interpolated.
+    #[inline]
+    pub fn clear_step_tracks(&mut self) {
+        self.step_tracks.clear();
+    }
+
     /// Gets output of `SamplingJob`.
     #[inline]
     pub fn output(&self) -> Option<&O> {
@@ -1080,6 +1361,57 @@ where
         self.output = None;
     }
 
+    /// Gets root track of `SamplingJob`.
+    #[inline]
+    pub fn root_track(&self) -> usize {
+        self.root_track
+    }
+
+    /// Sets root track of `SamplingJob`.
+    ///
+    /// Index of the animation track used by `root_motion_delta()`. Default is 0, the skeleton root joint.
+    #[inline]
+    pub fn set_root_track(&mut self, root_track: usize) {
+        self.root_track = root_track;
+    }
+
+    /// Computes root track motion between `prev_ratio` and `ratio`.
+    ///
+    /// Returns the translation delta and the rotation delta, such that
+    /// `rotation(ratio) = delta * rotation(prev_ratio)`. The rotation delta is normalized, so that deltas can be
+    /// chained every frame without drifting.
+    /// Both ratios are clamped in range 0.0-1.0. If `ratio` is less than `prev_ratio`, animation is considered
+    /// looping: motion from `prev_ratio` to the end of the animation is accumulated with motion from the beginning
+    /// of the animation to `ratio`.
+    ///
+    /// It doesn't use nor update the sampling context, so it can be called at any time once animation is set.
+    pub fn root_motion_delta(&self, prev_ratio: f32, ratio: f32) -> Result<(Vec3, Quat), OzzError> {
+        let anim = self.animation.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let prev_ratio = f32_clamp_or_max(prev_ratio, 0.0f32, 1.0f32);
+        let ratio = f32_clamp_or_max(ratio, 0.0f32, 1.0f32);
+
+        let (prev_translation, prev_rotation) = Self::sample_track(anim, self.root_track, prev_ratio)?;
+        let (translation, rotation) = Self::sample_track(anim, self.root_track, ratio)?;
+        if ratio >= prev_ratio {
+            let delta_rotation = rotation * prev_rotation.inverse();
+            return Ok((translation - prev_translation, delta_rotation.normalize()));
+        }
+
+        let (begin_translation, begin_rotation) = Self::sample_track(anim, self.root_track, 0.0)?;
+        let (end_translation, end_rotation) = Self::sample_track(anim, self.root_track, 1.0)?;
+        let delta_translation = (end_translation - prev_translation) + (translation - begin_translation);
+        let delta_rotation = (rotation * begin_rotation.inverse()) * (end_rotation * prev_rotation.inverse());
+        Ok((delta_translation, delta_rotation.normalize()))
+    }
+
+    fn sample_track(animation: &Animation, track: usize, ratio: f32) -> Result<(Vec3, Quat), OzzError> {
+        let (t0, t1, alpha) = animation.bracket_translation(track, ratio)?;
+        let translation = t0.decompress().lerp(t1.decompress(), alpha);
+        let (r0, r1, alpha) = animation.bracket_rotation(track, ratio)?;
+        let rotation = r0.decompress().lerp(r1.decompress(), alpha);
+        Ok((translation, rotation))
+    }
+
     /// Validates `SamplingJob` parameters.
     pub fn validate(&self) -> bool {
         (|| {
@@ -1097,6 +1429,11 @@ where
     /// Runs job's sampling task.
     /// The validate job before any operation is performed.
     pub fn run(&mut self) -> Result<(), OzzError> {
+        self.run_counted().map(|_| ())
+    }
+
+    // Runs job's sampling task, returns the number of soa track channels decompressed.
+    fn run_counted(&mut self) -> Result<usize, OzzError> {
         let anim = self.animation.as_ref().ok_or(OzzError::InvalidJob)?.obj();
         let ctx = self.context.as_mut().ok_or(OzzError::InvalidJob)?;
         let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
@@ -1108,30 +1445,281 @@ where
         }
 
         if anim.num_soa_tracks() == 0 {
-            return Ok(());
+            return Ok(0);
         }
 
-        let prev_ratio = Self::step_context(ctx.as_mut(), anim, self.ratio);
+        // A zero duration animation is a static pose, sampled at its first keys.
+        let ratio = if anim.duration() > 0.0 { self.ratio } else { 0.0 };
+        Self::sample(
+            anim,
+            ctx.as_mut(),
+            ratio,
+            self.interpolation,
+            self.rotation_interp,
+            &self.track_mask,
+            &self.step_tracks,
+            &mut output,
+        )
+    }
+
+    /// Runs job's sampling task, and counts the work done, see `SamplingStats`.
+    ///
+    /// Counters are accumulated while the context is updated, without extra allocation. Requested soa tracks only
+    /// are counted if a track mask is set.
+    pub fn run_with_stats(&mut self) -> Result<SamplingStats, OzzError> {
+        let decompressed = self.run_counted()?;
 
-        let args = ctx.as_mut().translation_update_args(anim);
-        Self::update_cache(args, anim, &anim.translations_ctrl(), self.ratio, prev_ratio);
-        let args = ctx.as_mut().translation_decompress_args();
-        Self::decompress_float3(args, anim.timepoints(), &anim.translations_ctrl(), anim.translations());
+        let anim = self.animation.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let soa_tracks = (0..anim.num_soa_tracks())
+            .filter(|idx| Self::soa_track_mask(&self.track_mask, *idx).any())
+            .count();
+        Ok(SamplingStats {
+            soa_tracks,
+            keys_decompressed: decompressed * 8,
+            cache_hits: soa_tracks * 3 - decompressed,
+            cache_misses: decompressed,
+        })
+    }
+
+    /// Runs job's sampling task, and computes joints velocities over `dt` seconds.
+    ///
+    /// The pose at the job's ratio is output as `run()` does. Velocities are the finite differences between 2
+    /// poses sampled `dt` seconds apart. The second pose is sampled forward from the job's ratio, wrapping across
+    /// the loop boundary if `looping` is set. Otherwise, near the end of the animation, it's sampled backward.
+    ///
+    /// `scratch` receives the second pose, it must be at least `num_soa_tracks` long. It's sampled before the
+    /// job's ratio, so that the context is left as `run()` leaves it.
+    ///
+    /// `linear` receives joints translation velocities (per second), and `angular` joints rotation velocities
+    /// (rotation axis scaled by radians per second), both in joints' parent space. They must be at least
+    /// `num_soa_tracks` long. Velocities are zero for zero duration animations, and for tracks excluded by the
+    /// track mask.
+    pub fn run_with_velocities(
+        &mut self,
+        dt: f32,
+        scratch: &mut [SoaTransform],
+        linear: &mut [SoaVec3],
+        angular: &mut [SoaVec3],
+    ) -> Result<(), OzzError> {
+        let anim = self.animation.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let num_soa_tracks = anim.num_soa_tracks();
+        let mut ok = self.validate() && dt > 0.0 && dt.is_finite();
+        ok &= scratch.len() >= num_soa_tracks;
+        ok &= linear.len() >= num_soa_tracks && angular.len() >= num_soa_tracks;
+        if !ok {
+            return Err(OzzError::InvalidJob);
+        }
 
-        let args = ctx.as_mut().rotation_update_args(anim);
-        Self::update_cache(args, anim, &anim.rotations_ctrl(), self.ratio, prev_ratio);
-        let args = ctx.as_mut().rotation_decompress_args();
-        Self::decompress_quat(args, anim.timepoints(), &anim.rotations_ctrl(), anim.rotations());
+        let duration = anim.duration();
+        if num_soa_tracks == 0 || duration <= 0.0 || duration.is_nan() {
+            linear[..num_soa_tracks].fill(SoaVec3::ZERO);
+            angular[..num_soa_tracks].fill(SoaVec3::ZERO);
+            return self.run();
+        }
 
-        let args = ctx.as_mut().scale_update_args(anim);
-        Self::update_cache(args, anim, &anim.scales_ctrl(), self.ratio, prev_ratio);
-        let args = ctx.as_mut().scale_decompress_args();
-        Self::decompress_float3(args, anim.timepoints(), &anim.scales_ctrl(), anim.scales());
+        let step = f32::min(dt / duration, 1.0);
+        let forward = self.looping || self.ratio + step <= 1.0;
+        let other_ratio = match (forward, self.ratio + step > 1.0) {
+            (true, true) => self.ratio + step - 1.0,
+            (true, false) => self.ratio + step,
+            (false, _) => f32::max(self.ratio - step, 0.0),
+        };
+        let ctx = self.context.as_mut().ok_or(OzzError::InvalidJob)?.as_mut();
+        Self::sample(
+            anim,
+            ctx,
+            other_ratio,
+            self.interpolation,
+            self.rotation_interp,
+            &self.track_mask,
+            &self.step_tracks,
+            &mut scratch[..num_soa_tracks],
+        )?;
+        self.run()?;
+
+        let output = self.output.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let (pose0, pose1) = match forward {
+            true => (&output[..num_soa_tracks], &scratch[..num_soa_tracks]),
+            false => (&scratch[..num_soa_tracks], &output[..num_soa_tracks]),
+        };
+        let inv_dt = f32x4::splat(1.0 / (step * duration));
+        for (idx, (p0, p1)) in pose0.iter().zip(pose1.iter()).enumerate() {
+            let mask = Self::soa_track_mask(&self.track_mask, idx);
+            let translation = p1.translation.sub(&p0.translation).mul_num(inv_dt);
+            linear[idx] = SoaVec3 {
+                x: mask.select(translation.x, ZERO),
+                y: mask.select(translation.y, ZERO),
+                z: mask.select(translation.z, ZERO),
+            };
+            for lane in 0..4 {
+                // Shortest rotation from pose0 to pose1.
+                let mut delta = p1.rotation.quat(lane) * p0.rotation.quat(lane).conjugate();
+                if delta.w < 0.0 {
+                    delta = -delta;
+                }
+                let velocity = if mask.test(lane) {
+                    delta.to_scaled_axis() * inv_dt[0]
+                } else {
+                    Vec3::ZERO
+                };
+                angular[idx].set_vec3(lane, velocity);
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs job's sampling task, with the time ratio mapped through a warp curve.
+    ///
+    /// `warp` is sampled at the job's ratio, and its value is used as the ratio to sample the animation. This
+    /// allows non-linear time mappings, like ease-in/out or speed ramps. Warp values must be non-decreasing
+    /// and in range 0.0-1.0, otherwise `OzzError::InvalidJob` is returned. The job's ratio isn't modified.
+    pub fn sample_warped(&mut self, warp: &Track<f32>) -> Result<(), OzzError> {
+        let values = warp.values();
+        let mut ok = values.iter().all(|value| (0.0..=1.0).contains(value));
+        ok &= values.windows(2).all(|pair| pair[0] <= pair[1]);
+        if !ok {
+            return Err(OzzError::InvalidJob);
+        }
 
-        Self::interpolates(anim, ctx.as_mut(), self.ratio, &mut output)?;
+        let mut warp_job: TrackSamplingJobRef<f32> = TrackSamplingJob::default();
+        warp_job.set_track(warp);
+        warp_job.set_ratio(self.ratio);
+        warp_job.run()?;
+
+        let ratio = self.ratio;
+        self.ratio = f32_clamp_or_max(warp_job.result(), 0.0, 1.0);
+        let result = self.run();
+        self.ratio = ratio;
+        result
+    }
+
+    /// Writes the sampled output as a flat buffer of interleaved TRS, for GPU upload.
+    ///
+    /// Each joint (animation track) takes `TRS_INTERLEAVED_STRIDE` floats: translation `x, y, z`, rotation
+    /// quaternion `x, y, z, w`, then scale `x, y, z`. Padding tracks aren't written. Must be called after
+    /// `run()`, as it only transposes the current output. `out` must be at least `num_tracks *
+    /// TRS_INTERLEAVED_STRIDE` long, otherwise `OzzError::InvalidJob` is returned.
+    pub fn write_trs_interleaved(&self, out: &mut [f32]) -> Result<(), OzzError> {
+        let anim = self.animation.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let output = self.output.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+
+        let mut ok = output.len() >= anim.num_soa_tracks();
+        ok &= out.len() >= anim.num_tracks() * TRS_INTERLEAVED_STRIDE;
+        if !ok {
+            return Err(OzzError::InvalidJob);
+        }
+
+        for (track, trs) in out
+            .chunks_exact_mut(TRS_INTERLEAVED_STRIDE)
+            .take(anim.num_tracks())
+            .enumerate()
+        {
+            let soa = &output[track / 4];
+            let lane = track % 4;
+            trs[0..3].copy_from_slice(&soa.translation.vec3(lane).to_array());
+            trs[3..7].copy_from_slice(&soa.rotation.quat(lane).to_array());
+            trs[7..10].copy_from_slice(&soa.scale.vec3(lane).to_array());
+        }
         Ok(())
     }
 
+    /// Runs job's sampling task, writing each track's `(translation, rotation, scale)` to `out`.
+    ///
+    /// Samples like `run()`, but each soa track is interpolated on the stack and written straight to `out`, so
+    /// that callers storing poses as arrays of structures don't need a SoA buffer: job output isn't needed and
+    /// is left unchanged. `out` length must be
+    /// `num_tracks`, otherwise `OzzError::InvalidJob` is returned. Tracks excluded by the track mask keep their
+    /// `out` values.
+    pub fn sample_into(&mut self, out: &mut [(Vec3, Quat, Vec3)]) -> Result<(), OzzError> {
+        let anim = self.animation.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let ctx = self.context.as_mut().ok_or(OzzError::InvalidJob)?;
+
+        let mut ok = ctx.as_ref().max_soa_tracks() >= anim.num_soa_tracks();
+        ok &= out.len() == anim.num_tracks();
+        if !ok {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let ratio = if anim.duration() > 0.0 { self.ratio } else { 0.0 };
+        let ctx = ctx.as_mut();
+        Self::update_keys(anim, ctx, ratio, &self.track_mask);
+
+        let ratio4 = f32x4::splat(ratio);
+        for (idx, out) in out.chunks_mut(4).enumerate() {
+            let lanes = Self::soa_track_mask(&self.track_mask, idx);
+            if !lanes.any() {
+                continue;
+            }
+            let steps = Self::soa_step_mask(&self.step_tracks, idx);
+            let soa = Self::interpolate_soa(ctx, idx, ratio4, self.rotation_interp, steps);
+            for (lane, trs) in out.iter_mut().enumerate() {
+                if !lanes.test(lane) {
+                    continue;
+                }
+                let (mut translation, mut scale) = (soa.translation.vec3(lane), soa.scale.vec3(lane));
+                if self.interpolation == Interpolation::Cubic && !steps.test(lane) {
+                    (translation, scale) = Self::interpolate_cubic(anim, ctx, idx * 4 + lane, ratio);
+                }
+                *trs = (translation, soa.rotation.quat(lane), scale);
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn sample(
+        anim: &Animation,
+        ctx: &mut SamplingContext,
+        ratio: f32,
+        interpolation: Interpolation,
+        rotation_interp: RotationInterp,
+        track_mask: &[bool],
+        step_tracks: &[bool],
+        output: &mut [SoaTransform],
+    ) -> Result<usize, OzzError> {
+        let decompressed = Self::update_keys(anim, ctx, ratio, track_mask);
+        Self::interpolates(anim, ctx, ratio, rotation_interp, track_mask, step_tracks, output)?;
+        if interpolation == Interpolation::Cubic {
+            Self::interpolates_cubic(anim, ctx, ratio, track_mask, step_tracks, output);
+        }
+        Ok(decompressed)
+    }
+
+    // Updates the keys cached in the context, so that they bracket `ratio`.
+    // Returns the number of soa track channels decompressed.
+    fn update_keys(anim: &Animation, ctx: &mut SamplingContext, ratio: f32, track_mask: &[bool]) -> usize {
+        let prev_ratio = Self::step_context(ctx, anim, ratio);
+
+        let args = ctx.translation_update_args(anim);
+        Self::update_cache(args, anim, &anim.translations_ctrl(), ratio, prev_ratio);
+        let args = ctx.translation_decompress_args();
+        let mut decompressed = Self::decompress_float3(
+            args,
+            anim.timepoints(),
+            &anim.translations_ctrl(),
+            anim.translations(),
+            track_mask,
+        );
+
+        let args = ctx.rotation_update_args(anim);
+        Self::update_cache(args, anim, &anim.rotations_ctrl(), ratio, prev_ratio);
+        let args = ctx.rotation_decompress_args();
+        decompressed += Self::decompress_quat(
+            args,
+            anim.timepoints(),
+            &anim.rotations_ctrl(),
+            anim.rotations(),
+            track_mask,
+        );
+
+        let args = ctx.scale_update_args(anim);
+        Self::update_cache(args, anim, &anim.scales_ctrl(), ratio, prev_ratio);
+        let args = ctx.scale_decompress_args();
+        decompressed +=
+            Self::decompress_float3(args, anim.timepoints(), &anim.scales_ctrl(), anim.scales(), track_mask);
+        decompressed
+    }
+
     #[inline]
     fn step_context(ctx: &mut SamplingContext, animation: &Animation, ratio: f32) -> f32 {
         let animation_id = animation as *const _ as u64;
@@ -1275,6 +1863,15 @@ where
         0
     }
 
+    // Lanes of soa track `idx` requested by `track_mask`, all lanes if `track_mask` is empty.
+    #[inline]
+    fn soa_track_mask(track_mask: &[bool], idx: usize) -> mask32x4 {
+        if track_mask.is_empty() {
+            return mask32x4::splat(true);
+        }
+        mask32x4::from_array([0, 1, 2, 3].map(|lane| track_mask.get(idx * 4 + lane).copied().unwrap_or(false)))
+    }
+
     #[inline(always)]
     fn key_ratio(ctrl: &KeyframesCtrl<'_>, timepoints: &[f32], at: usize) -> f32 {
         timepoints[ctrl.ratios[at] as usize]
@@ -1295,11 +1892,16 @@ where
         timepoints: &[f32],
         ctrl: &KeyframesCtrl<'_>,
         compressed: &[Float3Key],
-    ) {
+        track_mask: &[bool],
+    ) -> usize {
+        let mut decompressed = 0;
         for j in 0..args.outdated.len() {
             let mut outdated = args.outdated[j];
             for i in (8 * j)..(8 * j + 8) {
-                if outdated & 1 != 0 {
+                if outdated & 1 != 0 && Self::soa_track_mask(track_mask, i).any() {
+                    // Masked out soa tracks stay outdated, until they're requested.
+                    args.outdated[j] &= !(1 << (i - 8 * j));
+                    decompressed += 1;
                     let rights = &args.entries[i * 4..i * 4 + 4];
                     let lefts = [
                         rights[0] - (ctrl.previouses[rights[0] as usize] as u32),
@@ -1325,6 +1927,7 @@ where
                 outdated >>= 1;
             }
         }
+        decompressed
     }
 
     fn decompress_quat(
@@ -1332,11 +1935,16 @@ where
         timepoints: &[f32],
         ctrl: &KeyframesCtrl<'_>,
         compressed: &[QuaternionKey],
-    ) {
+        track_mask: &[bool],
+    ) -> usize {
+        let mut decompressed = 0;
         for j in 0..args.outdated.len() {
             let mut outdated = args.outdated[j];
             for i in (8 * j)..(8 * j + 8) {
-                if outdated & 1 != 0 {
+                if outdated & 1 != 0 && Self::soa_track_mask(track_mask, i).any() {
+                    // Masked out soa tracks stay outdated, until they're requested.
+                    args.outdated[j] &= !(1 << (i - 8 * j));
+                    decompressed += 1;
                     let rights = &args.entries[i * 4..i * 4 + 4];
                     let lefts = [
                         rights[0] - (ctrl.previouses[rights[0] as usize] as u32),
@@ -1362,29 +1970,222 @@ where
                 outdated >>= 1;
             }
         }
+        decompressed
     }
 
     fn interpolates(
         animation: &Animation,
         ctx: &mut SamplingContext,
         ratio: f32,
+        rotation_interp: RotationInterp,
+        track_mask: &[bool],
+        step_tracks: &[bool],
         output: &mut [SoaTransform],
     ) -> Result<(), OzzError> {
         let ratio4 = f32x4::splat(ratio);
         for (idx, out) in output.iter_mut().enumerate().take(animation.num_soa_tracks()) {
-            let translation = &ctx.translations()[idx];
-            let translation_ratio = (ratio4 - translation.ratio[0]) / (translation.ratio[1] - translation.ratio[0]);
-            out.translation = SoaVec3::lerp(&translation.value[0], &translation.value[1], translation_ratio);
+            let lanes = Self::soa_track_mask(track_mask, idx);
+            if !lanes.any() {
+                continue;
+            }
+            let steps = Self::soa_step_mask(step_tracks, idx);
+            let SoaTransform {
+                translation,
+                rotation,
+                scale,
+            } = Self::interpolate_soa(ctx, idx, ratio4, rotation_interp, steps);
+
+            if lanes.all() {
+                *out = SoaTransform::new(translation, rotation, scale);
+            } else {
+                // Keeps lanes of tracks that aren't requested.
+                out.translation.x = lanes.select(translation.x, out.translation.x);
+                out.translation.y = lanes.select(translation.y, out.translation.y);
+                out.translation.z = lanes.select(translation.z, out.translation.z);
+                out.rotation.x = lanes.select(rotation.x, out.rotation.x);
+                out.rotation.y = lanes.select(rotation.y, out.rotation.y);
+                out.rotation.z = lanes.select(rotation.z, out.rotation.z);
+                out.rotation.w = lanes.select(rotation.w, out.rotation.w);
+                out.scale.x = lanes.select(scale.x, out.scale.x);
+                out.scale.y = lanes.select(scale.y, out.scale.y);
+                out.scale.z = lanes.select(scale.z, out.scale.z);
+            }
+        }
+        Ok(())
+    }
+
+    // Interpolates soa track `idx` between the keys cached in the context.
+    #[inline]
+    fn interpolate_soa(
+        ctx: &SamplingContext,
+        idx: usize,
+        ratio4: f32x4,
+        rotation_interp: RotationInterp,
+        steps: mask32x4,
+    ) -> SoaTransform {
+        let translation = &ctx.translations()[idx];
+        let translation_ratio = (ratio4 - translation.ratio[0]) / (translation.ratio[1] - translation.ratio[0]);
+        let translation_ratio = Self::step_alpha(steps, translation_ratio);
+        let translation = SoaVec3::lerp(&translation.value[0], &translation.value[1], translation_ratio);
+
+        let rotation = &ctx.rotations()[idx];
+        let rotation_ratio = (ratio4 - rotation.ratio[0]) / (rotation.ratio[1] - rotation.ratio[0]);
+        let rotation_ratio = Self::step_alpha(steps, rotation_ratio);
+        let (from, to) = (&rotation.value[0], &rotation.value[1]);
+        let interpolated = match rotation_interp {
+            RotationInterp::Nlerp => SoaQuat::nlerp(from, to, rotation_ratio),
+            RotationInterp::Slerp => SoaQuat::slerp(from, to, rotation_ratio),
+        };
+        let rotation = Self::guard_antipodal(from, to, rotation_ratio, interpolated);
+
+        let scale = &ctx.scales()[idx];
+        let scale_ratio = (ratio4 - scale.ratio[0]) / (scale.ratio[1] - scale.ratio[0]);
+        let scale_ratio = Self::step_alpha(steps, scale_ratio);
+        let scale = SoaVec3::lerp(&scale.value[0], &scale.value[1], scale_ratio);
 
-            let rotation = &ctx.rotations()[idx];
-            let rotation_ratio = (ratio4 - rotation.ratio[0]) / (rotation.ratio[1] - rotation.ratio[0]);
-            out.rotation = SoaQuat::nlerp(&rotation.value[0], &rotation.value[1], rotation_ratio);
+        SoaTransform::new(translation, rotation, scale)
+    }
+
+    // Lanes of soa track `idx` using step interpolation, no lane if `step_tracks` is empty.
+    #[inline]
+    fn soa_step_mask(step_tracks: &[bool], idx: usize) -> mask32x4 {
+        mask32x4::from_array([0, 1, 2, 3].map(|lane| step_tracks.get(idx * 4 + lane).copied().unwrap_or(false)))
+    }
+
+    // Step lanes hold the first key until the second one is reached.
+    #[inline]
+    fn step_alpha(steps: mask32x4, alpha: f32x4) -> f32x4 {
+        steps.select(alpha.simd_ge(ONE).select(ONE, ZERO), alpha)
+    }
 
-            let scale = &ctx.scales()[idx];
-            let scale_ratio = (ratio4 - scale.ratio[0]) / (scale.ratio[1] - scale.ratio[0]);
-            out.scale = SoaVec3::lerp(&scale.value[0], &scale.value[1], scale_ratio);
+    // Nearly antipodal keys are linearly interpolated through (near) the origin, where normalization is
+    // numerically unstable. Such lanes fall back to the nearer key, which only happens with keys that aren't
+    // in the same hemisphere, and is what `QuaternionKey::decompress` guards against when restoring w.
+    #[inline]
+    fn guard_antipodal(from: &SoaQuat, to: &SoaQuat, alpha: f32x4, interpolated: SoaQuat) -> SoaQuat {
+        const EPSILON: f32x4 = f32x4::from_array([1e-6; 4]);
+
+        let lerp_x = (to.x - from.x) * alpha + from.x;
+        let lerp_y = (to.y - from.y) * alpha + from.y;
+        let lerp_z = (to.z - from.z) * alpha + from.z;
+        let lerp_w = (to.w - from.w) * alpha + from.w;
+        let len2 = lerp_x * lerp_x + lerp_y * lerp_y + lerp_z * lerp_z + lerp_w * lerp_w;
+        let degenerated = len2.simd_lt(EPSILON);
+        if !degenerated.any() {
+            return interpolated;
         }
-        Ok(())
+
+        let nearer = alpha.simd_lt(f32x4::splat(0.5));
+        SoaQuat {
+            x: degenerated.select(nearer.select(from.x, to.x), interpolated.x),
+            y: degenerated.select(nearer.select(from.y, to.y), interpolated.y),
+            z: degenerated.select(nearer.select(from.z, to.z), interpolated.z),
+            w: degenerated.select(nearer.select(from.w, to.w), interpolated.w),
+        }
+    }
+
+    // Overwrites linearly interpolated translations and scales with Catmull-Rom interpolated ones.
+    fn interpolates_cubic(
+        animation: &Animation,
+        ctx: &mut SamplingContext,
+        ratio: f32,
+        track_mask: &[bool],
+        step_tracks: &[bool],
+        output: &mut [SoaTransform],
+    ) {
+        for track in 0..animation.num_aligned_tracks() {
+            if !track_mask.is_empty() && !track_mask.get(track).copied().unwrap_or(false) {
+                continue;
+            }
+            if step_tracks.get(track).copied().unwrap_or(false) {
+                continue;
+            }
+            let (translation, scale) = Self::interpolate_cubic(animation, ctx, track, ratio);
+            let (soa, lane) = (&mut output[track / 4], track % 4);
+            soa.translation.x[lane] = translation.x;
+            soa.translation.y[lane] = translation.y;
+            soa.translation.z[lane] = translation.z;
+            soa.scale.x[lane] = scale.x;
+            soa.scale.y[lane] = scale.y;
+            soa.scale.z[lane] = scale.z;
+        }
+    }
+
+    // Catmull-Rom interpolated translation and scale of `track`.
+    fn interpolate_cubic(animation: &Animation, ctx: &mut SamplingContext, track: usize, ratio: f32) -> (Vec3, Vec3) {
+        let entry = ctx.translation_entries()[track] as usize;
+        let neighbor = &mut ctx.translation_neighbors_mut()[track];
+        let translation = Self::catmull_rom(
+            animation,
+            &animation.translations_ctrl(),
+            animation.translations(),
+            entry,
+            neighbor,
+            ratio,
+        );
+
+        let entry = ctx.scale_entries()[track] as usize;
+        let neighbor = &mut ctx.scale_neighbors_mut()[track];
+        let scale = Self::catmull_rom(
+            animation,
+            &animation.scales_ctrl(),
+            animation.scales(),
+            entry,
+            neighbor,
+            ratio,
+        );
+        (translation, scale)
+    }
+
+    // Interpolates between the key at `entry` and its previous key. Neighbors are found following previouses,
+    // the previous key of the first one, and the next key (the one referencing `entry`) of the last one.
+    // The next key is cached in `neighbor`, it's only searched for when entry changes.
+    fn catmull_rom(
+        animation: &Animation,
+        ctrl: &KeyframesCtrl<'_>,
+        compressed: &[Float3Key],
+        entry: usize,
+        neighbor: &mut u32,
+        ratio: f32,
+    ) -> Vec3 {
+        let num_aligned_tracks = animation.num_aligned_tracks();
+        let k2 = entry;
+        let k1 = k2 - ctrl.previouses[k2] as usize;
+        let k0 = if k1 >= num_aligned_tracks {
+            k1 - ctrl.previouses[k1] as usize
+        } else {
+            k1
+        };
+        let follows = |key: usize| key - ctrl.previouses[key] as usize == k2;
+        let first = usize::max(k2 + 1, num_aligned_tracks * 2);
+        let cached = *neighbor as usize;
+        let k3 = if cached >= first && cached < ctrl.previouses.len() && follows(cached) {
+            cached
+        } else {
+            // Playing forward, entry moves to the previously cached neighbor, whose next key is further.
+            let from = if cached == k2 {
+                usize::max(first, cached + 1)
+            } else {
+                first
+            };
+            let k3 = (from..ctrl.previouses.len()).find(|&key| follows(key)).unwrap_or(k2);
+            *neighbor = k3 as u32;
+            k3
+        };
+
+        let r1 = Self::key_ratio(ctrl, animation.timepoints(), k1);
+        let r2 = Self::key_ratio(ctrl, animation.timepoints(), k2);
+        let t = (ratio - r1) / (r2 - r1);
+        let (t2, t3) = (t * t, t * t * t);
+
+        let p0 = compressed[k0].decompress();
+        let p1 = compressed[k1].decompress();
+        let p2 = compressed[k2].decompress();
+        let p3 = compressed[k3].decompress();
+        0.5 * ((2.0 * p1)
+            + (p2 - p0) * t
+            + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+            + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
     }
 }
 
@@ -1433,17 +2234,54 @@ fn decode_gv4_stream<'t>(buffer: &'t [u8], stream: &mut [u32]) -> &'t [u8] {
 
 #[cfg(test)]
 mod sampling_tests {
-    use glam::{Quat, Vec3};
+    use core::f32::consts;
     use wasm_bindgen_test::*;
 
     use super::*;
-    use crate::animation::AnimationRaw;
+    use crate::animation::{AnimationRaw, Float3KeyF32, QuaternionKeyF32};
+    use crate::animation_builder::AnimationBuilder;
     use crate::base::OzzBuf;
 
     fn make_buf<T>(v: Vec<T>) -> Rc<RefCell<Vec<T>>> {
         Rc::new(RefCell::new(v))
     }
 
+    fn sample_pose_with(animation: &Animation, ctx: &mut SamplingContext, ratio: f32) -> Vec<SoaTransform> {
+        let mut pose = vec![SoaTransform::IDENTITY; animation.num_soa_tracks()];
+        let mut job: SamplingJobRef = SamplingJob::default();
+        job.set_animation(animation);
+        job.set_context(ctx);
+        job.set_output(&mut pose);
+        job.set_ratio(ratio);
+        job.run().unwrap();
+        pose
+    }
+
+    // Pushes the same key at ratio 0 and 1 for each given transformation type of `track`.
+    fn constant_track(
+        builder: &mut AnimationBuilder,
+        track: usize,
+        translation: Option<Vec3>,
+        rotation: Option<Quat>,
+        scale: Option<Vec3>,
+    ) {
+        for ratio in [0.0, 1.0] {
+            if let Some(translation) = translation {
+                builder
+                    .push_translation(track, ratio, Float3KeyF32::from(translation))
+                    .unwrap();
+            }
+            if let Some(rotation) = rotation {
+                builder
+                    .push_rotation(track, ratio, QuaternionKeyF32::from(rotation))
+                    .unwrap();
+            }
+            if let Some(scale) = scale {
+                builder.push_scale(track, ratio, Float3KeyF32::from(scale)).unwrap();
+            }
+        }
+    }
+
     // f16 -> f32
     // ignore overflow, infinite, NaN
     pub fn f16(f: f32) -> u16 {
@@ -1691,6 +2529,45 @@ mod sampling_tests {
         );
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_sampling_zero_duration() {
+        let mut ar = empty_animation_raw::<1>(0.0);
+        ar.timepoints = vec![0.0, 1.0];
+        ar.translations[0] = Float3Key::new([f16(1.0), f16(-1.0), f16(5.0)]);
+        // never reached, a zero duration animation is sampled at ratio 0
+        ar.translations[4] = Float3Key::new([f16(2.0), f16(2.0), f16(2.0)]);
+        let animation = Rc::new(Animation::from_raw(&ar));
+        assert_eq!(animation.ratio_at_time(1.0), 0.0);
+        assert_eq!(animation.ratio_at_looped_time(-1.0), 0.0);
+
+        let output = make_buf(vec![TX; 1]);
+        let mut job = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::new(1));
+        job.set_output(output.clone());
+        for (looping, time) in [
+            (false, 0.0),
+            (false, 0.5),
+            (false, -3.0),
+            (true, 7.25),
+            (false, f32::INFINITY),
+        ] {
+            job.set_looping(looping);
+            job.set_time(time).unwrap();
+            assert_eq!(job.ratio(), 0.0);
+            job.run().unwrap();
+            let out = output.borrow()[0];
+            assert_eq!(out.translation.vec3(0), Vec3::new(1.0, -1.0, 5.0));
+            assert!(out.rotation.quat(0).abs_diff_eq(QU, 1e-4));
+            assert_eq!(out.scale.vec3(0), V1);
+        }
+
+        job.set_ratio(0.7);
+        job.run().unwrap();
+        assert_eq!(output.borrow()[0].translation.vec3(0), Vec3::new(1.0, -1.0, 5.0));
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_sampling_1_track_2_key() {
@@ -1809,6 +2686,782 @@ mod sampling_tests {
         );
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_cubic_interpolation() {
+        let mut builder = AnimationBuilder::new(1.0, 1);
+        builder
+            .push_translation(0, 0.0, Float3KeyF32::new([0.0, 0.0, 0.0]))
+            .unwrap();
+        builder
+            .push_translation(0, 0.5, Float3KeyF32::new([0.0, 1.0, 0.0]))
+            .unwrap();
+        builder
+            .push_translation(0, 1.0, Float3KeyF32::new([0.0, 4.0, 0.0]))
+            .unwrap();
+        builder
+            .push_rotation(0, 0.0, QuaternionKeyF32::from(Quat::IDENTITY))
+            .unwrap();
+        builder
+            .push_rotation(0, 1.0, QuaternionKeyF32::from(Quat::IDENTITY))
+            .unwrap();
+        builder.push_scale(0, 0.0, Float3KeyF32::new([1.0; 3])).unwrap();
+        builder.push_scale(0, 1.0, Float3KeyF32::new([3.0; 3])).unwrap();
+        let animation = Rc::new(builder.build().unwrap());
+
+        let mut job: SamplingJob = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(make_buf(vec![SoaTransform::default(); 1]));
+        assert_eq!(job.interpolation(), Interpolation::Linear);
+
+        let sample = |job: &mut SamplingJob, ratio: f32| {
+            job.set_ratio(ratio);
+            job.run().unwrap();
+            let output = job.output().unwrap().buf().unwrap();
+            (output[0].translation.y[0], output[0].scale.x[0])
+        };
+
+        assert_eq!(sample(&mut job, 0.25), (0.5, 1.5));
+
+        // Catmull-Rom with p0 = 0 (repeated first key), p1 = 0, p2 = 1, p3 = 4 at t = 0.5:
+        // 0.5 * (2 * p1 + (p2 - p0) * t + (2 * p0 - 5 * p1 + 4 * p2 - p3) * t^2 + (3 * p1 - p0 - 3 * p2 + p3) * t^3)
+        // Scale has p0 = p1 = 1, p2 = p3 = 3 at t = 0.25.
+        job.set_interpolation(Interpolation::Cubic);
+        assert_eq!(sample(&mut job, 0.25), (0.3125, 1.40625));
+        // p0 = 0, p1 = 1, p2 = 4, p3 = 4 (repeated last key) at t = 0.5, and scale at t = 0.75.
+        assert_eq!(sample(&mut job, 0.75), (2.5625, 2.59375));
+
+        // Keys are reached exactly.
+        assert_eq!(sample(&mut job, 0.0), (0.0, 1.0));
+        assert_eq!(sample(&mut job, 0.5), (1.0, 2.0));
+        assert_eq!(sample(&mut job, 1.0), (4.0, 3.0));
+
+        // Rotations stay interpolated linearly.
+        let output = job.output().unwrap().buf().unwrap();
+        assert!(output[0].rotation.x[0].abs() < 1e-4);
+        assert!((output[0].rotation.w[0] - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_cubic_neighbor_cache() {
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let new_job = || {
+            let mut job: SamplingJob = SamplingJob::default();
+            job.set_animation(animation.clone());
+            job.set_context(SamplingContext::from_animation(&animation));
+            job.set_output(make_buf(vec![SoaTransform::default(); animation.num_soa_tracks()]));
+            job.set_interpolation(Interpolation::Cubic);
+            job
+        };
+
+        // cached neighbors (forward, then seeking backward) match neighbors searched from scratch
+        let mut job = new_job();
+        for ratio in (0..=40).map(|n| n as f32 / 40.0).chain([0.3, 0.1, 0.9]) {
+            job.set_ratio(ratio);
+            job.run().unwrap();
+            let mut fresh = new_job();
+            fresh.set_ratio(ratio);
+            fresh.run().unwrap();
+            assert_eq!(
+                *job.output().unwrap().buf().unwrap(),
+                *fresh.output().unwrap().buf().unwrap(),
+                "{}",
+                ratio
+            );
+        }
+        let ctx = job.context().unwrap();
+        assert!(ctx.translation_neighbors().iter().any(|&key| key != 0));
+        assert!(ctx.scale_neighbors().iter().any(|&key| key != 0));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_rotation_interp() {
+        let angle = 170.0f32.to_radians();
+        let mut builder = AnimationBuilder::new(1.0, 1);
+        builder.push_translation(0, 0.0, Float3KeyF32::new([0.0; 3])).unwrap();
+        builder.push_translation(0, 1.0, Float3KeyF32::new([0.0; 3])).unwrap();
+        builder
+            .push_rotation(0, 0.0, QuaternionKeyF32::from(Quat::IDENTITY))
+            .unwrap();
+        builder
+            .push_rotation(0, 1.0, QuaternionKeyF32::from(Quat::from_rotation_y(angle)))
+            .unwrap();
+        builder.push_scale(0, 0.0, Float3KeyF32::new([1.0; 3])).unwrap();
+        builder.push_scale(0, 1.0, Float3KeyF32::new([1.0; 3])).unwrap();
+        let animation = Rc::new(builder.build().unwrap());
+
+        let mut job: SamplingJob = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(make_buf(vec![SoaTransform::default(); 1]));
+        job.set_ratio(0.25);
+        assert_eq!(job.rotation_interp(), RotationInterp::Nlerp);
+
+        let sample = |job: &mut SamplingJob| {
+            job.run().unwrap();
+            let rotation = job.output().unwrap().buf().unwrap()[0].rotation;
+            Quat::from_xyzw(rotation.x[0], rotation.y[0], rotation.z[0], rotation.w[0])
+        };
+
+        let expected = Quat::from_rotation_y(angle * 0.25);
+        let nlerp = sample(&mut job);
+        job.set_rotation_interp(RotationInterp::Slerp);
+        let slerp = sample(&mut job);
+
+        assert!(slerp.angle_between(expected) < 1e-3);
+        assert!(nlerp.angle_between(expected) > 1e-2);
+        assert!((slerp.length() - 1.0).abs() < 1e-5);
+
+        // Keys are reached exactly.
+        job.set_ratio(0.0);
+        assert!(sample(&mut job).angle_between(Quat::IDENTITY) < 1e-3);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_antipodal_rotation_keys() {
+        // Nearly the same rotation, but in opposite hemispheres.
+        let rotation = Quat::from_rotation_x(0.7) * Quat::from_rotation_z(-0.3);
+        let mut builder = AnimationBuilder::new(1.0, 1);
+        constant_track(&mut builder, 0, Some(Vec3::ZERO), None, Some(Vec3::ONE));
+        builder.push_rotation(0, 0.0, QuaternionKeyF32::from(rotation)).unwrap();
+        builder
+            .push_rotation(
+                0,
+                1.0,
+                QuaternionKeyF32::from(-(rotation * Quat::from_rotation_y(1e-4))),
+            )
+            .unwrap();
+        let animation = Rc::new(builder.build().unwrap());
+
+        let mut job: SamplingJob = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(make_buf(vec![SoaTransform::default(); 1]));
+        for rotation_interp in [RotationInterp::Nlerp, RotationInterp::Slerp] {
+            job.set_rotation_interp(rotation_interp);
+            for idx in 0..=40 {
+                job.set_ratio(idx as f32 / 40.0);
+                job.run().unwrap();
+                let sampled = job.output().unwrap().buf().unwrap()[0].rotation.quat(0);
+                assert!(sampled.is_finite(), "{:?} {}", rotation_interp, idx);
+                assert!((sampled.length() - 1.0).abs() < 1e-4, "{:?} {}", rotation_interp, idx);
+                assert!(sampled.dot(rotation).abs() > 0.9999, "{:?} {}", rotation_interp, idx);
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_root_motion_delta() {
+        let mut builder = AnimationBuilder::new(2.0, 2);
+        for track in 0..2 {
+            builder.push_scale(track, 0.0, Float3KeyF32::new([1.0; 3])).unwrap();
+            builder.push_scale(track, 1.0, Float3KeyF32::new([1.0; 3])).unwrap();
+        }
+        builder
+            .push_translation(0, 0.0, Float3KeyF32::new([0.0, 0.0, 0.0]))
+            .unwrap();
+        builder
+            .push_translation(0, 0.5, Float3KeyF32::new([0.0, 0.0, 1.0]))
+            .unwrap();
+        builder
+            .push_translation(0, 1.0, Float3KeyF32::new([0.0, 0.0, 4.0]))
+            .unwrap();
+        builder
+            .push_rotation(0, 0.0, QuaternionKeyF32::from(Quat::IDENTITY))
+            .unwrap();
+        builder
+            .push_rotation(0, 1.0, QuaternionKeyF32::from(Quat::from_rotation_y(consts::FRAC_PI_2)))
+            .unwrap();
+        builder
+            .push_translation(1, 0.0, Float3KeyF32::new([0.0, 1.0, 0.0]))
+            .unwrap();
+        builder
+            .push_translation(1, 1.0, Float3KeyF32::new([0.0, 1.0, 0.0]))
+            .unwrap();
+        builder
+            .push_rotation(1, 0.0, QuaternionKeyF32::from(Quat::IDENTITY))
+            .unwrap();
+        builder
+            .push_rotation(1, 1.0, QuaternionKeyF32::from(Quat::IDENTITY))
+            .unwrap();
+        let animation = Rc::new(builder.build().unwrap());
+
+        let mut job: SamplingJob = SamplingJob::default();
+        assert!(job.root_motion_delta(0.0, 1.0).unwrap_err().is_invalid_job());
+        job.set_animation(animation.clone());
+
+        let (translation, rotation) = job.root_motion_delta(0.0, 0.5).unwrap();
+        assert!(translation.abs_diff_eq(Vec3::new(0.0, 0.0, 1.0), 1e-3));
+        assert!(rotation.abs_diff_eq(Quat::from_rotation_y(consts::FRAC_PI_4), 1e-3));
+
+        let (translation, rotation) = job.root_motion_delta(0.5, 0.0).unwrap();
+        assert!(translation.abs_diff_eq(Vec3::new(0.0, 0.0, 3.0), 1e-3));
+        assert!(rotation.abs_diff_eq(Quat::from_rotation_y(consts::FRAC_PI_4), 1e-3));
+
+        // forward and looping deltas are both normalized
+        for (prev_ratio, ratio) in [(0.1, 0.37), (0.73, 0.21)] {
+            let (_, rotation) = job.root_motion_delta(prev_ratio, ratio).unwrap();
+            assert!(rotation.is_normalized());
+            assert!((rotation.length() - 1.0).abs() < 1e-6);
+        }
+
+        // accumulated over a full loop
+        let ratios = [0.2, 0.45, 0.8, 0.95, 0.1, 0.2];
+        let mut total_translation = Vec3::ZERO;
+        let mut total_rotation = Quat::IDENTITY;
+        for w in ratios.windows(2) {
+            let (translation, rotation) = job.root_motion_delta(w[0], w[1]).unwrap();
+            assert!(rotation.is_normalized(), "{} -> {}", w[0], w[1]);
+            total_translation += translation;
+            total_rotation = rotation * total_rotation;
+        }
+        assert!(total_translation.abs_diff_eq(Vec3::new(0.0, 0.0, 4.0), 1e-3));
+        assert!(total_rotation.abs_diff_eq(Quat::from_rotation_y(consts::FRAC_PI_2), 1e-3));
+
+        job.set_root_track(1);
+        let (translation, rotation) = job.root_motion_delta(0.9, 0.3).unwrap();
+        assert!(translation.abs_diff_eq(Vec3::ZERO, 1e-6));
+        assert!(rotation.abs_diff_eq(Quat::IDENTITY, 1e-6));
+
+        job.set_root_track(2);
+        assert!(job.root_motion_delta(0.0, 1.0).unwrap_err().is_invalid_index());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_run_with_velocities() {
+        // root moves at constant speed (2, 0, 1) and turns at 0.5 rad/s, 2nd track is static
+        let mut builder = AnimationBuilder::new(2.0, 2);
+        for track in 0..2 {
+            let (translation, rotation) = if track == 0 {
+                (Vec3::new(4.0, 0.0, 2.0), Quat::from_rotation_y(1.0))
+            } else {
+                (Vec3::ONE, QU)
+            };
+            builder.push_translation(track, 0.0, Float3KeyF32::from(V0)).unwrap();
+            builder
+                .push_translation(track, 1.0, Float3KeyF32::from(translation))
+                .unwrap();
+            builder.push_rotation(track, 0.0, QuaternionKeyF32::from(QU)).unwrap();
+            builder
+                .push_rotation(track, 1.0, QuaternionKeyF32::from(rotation))
+                .unwrap();
+            constant_track(&mut builder, track, None, None, Some(V1));
+        }
+        let animation = Rc::new(builder.build().unwrap());
+
+        let output = make_buf(vec![SoaTransform::IDENTITY; 1]);
+        let mut job: SamplingJob = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(output.clone());
+        job.set_rotation_interp(RotationInterp::Slerp);
+
+        let mut scratch = vec![SoaTransform::IDENTITY; 1];
+        let mut linear = vec![SoaVec3::splat(99.0); 1];
+        let mut angular = vec![SoaVec3::splat(99.0); 1];
+        assert!(job
+            .run_with_velocities(0.0, &mut scratch, &mut linear, &mut angular)
+            .unwrap_err()
+            .is_invalid_job());
+        assert!(job
+            .run_with_velocities(f32::NAN, &mut scratch, &mut linear, &mut angular)
+            .unwrap_err()
+            .is_invalid_job());
+        assert!(job
+            .run_with_velocities(0.1, &mut scratch, &mut [], &mut angular)
+            .unwrap_err()
+            .is_invalid_job());
+        assert!(job
+            .run_with_velocities(0.1, &mut [], &mut linear, &mut angular)
+            .unwrap_err()
+            .is_invalid_job());
+
+        // the last one is sampled backward from the end of the animation
+        for time in [0.0, 0.5, 1.3, 2.0] {
+            job.set_time(time).unwrap();
+            job.run_with_velocities(1.0 / 60.0, &mut scratch, &mut linear, &mut angular)
+                .unwrap();
+            assert!(
+                linear[0].vec3(0).abs_diff_eq(Vec3::new(2.0, 0.0, 1.0), 1e-3),
+                "time={} {}",
+                time,
+                linear[0].vec3(0)
+            );
+            assert!(
+                angular[0].vec3(0).abs_diff_eq(Vec3::new(0.0, 0.5, 0.0), 2e-3),
+                "time={} {}",
+                time,
+                angular[0].vec3(0)
+            );
+            assert!(linear[0].vec3(1).abs_diff_eq(Vec3::new(0.5, 0.5, 0.5), 1e-3));
+            assert!(angular[0].vec3(1).abs_diff_eq(V0, 1e-6));
+
+            // output is the pose at job's ratio, and the context is left as run() leaves it
+            let expected = Vec3::new(4.0, 0.0, 2.0) * job.ratio();
+            assert!(output.borrow()[0].translation.vec3(0).abs_diff_eq(expected, 1e-3));
+            assert_eq!(job.context().unwrap().ratio(), job.ratio());
+        }
+
+        // looping, sampled forward across the loop boundary
+        job.set_looping(true);
+        job.set_time(2.0 - 1.0 / 120.0).unwrap();
+        job.run_with_velocities(1.0 / 60.0, &mut scratch, &mut linear, &mut angular)
+            .unwrap();
+        let expected = (Vec3::splat(1.0 / 240.0) - Vec3::splat(1.0 - 1.0 / 240.0)) * 60.0;
+        assert!(linear[0].vec3(1).abs_diff_eq(expected, 1e-2), "{}", linear[0].vec3(1));
+
+        // tracks excluded by the mask have no velocity
+        job.set_looping(false);
+        job.set_track_mask(&[true, false]);
+        job.set_time(0.5).unwrap();
+        job.run_with_velocities(1.0 / 60.0, &mut scratch, &mut linear, &mut angular)
+            .unwrap();
+        assert!(linear[0].vec3(0).abs_diff_eq(Vec3::new(2.0, 0.0, 1.0), 1e-3));
+        assert_eq!((linear[0].vec3(1), angular[0].vec3(1)), (V0, V0));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_run_with_stats() {
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let num_tracks = animation.num_tracks();
+        let num_soa_tracks = animation.num_soa_tracks();
+        let mut job: SamplingJob = SamplingJob::default();
+        assert!(job.run_with_stats().unwrap_err().is_invalid_job());
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::new(num_tracks));
+        job.set_output(make_buf(vec![SoaTransform::default(); num_soa_tracks]));
+
+        // first sampling decompresses all keys, nothing is cached yet
+        job.set_ratio(0.2);
+        let stats = job.run_with_stats().unwrap();
+        assert_eq!(stats.soa_tracks, num_soa_tracks);
+        assert_eq!(stats.keys_decompressed, num_soa_tracks * 4 * 2 * 3);
+        assert_eq!((stats.cache_hits, stats.cache_misses), (0, num_soa_tracks * 3));
+
+        // sequential sampling reuses most cached keys, nothing is decompressed at the same ratio
+        let mut hits = Vec::new();
+        for ratio in [0.2, 0.201, 0.202] {
+            job.set_ratio(ratio);
+            let stats = job.run_with_stats().unwrap();
+            assert_eq!(stats.cache_hits + stats.cache_misses, num_soa_tracks * 3);
+            assert_eq!(stats.keys_decompressed, stats.cache_misses * 8);
+            hits.push(stats.cache_hits);
+        }
+        assert_eq!(hits[0], num_soa_tracks * 3);
+        assert!(hits.iter().all(|hits| *hits > stats.cache_hits));
+
+        // same output as run()
+        let output = job.output().unwrap().borrow().clone();
+        job.run().unwrap();
+        assert_eq!(*job.output().unwrap().borrow(), output);
+
+        let mut mask = vec![false; num_tracks];
+        mask[0] = true;
+        mask[9] = true;
+        job.set_track_mask(&mask);
+        job.set_ratio(0.9);
+        let stats = job.run_with_stats().unwrap();
+        assert_eq!(stats.soa_tracks, 2);
+        assert_eq!(stats.cache_hits + stats.cache_misses, 2 * 3);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_step_tracks() {
+        let mut builder = AnimationBuilder::new(1.0, 2);
+        for track in 0..2 {
+            builder
+                .push_translation(track, 0.0, Float3KeyF32::new([1.0, 0.0, 0.0]))
+                .unwrap();
+            builder
+                .push_translation(track, 0.5, Float3KeyF32::new([0.0, 2.0, 0.0]))
+                .unwrap();
+            builder
+                .push_translation(track, 1.0, Float3KeyF32::new([0.0, 0.0, 4.0]))
+                .unwrap();
+            builder
+                .push_rotation(track, 0.0, QuaternionKeyF32::from(Quat::IDENTITY))
+                .unwrap();
+            builder
+                .push_rotation(track, 1.0, QuaternionKeyF32::from(Quat::from_rotation_y(1.0)))
+                .unwrap();
+            builder.push_scale(track, 0.0, Float3KeyF32::new([1.0; 3])).unwrap();
+            builder.push_scale(track, 1.0, Float3KeyF32::new([3.0; 3])).unwrap();
+        }
+        let animation = Rc::new(builder.build().unwrap());
+
+        for interpolation in [Interpolation::Linear, Interpolation::Cubic] {
+            let mut job: SamplingJob = SamplingJob::default();
+            job.set_animation(animation.clone());
+            job.set_context(SamplingContext::from_animation(&animation));
+            job.set_output(make_buf(vec![SoaTransform::default(); 1]));
+            job.set_interpolation(interpolation);
+            job.set_step_tracks(&[true]);
+            assert_eq!(job.step_tracks(), &[true]);
+
+            // forward, then backward to check cached keys
+            for ratio in [0.0, 0.25, 0.499, 0.5, 0.75, 0.999, 1.0, 0.3] {
+                job.set_ratio(ratio);
+                job.run().unwrap();
+                let output = job.output().unwrap().borrow()[0];
+                let (translation, rotation, scale) = match ratio {
+                    r if r < 0.5 => (Vec3::X, Quat::IDENTITY, Vec3::ONE),
+                    r if r < 1.0 => (Vec3::Y * 2.0, Quat::IDENTITY, Vec3::ONE),
+                    _ => (Vec3::Z * 4.0, Quat::from_rotation_y(1.0), Vec3::splat(3.0)),
+                };
+                assert!(output.translation.vec3(0).abs_diff_eq(translation, 2e-3), "{}", ratio);
+                assert!(output.rotation.quat(0).abs_diff_eq(rotation, 2e-3), "{}", ratio);
+                assert!(output.scale.vec3(0).abs_diff_eq(scale, 2e-3), "{}", ratio);
+
+                // track 1 is still interpolated
+                if ratio > 0.0 && ratio < 0.5 {
+                    assert!(!output.translation.vec3(1).abs_diff_eq(Vec3::X, 1e-2), "{}", ratio);
+                    assert!(output.scale.vec3(1).x > 1.0);
+                }
+            }
+
+            job.clear_step_tracks();
+            assert!(job.step_tracks().is_empty());
+            job.set_ratio(0.25);
+            job.run().unwrap();
+            let output = job.output().unwrap().borrow()[0];
+            assert!(!output.translation.vec3(0).abs_diff_eq(Vec3::X, 1e-2));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_track_mask() {
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let sample_all = |ratio: f32, interpolation: Interpolation| {
+            let output = make_buf(vec![SoaTransform::IDENTITY; animation.num_soa_tracks()]);
+            let mut job: SamplingJob = SamplingJob::default();
+            job.set_animation(animation.clone());
+            job.set_context(SamplingContext::from_animation(&animation));
+            job.set_output(output.clone());
+            job.set_interpolation(interpolation);
+            job.set_ratio(ratio);
+            job.run().unwrap();
+            output.take()
+        };
+
+        for interpolation in [Interpolation::Linear, Interpolation::Cubic] {
+            let sentinel = SoaTransform::new(SoaVec3::splat(99.0), SoaQuat::splat(0.5), SoaVec3::splat(-99.0));
+            let output = make_buf(vec![sentinel; animation.num_soa_tracks()]);
+            let mut job: SamplingJob = SamplingJob::default();
+            job.set_animation(animation.clone());
+            job.set_context(SamplingContext::from_animation(&animation));
+            job.set_output(output.clone());
+            job.set_interpolation(interpolation);
+
+            let mut mask = vec![false; animation.num_tracks()];
+            mask[5] = true;
+            job.set_track_mask(&mask);
+            assert_eq!(job.track_mask(), &mask[..]);
+            job.set_ratio(0.3);
+            job.run().unwrap();
+
+            let expected = sample_all(0.3, interpolation);
+            for (idx, (out, exp)) in output.borrow().iter().zip(expected.iter()).enumerate() {
+                for lane in 0..4 {
+                    if idx * 4 + lane == 5 {
+                        assert_eq!(out.translation.vec3(lane), exp.translation.vec3(lane));
+                        assert_eq!(out.rotation.quat(lane), exp.rotation.quat(lane));
+                        assert_eq!(out.scale.vec3(lane), exp.scale.vec3(lane));
+                    } else {
+                        assert_eq!(out.translation.vec3(lane), sentinel.translation.vec3(lane));
+                        assert_eq!(out.rotation.quat(lane), sentinel.rotation.quat(lane));
+                        assert_eq!(out.scale.vec3(lane), sentinel.scale.vec3(lane));
+                    }
+                }
+            }
+
+            // skipped tracks are sampled once the mask is cleared, with the same context
+            job.clear_track_mask();
+            assert!(job.track_mask().is_empty());
+            job.set_ratio(0.6);
+            job.run().unwrap();
+            assert_eq!(*output.borrow(), sample_all(0.6, interpolation));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_set_time() {
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let mut job: SamplingJob = SamplingJob::default();
+        assert!(job.set_time(1.0).unwrap_err().is_invalid_job());
+
+        job.set_animation(animation.clone());
+        job.set_time(0.0).unwrap();
+        assert_eq!(job.ratio(), 0.0);
+        job.set_time(-1.0).unwrap();
+        assert_eq!(job.ratio(), 0.0);
+        job.set_time(animation.duration()).unwrap();
+        assert_eq!(job.ratio(), 1.0);
+        job.set_time(10.0).unwrap();
+        assert_eq!(job.ratio(), 1.0);
+        job.set_time(4.3).unwrap();
+        assert!((job.ratio() - 0.5).abs() < 1e-6);
+
+        job.set_looping(true);
+        assert!(job.looping());
+        job.set_time(8.6 + 4.3).unwrap();
+        assert!((job.ratio() - 0.5).abs() < 1e-6);
+        job.set_time(-2.15).unwrap();
+        assert!((job.ratio() - 0.75).abs() < 1e-6);
+
+        job.set_typed_time(Seconds::new(4.3 + 2.15)).unwrap();
+        assert!((job.ratio() - 0.75).abs() < 1e-6);
+        job.set_typed_ratio(Ratio::new(1.5));
+        assert_eq!(job.ratio(), 1.0);
+        job.set_typed_ratio(Seconds::new(4.3).to_ratio(animation.duration()));
+        assert!((job.ratio() - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_write_trs_interleaved() {
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let output = make_buf(vec![SoaTransform::IDENTITY; animation.num_soa_tracks()]);
+        let mut job: SamplingJob = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(output.clone());
+        job.set_ratio(0.4);
+        job.run().unwrap();
+
+        let mut out = vec![f32::NAN; animation.num_tracks() * TRS_INTERLEAVED_STRIDE + 1];
+        job.write_trs_interleaved(&mut out).unwrap();
+        let soa = output.borrow();
+        for track in 0..animation.num_tracks() {
+            let trs = &out[track * TRS_INTERLEAVED_STRIDE..(track + 1) * TRS_INTERLEAVED_STRIDE];
+            assert_eq!(Vec3::from_slice(&trs[0..3]), soa[track / 4].translation.vec3(track % 4));
+            assert_eq!(Quat::from_slice(&trs[3..7]), soa[track / 4].rotation.quat(track % 4));
+            assert_eq!(Vec3::from_slice(&trs[7..10]), soa[track / 4].scale.vec3(track % 4));
+        }
+        // trailing floats are left unchanged
+        assert!(out.last().unwrap().is_nan());
+
+        let mut out = vec![0.0; animation.num_tracks() * TRS_INTERLEAVED_STRIDE - 1];
+        assert!(job.write_trs_interleaved(&mut out).unwrap_err().is_invalid_job());
+        job.clear_output();
+        assert!(job.write_trs_interleaved(&mut []).unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_sample_into() {
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let output = make_buf(vec![SoaTransform::IDENTITY; animation.num_soa_tracks()]);
+        let mut job: SamplingJob = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_ratio(0.0);
+        assert!(job.run().unwrap_err().is_invalid_job()); // no output
+
+        let mut out = vec![(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE); animation.num_tracks()];
+        let mut expected_job: SamplingJob = SamplingJob::default();
+        expected_job.set_animation(animation.clone());
+        expected_job.set_context(SamplingContext::from_animation(&animation));
+        expected_job.set_output(output.clone());
+        let mut steps = vec![false; animation.num_tracks()];
+        steps[2] = true;
+        for interpolation in [Interpolation::Linear, Interpolation::Cubic] {
+            job.set_interpolation(interpolation);
+            job.set_step_tracks(&steps);
+            expected_job.set_interpolation(interpolation);
+            expected_job.set_step_tracks(&steps);
+            for ratio in [0.0, 0.3, 0.31, 0.9, 0.1, 1.0] {
+                job.set_ratio(ratio);
+                job.sample_into(&mut out).unwrap();
+                expected_job.set_ratio(ratio);
+                expected_job.run().unwrap();
+                let soa = output.borrow();
+                for (track, (translation, rotation, scale)) in out.iter().enumerate() {
+                    assert_eq!(*translation, soa[track / 4].translation.vec3(track % 4));
+                    assert_eq!(*rotation, soa[track / 4].rotation.quat(track % 4));
+                    assert_eq!(*scale, soa[track / 4].scale.vec3(track % 4));
+                }
+            }
+        }
+        job.set_interpolation(Interpolation::Linear);
+
+        // masked tracks keep their values
+        let mut mask = vec![true; animation.num_tracks()];
+        mask[1] = false;
+        job.set_track_mask(&mask);
+        out[1] = (Vec3::splat(7.0), Quat::IDENTITY, Vec3::splat(2.0));
+        job.set_ratio(0.5);
+        job.sample_into(&mut out).unwrap();
+        assert_eq!(out[1], (Vec3::splat(7.0), Quat::IDENTITY, Vec3::splat(2.0)));
+        assert_ne!(out[0].0, Vec3::splat(7.0));
+
+        let mut out = vec![(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE); animation.num_tracks() + 1];
+        assert!(job.sample_into(&mut out).unwrap_err().is_invalid_job());
+        job.clear_context();
+        assert!(job.sample_into(&mut out[1..]).unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_context_switch_animation() {
+        let clip_a = Animation::from_path("./resource/playback/animation.ozz").unwrap();
+        let clip_b = clip_a.slice(0.5, 0.9).unwrap();
+        let mut ctx = SamplingContext::new(clip_a.num_tracks());
+        for ratio in [0.0, 0.4, 0.8, 0.95] {
+            sample_pose_with(&clip_a, &mut ctx, ratio);
+        }
+        for ratio in [0.1, 0.6, 0.3] {
+            let expected = sample_pose_with(&clip_b, &mut SamplingContext::new(clip_b.num_tracks()), ratio);
+            assert_eq!(sample_pose_with(&clip_b, &mut ctx, ratio), expected);
+            assert_eq!(ctx.animation_id(), &clip_b as *const _ as u64);
+        }
+
+        ctx.clear();
+        assert_eq!(ctx.animation_id(), 0);
+        let expected = sample_pose_with(&clip_a, &mut SamplingContext::new(clip_a.num_tracks()), 0.7);
+        assert_eq!(sample_pose_with(&clip_a, &mut ctx, 0.7), expected);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_advance() {
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let duration = animation.duration();
+        let mut job: SamplingJob = SamplingJob::default();
+        assert!(job.advance(0.1).unwrap_err().is_invalid_job());
+        job.set_animation(animation.clone());
+        assert_eq!(job.speed(), 1.0);
+
+        // forward, clamped
+        let (ratio, crossed) = job.advance(duration * 0.25).unwrap();
+        assert!((ratio - 0.25).abs() < 1e-5 && !crossed);
+        let (ratio, crossed) = job.advance(duration).unwrap();
+        assert!(ratio == 1.0 && !crossed);
+
+        // forward, looping
+        job.set_looping(true);
+        job.set_ratio(0.25);
+        job.set_speed(2.0);
+        let (ratio, crossed) = job.advance(duration * 0.25).unwrap();
+        assert!((ratio - 0.75).abs() < 1e-5 && !crossed);
+        let (ratio, crossed) = job.advance(duration * 0.25).unwrap();
+        assert!((ratio - 0.25).abs() < 1e-5 && crossed);
+        assert_eq!(job.ratio(), ratio);
+
+        // reverse, crossing 0
+        job.set_speed(-1.0);
+        let (ratio, crossed) = job.advance(duration * 0.2).unwrap();
+        assert!((ratio - 0.05).abs() < 1e-5 && !crossed);
+        let (ratio, crossed) = job.advance(duration * 0.1).unwrap();
+        assert!((ratio - 0.95).abs() < 1e-5 && crossed);
+
+        // reverse, clamped
+        job.set_looping(false);
+        let (ratio, crossed) = job.advance(duration * 2.0).unwrap();
+        assert!(ratio == 0.0 && !crossed);
+
+        // paused
+        job.set_ratio(0.4);
+        job.set_speed(0.0);
+        for looping in [false, true] {
+            job.set_looping(looping);
+            let (ratio, crossed) = job.advance(duration * 0.3).unwrap();
+            assert!((ratio - 0.4).abs() < 1e-6 && !crossed);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_set_ratio_looped() {
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let mut job: SamplingJob = SamplingJob::default();
+        job.set_animation(animation.clone());
+        job.set_context(SamplingContext::from_animation(&animation));
+        job.set_output(make_buf(vec![SoaTransform::IDENTITY; animation.num_soa_tracks()]));
+
+        let sample = |job: &mut SamplingJob| {
+            job.run().unwrap();
+            job.output().unwrap().borrow().clone()
+        };
+
+        for (looped, ratio) in [
+            (1.0, 0.0),
+            (1.25, 0.25),
+            (-0.25, 0.75),
+            (3.5, 0.5),
+            (0.4, 0.4),
+            (-1e-9, 0.0),
+        ] {
+            job.set_ratio_looped(looped);
+            assert!((job.ratio() - ratio).abs() < 1e-6);
+            assert!(job.ratio() < 1.0);
+            let pose = sample(&mut job);
+            job.set_ratio(ratio);
+            assert!(SoaTransform::slice_abs_diff_eq(&pose, &sample(&mut job), 1e-5));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_sample_warped() {
+        let animation = Rc::new(Animation::from_path("./resource/playback/animation.ozz").unwrap());
+        let new_job = || {
+            let mut job: SamplingJob = SamplingJob::default();
+            job.set_animation(animation.clone());
+            job.set_context(SamplingContext::new(animation.num_tracks()));
+            job.set_output(Rc::new(RefCell::new(vec![
+                SoaTransform::default();
+                animation.num_soa_tracks()
+            ])));
+            job
+        };
+
+        // Holds until 0.25, then accelerates.
+        let warp = Track::from_raw(&[0.0, 0.0, 0.1, 1.0], &[0.0, 0.25, 0.5, 1.0], &[0]).unwrap();
+        let warped_ratio = |ratio: f32| match ratio {
+            r if r <= 0.25 => 0.0,
+            r if r <= 0.5 => (r - 0.25) / 0.25 * 0.1,
+            r => 0.1 + (r - 0.5) / 0.5 * 0.9,
+        };
+
+        let mut job = new_job();
+        let mut expected_job = new_job();
+        for ratio in [0.0, 0.1, 0.25, 0.3, 0.5, 0.6, 0.75, 0.9, 1.0] {
+            job.set_ratio(ratio);
+            job.sample_warped(&warp).unwrap();
+            assert_eq!(job.ratio(), ratio);
+
+            expected_job.set_ratio(warped_ratio(ratio));
+            expected_job.run().unwrap();
+
+            let output = job.output().unwrap().buf().unwrap();
+            let expected = expected_job.output().unwrap().buf().unwrap();
+            for (out, exp) in output.iter().zip(expected.iter()) {
+                for (a, b) in [
+                    (out.translation.x, exp.translation.x),
+                    (out.rotation.w, exp.rotation.w),
+                    (out.scale.z, exp.scale.z),
+                ] {
+                    assert!((a - b).abs().reduce_max() < 1e-5, "ratio {}: {:?} != {:?}", ratio, a, b);
+                }
+            }
+        }
+
+        let decreasing = Track::from_raw(&[0.0, 0.5, 0.4], &[0.0, 0.5, 1.0], &[0]).unwrap();
+        assert!(job.sample_warped(&decreasing).unwrap_err().is_invalid_job());
+        let out_of_range = Track::from_raw(&[0.0, 1.5], &[0.0, 1.0], &[0]).unwrap();
+        assert!(job.sample_warped(&out_of_range).unwrap_err().is_invalid_job());
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_cache() {
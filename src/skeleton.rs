@@ -2,17 +2,42 @@
 //! Skeleton data structure definition.
 //!
 
+use ::alloc::alloc::{self, Layout};
+#[cfg(feature = "std")]
+use ::alloc::string::String;
+use ::alloc::sync::Arc;
+use ::alloc::vec::Vec;
+#[cfg(feature = "std")]
 use bimap::BiHashMap;
-use std::alloc::{self, Layout};
+#[cfg(not(feature = "std"))]
+use core::cell::OnceCell as OnceLock;
+use core::{mem, slice};
+use glam::Mat4;
+#[cfg(feature = "std")]
 use std::io::Read;
-use std::{mem, slice};
+#[cfg(feature = "std")]
+use std::sync::OnceLock;
 
+#[cfg(feature = "std")]
 use crate::archive::Archive;
-use crate::base::{DeterministicState, OzzError, OzzIndex};
+#[cfg(feature = "std")]
+use crate::base::DeterministicState;
+#[cfg(feature = "std")]
+use crate::base::OzzError;
+use crate::base::{OzzIndex, SKELETON_NO_PARENT};
 use crate::math::SoaTransform;
+#[cfg(feature = "std")]
+use crate::name_interner::NameInterner;
 
 /// Rexported `BiHashMap` in bimap crate.
-pub type JointHashMap = BiHashMap<String, i16, DeterministicState, DeterministicState>;
+///
+/// Names are `Arc<str>`, so that skeletons can share them through a `NameInterner`.
+#[cfg(feature = "std")]
+pub type JointHashMap = BiHashMap<Arc<str>, i16, DeterministicState, DeterministicState>;
+
+/// Rexported `BiBTreeMap` in bimap crate, `BiHashMap` needs std.
+#[cfg(not(feature = "std"))]
+pub type JointHashMap = bimap::BiBTreeMap<Arc<str>, i16>;
 
 ///
 /// This runtime skeleton data structure provides a const-only access to joint
@@ -33,6 +58,8 @@ pub struct Skeleton {
     joint_rest_poses: *mut SoaTransform,
     joint_names: JointHashMap,
     joint_parents: *mut i16,
+    rest_model_matrices: OnceLock<Vec<Mat4>>,
+    inverse_bind_matrices: OnceLock<Vec<Mat4>>,
 }
 
 impl Drop for Skeleton {
@@ -42,8 +69,8 @@ impl Drop for Skeleton {
                 let layout = Layout::from_size_align_unchecked(self.size, mem::align_of::<SoaTransform>());
                 alloc::dealloc(self.joint_rest_poses as *mut u8, layout);
             }
-            self.joint_rest_poses = std::ptr::null_mut();
-            self.joint_parents = std::ptr::null_mut();
+            self.joint_rest_poses = core::ptr::null_mut();
+            self.joint_parents = core::ptr::null_mut();
         }
     }
 }
@@ -79,6 +106,7 @@ impl Skeleton {
     }
 
     /// Reads a `SkeletonMeta` from a reader.
+    #[cfg(feature = "std")]
     pub fn read_meta(archive: &mut Archive<impl Read>, with_joints: bool) -> Result<SkeletonMeta, OzzError> {
         if archive.tag() != Self::tag() {
             return Err(OzzError::InvalidTag);
@@ -104,7 +132,7 @@ impl Skeleton {
             DeterministicState::new(),
         );
         for idx in 0..num_joints {
-            joint_names.insert(archive.read::<String>()?, idx as i16);
+            joint_names.insert(archive.read::<String>()?.into(), idx as i16);
         }
 
         let joint_parents: Vec<i16> = archive.read_vec(num_joints as usize)?;
@@ -118,13 +146,16 @@ impl Skeleton {
     }
 
     /// Reads a `Skeleton` from a reader.
+    #[cfg(feature = "std")]
     pub fn from_archive(archive: &mut Archive<impl Read>) -> Result<Skeleton, OzzError> {
         let meta = Skeleton::read_meta(archive, false)?;
         let mut skeleton = Skeleton::new(meta);
 
         let _char_count: u32 = archive.read()?;
         for idx in 0..skeleton.num_joints() {
-            skeleton.joint_names.insert(archive.read::<String>()?, idx as i16);
+            skeleton
+                .joint_names
+                .insert(archive.read::<String>()?.into(), idx as i16);
         }
 
         archive.read_slice(skeleton.joint_parents_mut())?;
@@ -133,7 +164,7 @@ impl Skeleton {
     }
 
     /// Reads a `Skeleton` from a file.
-    #[cfg(not(feature = "wasm"))]
+    #[cfg(all(feature = "std", not(feature = "wasm")))]
     pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Skeleton, OzzError> {
         let mut archive = Archive::from_path(path)?;
         Skeleton::from_archive(&mut archive)
@@ -150,7 +181,7 @@ impl Skeleton {
         let mut skeleton = Skeleton::new(SkeletonMeta {
             version: Self::version(),
             num_joints: raw.joint_parents.len() as u32,
-            joint_names: BiHashMap::default(),
+            joint_names: JointHashMap::default(),
             joint_parents: Vec::new(),
         });
         skeleton.joint_rest_poses_mut().copy_from_slice(&raw.joint_rest_poses);
@@ -172,13 +203,18 @@ impl Skeleton {
             size: 0,
             num_joints: meta.num_joints,
             num_soa_joints: meta.num_joints.div_ceil(4),
-            joint_rest_poses: std::ptr::null_mut(),
-            joint_parents: std::ptr::null_mut(),
+            joint_rest_poses: core::ptr::null_mut(),
+            joint_parents: core::ptr::null_mut(),
+            #[cfg(feature = "std")]
             joint_names: BiHashMap::with_capacity_and_hashers(
                 meta.num_joints as usize,
                 DeterministicState::new(),
                 DeterministicState::new(),
             ),
+            #[cfg(not(feature = "std"))]
+            joint_names: JointHashMap::new(),
+            rest_model_matrices: OnceLock::new(),
+            inverse_bind_matrices: OnceLock::new(),
         };
 
         const ALIGN: usize = mem::align_of::<SoaTransform>();
@@ -246,7 +282,24 @@ impl Skeleton {
     /// Gets joint's name by index.
     #[inline]
     pub fn name_by_joint(&self, index: i16) -> Option<&str> {
-        self.joint_names.get_by_right(&index).map(|s| s.as_str())
+        self.joint_names.get_by_right(&index).map(|s| s.as_ref())
+    }
+
+    /// Replaces joint names with the ones interned in `interner`.
+    ///
+    /// Skeletons interned in the same `NameInterner` share the storage of their equal names, instead of each
+    /// owning a copy. Useful for banks of skeletons repeating long joint names.
+    #[cfg(feature = "std")]
+    pub fn intern_names(&mut self, interner: &NameInterner) {
+        let mut joint_names = JointHashMap::with_capacity_and_hashers(
+            self.num_joints(),
+            DeterministicState::new(),
+            DeterministicState::new(),
+        );
+        for (name, joint) in self.joint_names.iter() {
+            joint_names.insert(interner.intern(name), *joint);
+        }
+        self.joint_names = joint_names;
     }
 
     /// Gets joint's parent indices range.
@@ -292,6 +345,38 @@ impl Skeleton {
         }
     }
 
+    /// Gets model-space matrices of the rest pose, ordered like joints.
+    ///
+    /// Computed by a local-to-model pass over the rest pose on first call, then cached.
+    pub fn rest_model_matrices(&self) -> &[Mat4] {
+        self.rest_model_matrices.get_or_init(|| {
+            let rest_poses = self.joint_rest_poses();
+            let mut models: Vec<Mat4> = Vec::with_capacity(self.num_joints());
+            for (joint, &parent) in self.joint_parents().iter().enumerate() {
+                let (soa, lane) = (&rest_poses[joint / 4], joint % 4);
+                let local = Mat4::from_scale_rotation_translation(
+                    soa.scale.vec3(lane),
+                    soa.rotation.quat(lane),
+                    soa.translation.vec3(lane),
+                );
+                let model = match parent as i32 {
+                    SKELETON_NO_PARENT => local,
+                    parent => models[parent as usize] * local,
+                };
+                models.push(model);
+            }
+            models
+        })
+    }
+
+    /// Gets inverse bind matrices, the inverse of rest pose model-space matrices, ordered like joints.
+    ///
+    /// Usually used by skinning, see `rest_model_matrices()`. Computed on first call, then cached.
+    pub fn inverse_bind_matrices(&self) -> &[Mat4] {
+        self.inverse_bind_matrices
+            .get_or_init(|| self.rest_model_matrices().iter().map(Mat4::inverse).collect())
+    }
+
     /// Iterates through the joint hierarchy in reverse depth-first order.
     ///
     /// * `f` - The function to call for each joint. The function takes arguments `(joint: i16, parent: i16)`.
@@ -346,10 +431,15 @@ const _: () = {
     impl<S: Serializer + ScratchSpace + ?Sized> Serialize<S> for Skeleton {
         fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
             serializer.align_for::<SoaTransform>()?;
+            let joint_names: Vec<(String, i16)> = self
+                .joint_names()
+                .iter()
+                .map(|(key, value)| (key.to_string(), *value))
+                .collect();
             Ok(SkeletonResolver {
                 joint_rest_poses: ArchivedVec::serialize_from_slice(self.joint_rest_poses(), serializer)?,
                 joint_names: ArchivedVec::serialize_from_iter(
-                    self.joint_names().iter().map(|(key, value)| Entry { key, value }),
+                    joint_names.iter().map(|(key, value)| Entry { key, value }),
                     serializer,
                 )?,
                 joint_parents: ArchivedVec::serialize_from_slice(self.joint_parents(), serializer)?,
@@ -377,7 +467,7 @@ const _: () = {
                 DeterministicState::new(),
             );
             for entry in archived.joint_names.iter() {
-                skeleton.joint_names.insert(entry.key.to_string(), entry.value);
+                skeleton.joint_names.insert(entry.key.as_str().into(), entry.value);
             }
 
             skeleton
@@ -414,6 +504,7 @@ mod tests {
 
     use super::*;
     use crate::math::{SoaQuat, SoaVec3};
+    use glam::{Quat, Vec3};
 
     #[allow(clippy::excessive_precision)]
     #[test]
@@ -484,6 +575,57 @@ mod tests {
         assert_eq!(skeleton.joint_by_name("Bip01 R Toe0Nub"), Some(66));
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_rest_model_matrices() {
+        // root, 2 children, and a grand child, with rotations and non-uniform scales
+        let mut rest_poses = vec![SoaTransform::IDENTITY; 2];
+        let locals = [
+            (Vec3::new(1.0, 2.0, 3.0), Quat::from_rotation_y(0.5), Vec3::ONE),
+            (
+                Vec3::new(0.0, 1.0, 0.0),
+                Quat::from_rotation_x(-0.3),
+                Vec3::new(1.0, 2.0, 1.0),
+            ),
+            (Vec3::new(0.5, 0.0, 0.0), Quat::from_rotation_z(1.2), Vec3::splat(0.5)),
+            (Vec3::new(-1.0, 0.0, 0.5), Quat::IDENTITY, Vec3::ONE),
+            (Vec3::new(0.0, 0.0, 2.0), Quat::from_rotation_x(0.7), Vec3::ONE),
+        ];
+        for (joint, (translation, rotation, scale)) in locals.iter().enumerate() {
+            rest_poses[joint / 4].translation.set_vec3(joint % 4, *translation);
+            rest_poses[joint / 4].rotation.set_quat(joint % 4, *rotation);
+            rest_poses[joint / 4].scale.set_vec3(joint % 4, *scale);
+        }
+        let mut joint_names = JointHashMap::with_hashers(DeterministicState::new(), DeterministicState::new());
+        for joint in 0..5 {
+            joint_names.insert(format!("j{}", joint).into(), joint as i16);
+        }
+        let skeleton = Skeleton::from_raw(&SkeletonRaw {
+            joint_rest_poses: rest_poses,
+            joint_names,
+            joint_parents: vec![-1, 0, 1, 0, 3],
+        });
+
+        let models = skeleton.rest_model_matrices();
+        assert_eq!(models.len(), 5);
+        let local = |joint: usize| {
+            let (translation, rotation, scale) = locals[joint];
+            Mat4::from_scale_rotation_translation(scale, rotation, translation)
+        };
+        assert!(models[0].abs_diff_eq(local(0), 1e-6));
+        assert!(models[2].abs_diff_eq(local(0) * local(1) * local(2), 1e-5));
+        assert!(models[4].abs_diff_eq(local(0) * local(3) * local(4), 1e-5));
+
+        let inverse_binds = skeleton.inverse_bind_matrices();
+        for (model, inverse_bind) in models.iter().zip(inverse_binds.iter()) {
+            assert!((*model * *inverse_bind).abs_diff_eq(Mat4::IDENTITY, 1e-5));
+        }
+
+        // cached
+        assert!(std::ptr::eq(skeleton.rest_model_matrices(), models));
+        assert!(std::ptr::eq(skeleton.inverse_bind_matrices(), inverse_binds));
+    }
+
     #[cfg(feature = "rkyv")]
     #[test]
     #[wasm_bindgen_test]
@@ -2,13 +2,16 @@
 //! Skinning Job.
 //!
 
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::Debug;
 use glam::{Mat4, Vec3, Vec4};
-use std::cell::RefCell;
-use std::fmt::Debug;
-use std::rc::Rc;
+#[cfg(feature = "std")]
 use std::sync::{Arc, RwLock};
 
 use crate::base::{OzzBuf, OzzError, OzzMutBuf};
+use crate::skinning_matrices_job::AFFINE_MATRIX_STRIDE;
 
 /// Skinning job.
 ///
@@ -52,6 +55,7 @@ pub type SkinningJobRc = SkinningJob<
     Rc<RefCell<Vec<Vec3>>>,
     Rc<RefCell<Vec<Vec3>>>,
 >;
+#[cfg(feature = "std")]
 pub type SkinningJobArc = SkinningJob<
     Arc<RwLock<Vec<Mat4>>>,
     Arc<RwLock<Vec<u16>>>,
@@ -601,6 +605,109 @@ where
     skinning_n!(skinning_n_pnt_it, IT, PNT);
 }
 
+/// Skinning matrices used by `skin_vertices()`, one for each joint.
+#[derive(Debug, Clone, Copy)]
+pub enum SkinningMatrices<'t> {
+    /// 4x4 matrices, like outputted by `SkinningMatricesJob::run()`.
+    Mat4(&'t [Mat4]),
+    /// 4x3 affine matrices, `AFFINE_MATRIX_STRIDE` floats each, like outputted by
+    /// `SkinningMatricesJob::run_affine()`.
+    Affine(&'t [f32]),
+}
+
+impl SkinningMatrices<'_> {
+    fn len(&self) -> usize {
+        match self {
+            SkinningMatrices::Mat4(matrices) => matrices.len(),
+            SkinningMatrices::Affine(floats) => floats.len() / AFFINE_MATRIX_STRIDE,
+        }
+    }
+
+    fn get(&self, joint: usize) -> Mat4 {
+        match self {
+            SkinningMatrices::Mat4(matrices) => matrices[joint],
+            SkinningMatrices::Affine(floats) => {
+                let rows = &floats[joint * AFFINE_MATRIX_STRIDE..(joint + 1) * AFFINE_MATRIX_STRIDE];
+                Mat4::from_cols(
+                    Vec4::from_slice(&rows[0..4]),
+                    Vec4::from_slice(&rows[4..8]),
+                    Vec4::from_slice(&rows[8..12]),
+                    Vec4::W,
+                )
+                .transpose()
+            }
+        }
+    }
+}
+
+///
+/// Skins vertices on the CPU, a simpler alternative to `SkinningJob` for tools, previews and tests.
+///
+/// Each vertex is influenced by up to 4 joints, `joint_indices` and `joint_weights` are indexed like
+/// `positions`. Unused influences must have a zero weight, their joint index is ignored (so it can be any padding
+/// value, like `u16::MAX`). Weights are used as is (they should sum to 1).
+/// Deformed positions are written to `out_positions`. If `normals` is provided as
+/// `(in_normals, out_normals, it_matrices)`, normals are transformed by the inverse-transpose of the matrices, and
+/// aren't normalized. `it_matrices` is a scratch buffer, at least as long as `matrices`, receiving the
+/// inverse-transpose matrices. It can be reused across calls to avoid allocations.
+///
+/// Returns `OzzError::InvalidJob` if a buffer is too short, or `OzzError::InvalidIndex` if the joint index of a
+/// weighted influence is out of `matrices` range.
+///
+pub fn skin_vertices(
+    matrices: SkinningMatrices<'_>,
+    positions: &[Vec3],
+    joint_indices: &[[u16; 4]],
+    joint_weights: &[[f32; 4]],
+    out_positions: &mut [Vec3],
+    normals: Option<(&[Vec3], &mut [Vec3], &mut [Mat4])>,
+) -> Result<(), OzzError> {
+    let count = positions.len();
+    let num_joints = matrices.len();
+    let mut ok = joint_indices.len() >= count && joint_weights.len() >= count && out_positions.len() >= count;
+    if let Some((in_normals, out_normals, it_matrices)) = &normals {
+        ok &= in_normals.len() >= count && out_normals.len() >= count && it_matrices.len() >= num_joints;
+    }
+    if !ok {
+        return Err(OzzError::InvalidJob);
+    }
+    let out_of_range = joint_indices[..count]
+        .iter()
+        .zip(&joint_weights[..count])
+        .flat_map(|(indices, weights)| indices.iter().zip(weights))
+        .any(|(&joint, &weight)| weight != 0.0 && joint as usize >= num_joints);
+    if out_of_range {
+        return Err(OzzError::InvalidIndex);
+    }
+
+    let blend = |vertex: usize, matrix: &dyn Fn(usize) -> Mat4| {
+        let (indices, weights) = (&joint_indices[vertex], &joint_weights[vertex]);
+        let mut blended = Mat4::ZERO;
+        for influence in 0..4 {
+            if weights[influence] != 0.0 {
+                blended += matrix(indices[influence] as usize) * weights[influence];
+            }
+        }
+        blended
+    };
+
+    let matrix = |joint: usize| matrices.get(joint);
+    for (vertex, (position, out)) in positions.iter().zip(out_positions.iter_mut()).enumerate() {
+        *out = blend(vertex, &matrix).transform_point3(*position);
+    }
+
+    if let Some((in_normals, out_normals, it_matrices)) = normals {
+        for (joint, it_matrix) in it_matrices[..num_joints].iter_mut().enumerate() {
+            *it_matrix = matrices.get(joint).inverse().transpose();
+        }
+        let it_matrix = |joint: usize| it_matrices[joint];
+        for (vertex, (normal, out)) in in_normals[..count].iter().zip(out_normals.iter_mut()).enumerate() {
+            *out = blend(vertex, &it_matrix).transform_vector3(*normal);
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod skinning_tests {
     use wasm_bindgen_test::*;
@@ -1126,4 +1233,105 @@ mod skinning_tests {
             assert!(out_tangents.borrow()[1].abs_diff_eq(Vec3::new(0.02, 0.01, 0.03), 1e-6));
         }
     }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_skin_vertices() {
+        use crate::skinning_matrices_job::{SkinningMatricesJob, SkinningMatricesJobRef};
+        use glam::Quat;
+
+        let models = [
+            Mat4::from_translation(Vec3::new(5.0, 0.0, 0.0)),
+            Mat4::from_scale_rotation_translation(Vec3::new(1.0, 2.0, 1.0), Quat::from_rotation_z(0.8), Vec3::Y),
+        ];
+        let inverse_binds = [Mat4::IDENTITY, Mat4::from_translation(Vec3::new(0.0, -1.0, 0.0))];
+        let mut skinning = [Mat4::ZERO; 2];
+        let mut affine = [0.0; AFFINE_MATRIX_STRIDE * 2];
+        let mut job: SkinningMatricesJobRef = SkinningMatricesJob::default();
+        job.set_model_matrices(&models);
+        job.set_inverse_bind_matrices(&inverse_binds);
+        job.run_affine(&mut affine).unwrap();
+        job.set_output(&mut skinning);
+        job.run().unwrap();
+
+        // rigidly bound to joint 1, then blended half and half
+        let positions = [Vec3::new(0.5, 1.5, -0.2), Vec3::new(0.5, 1.5, -0.2)];
+        let joint_indices = [[1, 0, 0, 0], [0, 1, 0, 0]];
+        let joint_weights = [[1.0, 0.0, 0.0, 0.0], [0.5, 0.5, 0.0, 0.0]];
+        let normals = [Vec3::X, Vec3::X];
+        let mut it_matrices = [Mat4::ZERO; 2];
+        for matrices in [SkinningMatrices::Mat4(&skinning), SkinningMatrices::Affine(&affine)] {
+            let mut out_positions = [Vec3::ZERO; 2];
+            let mut out_normals = [Vec3::ZERO; 2];
+            skin_vertices(
+                matrices,
+                &positions,
+                &joint_indices,
+                &joint_weights,
+                &mut out_positions,
+                Some((&normals, &mut out_normals, &mut it_matrices)),
+            )
+            .unwrap();
+
+            let bone = skinning[1];
+            assert_eq!(out_positions[0], bone.transform_point3(positions[0]));
+            let expected = (positions[0] + Vec3::new(5.0, 0.0, 0.0) + bone.transform_point3(positions[0])) * 0.5;
+            assert!(out_positions[1].abs_diff_eq(expected, 1e-5));
+
+            // normal stays perpendicular to the deformed surface
+            let tangent = bone.transform_vector3(Vec3::Y);
+            assert!(out_normals[0].dot(tangent).abs() < 1e-5);
+            let expected = bone.inverse().transpose().transform_vector3(Vec3::X);
+            assert!(out_normals[0].abs_diff_eq(expected, 1e-5));
+        }
+
+        let mut out_positions = [Vec3::ZERO; 1];
+        let matrices = SkinningMatrices::Mat4(&skinning);
+        let err = skin_vertices(
+            matrices,
+            &positions,
+            &joint_indices,
+            &joint_weights,
+            &mut out_positions,
+            None,
+        );
+        assert!(err.unwrap_err().is_invalid_job());
+        let mut out_positions = [Vec3::ZERO; 2];
+        let err = skin_vertices(
+            matrices,
+            &positions,
+            &[[2, 0, 0, 0]; 2],
+            &joint_weights,
+            &mut out_positions,
+            None,
+        );
+        assert!(err.unwrap_err().is_invalid_index());
+
+        // padding influences with a zero weight, whatever their joint index
+        let padded_indices = [[1, u16::MAX, u16::MAX, u16::MAX], [u16::MAX, 0, 1, u16::MAX]];
+        let padded_weights = [[1.0, 0.0, 0.0, 0.0], [0.0, 0.5, 0.5, 0.0]];
+        let mut out_normals = [Vec3::ZERO; 2];
+        skin_vertices(
+            matrices,
+            &positions,
+            &padded_indices,
+            &padded_weights,
+            &mut out_positions,
+            Some((&normals, &mut out_normals, &mut it_matrices)),
+        )
+        .unwrap();
+        assert_eq!(out_positions[0], skinning[1].transform_point3(positions[0]));
+        let expected = (positions[0] + Vec3::new(5.0, 0.0, 0.0) + skinning[1].transform_point3(positions[0])) * 0.5;
+        assert!(out_positions[1].abs_diff_eq(expected, 1e-5));
+
+        let err = skin_vertices(
+            matrices,
+            &positions,
+            &joint_indices,
+            &joint_weights,
+            &mut out_positions,
+            Some((&normals, &mut out_normals, &mut it_matrices[..1])),
+        );
+        assert!(err.unwrap_err().is_invalid_job());
+    }
 }
@@ -0,0 +1,286 @@
+//!
+//! Skinning Matrices Job.
+//!
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use glam::Mat4;
+#[cfg(feature = "std")]
+use std::sync::{Arc, RwLock};
+
+use crate::base::{OzzBuf, OzzError, OzzMutBuf};
+
+/// Number of floats per joint written by `SkinningMatricesJob::run_affine()`.
+pub const AFFINE_MATRIX_STRIDE: usize = 12;
+
+///
+/// Computes skinning matrices, as `model_matrices[i] * inverse_bind_matrices[i]` for each joint.
+///
+/// Skinning matrices transform mesh vertices from bind pose model-space to animated model-space. Model
+/// matrices are usually outputted by `LocalToModelJob`, inverse bind matrices by
+/// `Skeleton::inverse_bind_matrices()` (or provided by the mesh). Both inputs must have the same length.
+///
+/// `run()` writes `Mat4` to the output, `run_affine()` writes 4x3 affine matrices to a flat `f32` buffer
+/// instead, which saves GPU upload bandwidth.
+///
+#[derive(Debug)]
+pub struct SkinningMatricesJob<I = Rc<RefCell<Vec<Mat4>>>, O = Rc<RefCell<Vec<Mat4>>>>
+where
+    I: OzzBuf<Mat4>,
+    O: OzzMutBuf<Mat4>,
+{
+    model_matrices: Option<I>,
+    inverse_bind_matrices: Option<I>,
+    output: Option<O>,
+}
+
+pub type SkinningMatricesJobRef<'t> = SkinningMatricesJob<&'t [Mat4], &'t mut [Mat4]>;
+pub type SkinningMatricesJobRc = SkinningMatricesJob<Rc<RefCell<Vec<Mat4>>>, Rc<RefCell<Vec<Mat4>>>>;
+#[cfg(feature = "std")]
+pub type SkinningMatricesJobArc = SkinningMatricesJob<Arc<RwLock<Vec<Mat4>>>, Arc<RwLock<Vec<Mat4>>>>;
+
+impl<I, O> Default for SkinningMatricesJob<I, O>
+where
+    I: OzzBuf<Mat4>,
+    O: OzzMutBuf<Mat4>,
+{
+    fn default() -> SkinningMatricesJob<I, O> {
+        SkinningMatricesJob {
+            model_matrices: None,
+            inverse_bind_matrices: None,
+            output: None,
+        }
+    }
+}
+
+impl<I, O> SkinningMatricesJob<I, O>
+where
+    I: OzzBuf<Mat4>,
+    O: OzzMutBuf<Mat4>,
+{
+    /// Gets model matrices of `SkinningMatricesJob`.
+    #[inline]
+    pub fn model_matrices(&self) -> Option<&I> {
+        self.model_matrices.as_ref()
+    }
+
+    /// Sets model matrices of `SkinningMatricesJob`.
+    ///
+    /// The animated model-space matrices, one for each joint.
+    #[inline]
+    pub fn set_model_matrices(&mut self, model_matrices: I) {
+        self.model_matrices = Some(model_matrices);
+    }
+
+    /// Clears model matrices of `SkinningMatricesJob`.
+    #[inline]
+    pub fn clear_model_matrices(&mut self) {
+        self.model_matrices = None;
+    }
+
+    /// Gets inverse bind matrices of `SkinningMatricesJob`.
+    #[inline]
+    pub fn inverse_bind_matrices(&self) -> Option<&I> {
+        self.inverse_bind_matrices.as_ref()
+    }
+
+    /// Sets inverse bind matrices of `SkinningMatricesJob`.
+    ///
+    /// The inverse of bind pose model-space matrices, one for each joint.
+    #[inline]
+    pub fn set_inverse_bind_matrices(&mut self, inverse_bind_matrices: I) {
+        self.inverse_bind_matrices = Some(inverse_bind_matrices);
+    }
+
+    /// Clears inverse bind matrices of `SkinningMatricesJob`.
+    #[inline]
+    pub fn clear_inverse_bind_matrices(&mut self) {
+        self.inverse_bind_matrices = None;
+    }
+
+    /// Gets output of `SkinningMatricesJob`.
+    #[inline]
+    pub fn output(&self) -> Option<&O> {
+        self.output.as_ref()
+    }
+
+    /// Sets output of `SkinningMatricesJob`.
+    ///
+    /// The output range to be filled with skinning matrices, one for each joint. Not needed by `run_affine()`.
+    #[inline]
+    pub fn set_output(&mut self, output: O) {
+        self.output = Some(output);
+    }
+
+    /// Clears output of `SkinningMatricesJob`.
+    #[inline]
+    pub fn clear_output(&mut self) {
+        self.output = None;
+    }
+
+    /// Validates `SkinningMatricesJob` parameters.
+    pub fn validate(&self) -> bool {
+        (|| {
+            let model_matrices = self.model_matrices.as_ref()?.buf().ok()?;
+            let inverse_bind_matrices = self.inverse_bind_matrices.as_ref()?.buf().ok()?;
+            let output = self.output.as_ref()?.buf().ok()?;
+
+            let mut ok = model_matrices.len() == inverse_bind_matrices.len();
+            ok &= output.len() >= model_matrices.len();
+            Some(ok)
+        })()
+        .unwrap_or(false)
+    }
+
+    /// Runs skinning matrices job's task.
+    /// The validate job before any operation is performed.
+    pub fn run(&mut self) -> Result<(), OzzError> {
+        let model_matrices = self.model_matrices.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let inverse_bind_matrices = self.inverse_bind_matrices.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+
+        let mut ok = model_matrices.len() == inverse_bind_matrices.len();
+        ok &= output.len() >= model_matrices.len();
+        if !ok {
+            return Err(OzzError::InvalidJob);
+        }
+
+        for ((out, model), inverse_bind) in output
+            .iter_mut()
+            .zip(model_matrices.iter())
+            .zip(inverse_bind_matrices.iter())
+        {
+            *out = *model * *inverse_bind;
+        }
+        Ok(())
+    }
+
+    /// Runs skinning matrices job's task, writing 4x3 affine matrices to `output`.
+    ///
+    /// Each joint takes `AFFINE_MATRIX_STRIDE` floats: the first 3 rows of the skinning matrix, row after row,
+    /// as the last row of an affine matrix is always `0, 0, 0, 1`. This matches the common `mat3x4` row-major
+    /// GPU layout. `output` must be at least `AFFINE_MATRIX_STRIDE` times the number of joints long, the job
+    /// output isn't used.
+    pub fn run_affine(&self, output: &mut [f32]) -> Result<(), OzzError> {
+        let model_matrices = self.model_matrices.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let inverse_bind_matrices = self.inverse_bind_matrices.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+
+        let mut ok = model_matrices.len() == inverse_bind_matrices.len();
+        ok &= output.len() >= model_matrices.len() * AFFINE_MATRIX_STRIDE;
+        if !ok {
+            return Err(OzzError::InvalidJob);
+        }
+
+        for ((out, model), inverse_bind) in output
+            .chunks_exact_mut(AFFINE_MATRIX_STRIDE)
+            .zip(model_matrices.iter())
+            .zip(inverse_bind_matrices.iter())
+        {
+            let skinning = (*model * *inverse_bind).transpose();
+            out[0..4].copy_from_slice(&skinning.x_axis.to_array());
+            out[4..8].copy_from_slice(&skinning.y_axis.to_array());
+            out[8..12].copy_from_slice(&skinning.z_axis.to_array());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod skinning_matrices_tests {
+    use glam::{Quat, Vec3, Vec4};
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::local_to_model_job::LocalToModelJob;
+    use crate::skeleton::Skeleton;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validity() {
+        let buf = |len| Rc::new(RefCell::new(vec![Mat4::IDENTITY; len]));
+
+        let mut job: SkinningMatricesJob = SkinningMatricesJob::default();
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+        assert!(job.run_affine(&mut [0.0; 48]).unwrap_err().is_invalid_job());
+
+        job.set_model_matrices(buf(4));
+        job.set_inverse_bind_matrices(buf(3));
+        job.set_output(buf(4));
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+        assert!(job.run_affine(&mut [0.0; 48]).unwrap_err().is_invalid_job());
+
+        job.set_inverse_bind_matrices(buf(4));
+        assert!(job.validate());
+        assert!(job.run().is_ok());
+        assert!(job.run_affine(&mut [0.0; 48]).is_ok());
+        assert!(job.run_affine(&mut [0.0; 47]).unwrap_err().is_invalid_job());
+
+        job.set_output(buf(3));
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_rest_pose() {
+        let skeleton = Rc::new(Skeleton::from_path("./resource/playback/skeleton.ozz").unwrap());
+        let model_matrices = Rc::new(RefCell::new(vec![Mat4::ZERO; skeleton.num_joints()]));
+        let mut l2m_job: LocalToModelJob = LocalToModelJob::default();
+        l2m_job.set_skeleton(skeleton.clone());
+        l2m_job.set_input(Rc::new(RefCell::new(skeleton.joint_rest_poses().to_vec())));
+        l2m_job.set_output(model_matrices.clone());
+        l2m_job.run().unwrap();
+
+        let model_matrices = model_matrices.borrow();
+        let mut output = vec![Mat4::ZERO; skeleton.num_joints()];
+        let mut job: SkinningMatricesJobRef = SkinningMatricesJob::default();
+        job.set_model_matrices(&model_matrices);
+        job.set_inverse_bind_matrices(skeleton.inverse_bind_matrices());
+        job.set_output(&mut output);
+        job.run().unwrap();
+        for skinning in output.iter() {
+            assert!(skinning.abs_diff_eq(Mat4::IDENTITY, 1e-4), "{}", skinning);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_run_affine() {
+        let models = [
+            Mat4::from_scale_rotation_translation(Vec3::ONE, Quat::from_rotation_y(0.4), Vec3::new(1.0, 2.0, 3.0)),
+            Mat4::from_scale_rotation_translation(Vec3::splat(2.0), Quat::from_rotation_x(-1.1), Vec3::Z),
+        ];
+        let inverse_binds = [
+            Mat4::from_translation(Vec3::new(0.0, -1.0, 0.5)),
+            Mat4::from_rotation_z(0.3),
+        ];
+        let mut output = [Mat4::ZERO; 2];
+        let mut affine = [f32::NAN; AFFINE_MATRIX_STRIDE * 2 + 1];
+        let mut job: SkinningMatricesJobRef = SkinningMatricesJob::default();
+        job.set_model_matrices(&models);
+        job.set_inverse_bind_matrices(&inverse_binds);
+        job.run_affine(&mut affine).unwrap();
+        job.set_output(&mut output);
+        job.run().unwrap();
+
+        for joint in 0..2 {
+            let expected = models[joint] * inverse_binds[joint];
+            assert_eq!(output[joint], expected);
+            let rows = &affine[joint * AFFINE_MATRIX_STRIDE..(joint + 1) * AFFINE_MATRIX_STRIDE];
+            for row in 0..3 {
+                assert_eq!(Vec4::from_slice(&rows[row * 4..row * 4 + 4]), expected.row(row));
+            }
+            let point = Vec3::new(0.3, -0.7, 1.5);
+            let transformed = Vec3::new(
+                Vec4::from_slice(&rows[0..4]).dot(point.extend(1.0)),
+                Vec4::from_slice(&rows[4..8]).dot(point.extend(1.0)),
+                Vec4::from_slice(&rows[8..12]).dot(point.extend(1.0)),
+            );
+            assert!(transformed.abs_diff_eq(expected.transform_point3(point), 1e-5));
+        }
+        assert!(affine[AFFINE_MATRIX_STRIDE * 2].is_nan());
+    }
+}
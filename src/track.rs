@@ -2,17 +2,36 @@
 //! Track data structure definition.
 //!
 
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Debug;
 use glam::{Quat, Vec2, Vec3, Vec4};
-use std::fmt::Debug;
+#[cfg(feature = "std")]
 use std::io::Read;
 
+#[cfg(feature = "std")]
 use crate::archive::{Archive, ArchiveRead};
+#[cfg(feature = "std")]
 use crate::base::OzzError;
 
+/// Readable from an `Archive` with std, nothing is required without std.
+#[cfg(feature = "std")]
+pub trait TrackValueRead: ArchiveRead<Self> + Sized {}
+
+#[cfg(feature = "std")]
+impl<T: ArchiveRead<T>> TrackValueRead for T {}
+
+/// Readable from an `Archive` with std, nothing is required without std.
+#[cfg(not(feature = "std"))]
+pub trait TrackValueRead {}
+
+#[cfg(not(feature = "std"))]
+impl<T> TrackValueRead for T {}
+
 /// Value type that can be stored in a `Track`.
 pub trait TrackValue
 where
-    Self: Debug + Default + Copy + Clone + PartialEq + ArchiveRead<Self>,
+    Self: Debug + Default + Copy + Clone + PartialEq + TrackValueRead,
 {
     /// Ozz file tag in '.ozz' file for `Archive`.
     fn tag() -> &'static str;
@@ -153,6 +172,7 @@ impl<V: TrackValue> Track<V> {
     }
 
     /// Reads an `Track` from an `Archive`.
+    #[cfg(feature = "std")]
     pub fn from_archive(archive: &mut Archive<impl Read>) -> Result<Track<V>, OzzError> {
         if archive.tag() != Self::tag() {
             return Err(OzzError::InvalidTag);
@@ -184,7 +204,7 @@ impl<V: TrackValue> Track<V> {
     }
 
     /// Reads an `Track` from a file path.
-    #[cfg(not(feature = "wasm"))]
+    #[cfg(all(feature = "std", not(feature = "wasm")))]
     pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Track<V>, OzzError> {
         let mut archive = Archive::from_path(path)?;
         Track::from_archive(&mut archive)
@@ -2,9 +2,9 @@
 //! Track sampling job.
 //!
 
-use std::fmt::Debug;
-use std::rc::Rc;
-use std::sync::Arc;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use core::fmt::Debug;
 
 use crate::base::{OzzError, OzzObj};
 use crate::math::f32_clamp_or_max;
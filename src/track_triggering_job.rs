@@ -2,11 +2,13 @@
 //! Track Triggering Job.
 //!
 
-use std::fmt::Debug;
-use std::rc::Rc;
-use std::sync::Arc;
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use core::fmt::Debug;
 
 use crate::base::{OzzError, OzzObj};
+#[cfg(not(feature = "std"))]
+use crate::math::StdFloat;
 
 use crate::track::Track;
 
@@ -0,0 +1,369 @@
+//!
+//! Transition Job.
+//!
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::simd::prelude::*;
+#[cfg(feature = "std")]
+use std::sync::{Arc, RwLock};
+
+use crate::base::{OzzBuf, OzzError, OzzMutBuf, OzzObj};
+use crate::math::{f32_clamp_or_max, fx4_sign, SoaTransform, SoaVec3};
+use crate::skeleton::Skeleton;
+
+/// Easing curve applied to the ratio of `TransitionJob`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionEase {
+    /// Blends at constant speed.
+    #[default]
+    Linear,
+    /// Smoothstep curve `3t² - 2t³`, starts and ends the transition with a zero speed.
+    SmoothStep,
+}
+
+impl TransitionEase {
+    /// Applies the easing curve to `ratio`, in range 0.0-1.0.
+    #[inline]
+    pub fn apply(&self, ratio: f32) -> f32 {
+        match self {
+            TransitionEase::Linear => ratio,
+            TransitionEase::SmoothStep => ratio * ratio * (3.0 - 2.0 * ratio),
+        }
+    }
+}
+
+///
+/// Crossfades from a local-space pose to another, like for state machine transitions.
+///
+/// Unlike `BlendingJob`, there are exactly 2 inputs and a single ratio: output is `from` at ratio 0.0 and `to`
+/// at ratio 1.0 (both copied exactly). In between, translations and scales are linearly interpolated, and
+/// rotations are normalized-lerped along the shortest path. The ratio is eased by `ease` beforehand.
+///
+/// `from` is usually a snapshot of the pose when the transition started, and `to` the new clip's pose.
+///
+#[derive(Debug)]
+pub struct TransitionJob<S = Rc<Skeleton>, I = Rc<RefCell<Vec<SoaTransform>>>, O = Rc<RefCell<Vec<SoaTransform>>>>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    skeleton: Option<S>,
+    from: Option<I>,
+    to: Option<I>,
+    ratio: f32,
+    ease: TransitionEase,
+    output: Option<O>,
+}
+
+pub type TransitionJobRef<'t> = TransitionJob<&'t Skeleton, &'t [SoaTransform], &'t mut [SoaTransform]>;
+pub type TransitionJobRc = TransitionJob<Rc<Skeleton>, Rc<RefCell<Vec<SoaTransform>>>, Rc<RefCell<Vec<SoaTransform>>>>;
+#[cfg(feature = "std")]
+pub type TransitionJobArc =
+    TransitionJob<Arc<Skeleton>, Arc<RwLock<Vec<SoaTransform>>>, Arc<RwLock<Vec<SoaTransform>>>>;
+
+impl<S, I, O> Default for TransitionJob<S, I, O>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    fn default() -> TransitionJob<S, I, O> {
+        TransitionJob {
+            skeleton: None,
+            from: None,
+            to: None,
+            ratio: 0.0,
+            ease: TransitionEase::default(),
+            output: None,
+        }
+    }
+}
+
+impl<S, I, O> TransitionJob<S, I, O>
+where
+    S: OzzObj<Skeleton>,
+    I: OzzBuf<SoaTransform>,
+    O: OzzMutBuf<SoaTransform>,
+{
+    /// Gets skeleton of `TransitionJob`.
+    #[inline]
+    pub fn skeleton(&self) -> Option<&S> {
+        self.skeleton.as_ref()
+    }
+
+    /// Sets skeleton of `TransitionJob`.
+    ///
+    /// The skeleton that will be used during job. The number of joints determines the number of elements
+    /// to blend.
+    #[inline]
+    pub fn set_skeleton(&mut self, skeleton: S) {
+        self.skeleton = Some(skeleton);
+    }
+
+    /// Clears skeleton of `TransitionJob`.
+    #[inline]
+    pub fn clear_skeleton(&mut self) {
+        self.skeleton = None;
+    }
+
+    /// Gets from of `TransitionJob`.
+    #[inline]
+    pub fn from(&self) -> Option<&I> {
+        self.from.as_ref()
+    }
+
+    /// Sets from of `TransitionJob`.
+    ///
+    /// The local-space pose the transition starts from, output at ratio 0.0.
+    #[inline]
+    pub fn set_from(&mut self, from: I) {
+        self.from = Some(from);
+    }
+
+    /// Clears from of `TransitionJob`.
+    #[inline]
+    pub fn clear_from(&mut self) {
+        self.from = None;
+    }
+
+    /// Gets to of `TransitionJob`.
+    #[inline]
+    pub fn to(&self) -> Option<&I> {
+        self.to.as_ref()
+    }
+
+    /// Sets to of `TransitionJob`.
+    ///
+    /// The local-space pose the transition goes to, output at ratio 1.0.
+    #[inline]
+    pub fn set_to(&mut self, to: I) {
+        self.to = Some(to);
+    }
+
+    /// Clears to of `TransitionJob`.
+    #[inline]
+    pub fn clear_to(&mut self) {
+        self.to = None;
+    }
+
+    /// Gets ratio of `TransitionJob`.
+    #[inline]
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    /// Sets ratio of `TransitionJob`.
+    ///
+    /// Progress of the transition, clamped in range 0.0-1.0, usually the elapsed time divided by the
+    /// transition duration.
+    #[inline]
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.ratio = f32_clamp_or_max(ratio, 0.0, 1.0);
+    }
+
+    /// Gets ease of `TransitionJob`.
+    #[inline]
+    pub fn ease(&self) -> TransitionEase {
+        self.ease
+    }
+
+    /// Sets ease of `TransitionJob`.
+    ///
+    /// Easing curve applied to the ratio. Default is `TransitionEase::Linear`.
+    #[inline]
+    pub fn set_ease(&mut self, ease: TransitionEase) {
+        self.ease = ease;
+    }
+
+    /// Gets output of `TransitionJob`.
+    #[inline]
+    pub fn output(&self) -> Option<&O> {
+        self.output.as_ref()
+    }
+
+    /// Sets output of `TransitionJob`.
+    ///
+    /// The range of output local-space transforms, one for each soa joint.
+    #[inline]
+    pub fn set_output(&mut self, output: O) {
+        self.output = Some(output);
+    }
+
+    /// Clears output of `TransitionJob`.
+    #[inline]
+    pub fn clear_output(&mut self) {
+        self.output = None;
+    }
+
+    /// Validates `TransitionJob` parameters.
+    pub fn validate(&self) -> bool {
+        (|| {
+            let skeleton = self.skeleton.as_ref()?.obj();
+            let from = self.from.as_ref()?.buf().ok()?;
+            let to = self.to.as_ref()?.buf().ok()?;
+            let output = self.output.as_ref()?.buf().ok()?;
+
+            let mut ok = from.len() >= skeleton.num_soa_joints();
+            ok &= to.len() >= skeleton.num_soa_joints();
+            ok &= output.len() >= skeleton.num_soa_joints();
+            Some(ok)
+        })()
+        .unwrap_or(false)
+    }
+
+    /// Runs transition job's task.
+    /// The validate job before any operation is performed.
+    pub fn run(&mut self) -> Result<(), OzzError> {
+        let skeleton = self.skeleton.as_ref().ok_or(OzzError::InvalidJob)?.obj();
+        let from = self.from.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let to = self.to.as_ref().ok_or(OzzError::InvalidJob)?.buf()?;
+        let mut output = self.output.as_mut().ok_or(OzzError::InvalidJob)?.mut_buf()?;
+
+        let num_soa_joints = skeleton.num_soa_joints();
+        let mut ok = from.len() >= num_soa_joints;
+        ok &= to.len() >= num_soa_joints;
+        ok &= output.len() >= num_soa_joints;
+        if !ok {
+            return Err(OzzError::InvalidJob);
+        }
+
+        let ratio = self.ease.apply(self.ratio);
+        if ratio <= 0.0 {
+            output[..num_soa_joints].copy_from_slice(&from[..num_soa_joints]);
+        } else if ratio >= 1.0 {
+            output[..num_soa_joints].copy_from_slice(&to[..num_soa_joints]);
+        } else {
+            let alpha = f32x4::splat(ratio);
+            for ((out, from), to) in output.iter_mut().zip(from.iter()).zip(to.iter()).take(num_soa_joints) {
+                // Takes the shortest path, by flipping `to` rotations in the opposite hemisphere.
+                let rotation = to.rotation.xor_num(fx4_sign(from.rotation.dot(&to.rotation)));
+                *out = SoaTransform::new(
+                    SoaVec3::lerp(&from.translation, &to.translation, alpha),
+                    from.rotation.nlerp(&rotation, alpha),
+                    SoaVec3::lerp(&from.scale, &to.scale, alpha),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod transition_tests {
+    use glam::{Quat, Vec3};
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::base::DeterministicState;
+    use crate::skeleton::{JointHashMap, SkeletonRaw};
+
+    fn new_skeleton() -> Rc<Skeleton> {
+        // 5 joints
+        Rc::new(Skeleton::from_raw(&SkeletonRaw {
+            joint_rest_poses: vec![SoaTransform::IDENTITY; 2],
+            joint_names: {
+                let mut map = JointHashMap::with_hashers(DeterministicState::new(), DeterministicState::new());
+                map.insert("j0".into(), 0);
+                map.insert("j1".into(), 1);
+                map.insert("j2".into(), 2);
+                map.insert("j3".into(), 3);
+                map.insert("j4".into(), 4);
+                map
+            },
+            joint_parents: vec![-1, 0, 1, 2, 3],
+        }))
+    }
+
+    fn new_pose(offset: f32) -> Vec<SoaTransform> {
+        let mut pose = vec![SoaTransform::IDENTITY; 2];
+        for joint in 0..5 {
+            let f = joint as f32 + offset;
+            let soa = &mut pose[joint / 4];
+            soa.translation.set_vec3(joint % 4, Vec3::new(f, 0.3 * f, -f));
+            soa.rotation.set_quat(joint % 4, Quat::from_rotation_y(0.2 * f));
+            soa.scale.set_vec3(joint % 4, Vec3::splat(1.0 + 0.1 * f));
+        }
+        pose
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_validity() {
+        let skeleton = new_skeleton();
+        let buf = |len| Rc::new(RefCell::new(vec![SoaTransform::IDENTITY; len]));
+
+        let mut job: TransitionJob = TransitionJob::default();
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        job.set_skeleton(skeleton.clone());
+        job.set_from(buf(2));
+        job.set_output(buf(2));
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        job.set_to(buf(1));
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+
+        job.set_to(buf(2));
+        assert!(job.validate());
+        assert!(job.run().is_ok());
+
+        job.set_output(buf(1));
+        assert!(!job.validate());
+        assert!(job.run().unwrap_err().is_invalid_job());
+    }
+
+    fn run(from: &[SoaTransform], to: &[SoaTransform], ratio: f32, ease: TransitionEase) -> Vec<SoaTransform> {
+        let skeleton = new_skeleton();
+        let mut output = vec![SoaTransform::IDENTITY; 2];
+        let mut job: TransitionJobRef = TransitionJob::default();
+        job.set_skeleton(&skeleton);
+        job.set_from(from);
+        job.set_to(to);
+        job.set_ratio(ratio);
+        job.set_ease(ease);
+        job.set_output(&mut output);
+        job.run().unwrap();
+        output
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_run() {
+        let from = new_pose(0.0);
+        let mut to = new_pose(3.0);
+        // opposite hemisphere, same rotation
+        let rotation = to[0].rotation.quat(1);
+        to[0].rotation.set_quat(1, -rotation);
+
+        assert_eq!(run(&from, &to, 0.0, TransitionEase::Linear), from);
+        assert_eq!(run(&from, &to, 1.0, TransitionEase::Linear), to);
+        assert_eq!(run(&from, &to, -1.0, TransitionEase::SmoothStep), from);
+        assert_eq!(run(&from, &to, 2.0, TransitionEase::SmoothStep), to);
+
+        for (ease, ratio, alpha) in [
+            (TransitionEase::Linear, 0.5, 0.5),
+            (TransitionEase::Linear, 0.25, 0.25),
+            (TransitionEase::SmoothStep, 0.5, 0.5),
+            (TransitionEase::SmoothStep, 0.25, 0.15625),
+        ] {
+            let output = run(&from, &to, ratio, ease);
+            for joint in 0..5 {
+                let (out, from, to) = (&output[joint / 4], &from[joint / 4], &to[joint / 4]);
+                let lane = joint % 4;
+                let translation = from.translation.vec3(lane).lerp(to.translation.vec3(lane), alpha);
+                assert!(out.translation.vec3(lane).abs_diff_eq(translation, 1e-5));
+                // glam lerp takes the shortest path and normalizes
+                let rotation = from.rotation.quat(lane).lerp(to.rotation.quat(lane), alpha);
+                assert!(out.rotation.quat(lane).abs_diff_eq(rotation, 1e-5));
+                let scale = from.scale.vec3(lane).lerp(to.scale.vec3(lane), alpha);
+                assert!(out.scale.vec3(lane).abs_diff_eq(scale, 1e-5));
+            }
+        }
+    }
+}
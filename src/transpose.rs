@@ -0,0 +1,104 @@
+//!
+//! Transposition between SoA and AoS layouts.
+//!
+
+use glam::{Quat, Vec3};
+
+use crate::math::{SoaQuat, SoaVec3};
+
+/// Transposes `soa` to `aos`, lane `i % 4` of `soa[i / 4]` is written to `aos[i]`.
+///
+/// `min(aos.len(), soa.len() * 4)` elements are transposed, so trailing lanes of the last `SoaVec3` are
+/// ignored when `aos` length isn't a multiple of 4. Remaining `aos` elements are left unchanged.
+pub fn soa_to_aos_float3(soa: &[SoaVec3], aos: &mut [Vec3]) {
+    for (idx, value) in aos.iter_mut().take(soa.len() * 4).enumerate() {
+        *value = soa[idx / 4].vec3(idx % 4);
+    }
+}
+
+/// Transposes `aos` to `soa`, `aos[i]` is written to lane `i % 4` of `soa[i / 4]`.
+///
+/// `min(aos.len(), soa.len() * 4)` elements are transposed. Trailing lanes of the last written `SoaVec3`
+/// are zero-filled when `aos` length isn't a multiple of 4. Remaining `soa` elements are left unchanged.
+pub fn aos_to_soa_float3(aos: &[Vec3], soa: &mut [SoaVec3]) {
+    for (soa, aos) in soa.iter_mut().zip(aos.chunks(4)) {
+        *soa = SoaVec3::ZERO;
+        for (lane, value) in aos.iter().enumerate() {
+            soa.set_vec3(lane, *value);
+        }
+    }
+}
+
+/// Transposes `soa` to `aos`, lane `i % 4` of `soa[i / 4]` is written to `aos[i]`.
+///
+/// See `soa_to_aos_float3()`.
+pub fn soa_to_aos_quaternion(soa: &[SoaQuat], aos: &mut [Quat]) {
+    for (idx, value) in aos.iter_mut().take(soa.len() * 4).enumerate() {
+        *value = soa[idx / 4].quat(idx % 4);
+    }
+}
+
+/// Transposes `aos` to `soa`, `aos[i]` is written to lane `i % 4` of `soa[i / 4]`.
+///
+/// See `aos_to_soa_float3()`. Trailing lanes are filled with identity rather than zero, like padding joints
+/// of a skeleton, so that they remain valid rotations.
+pub fn aos_to_soa_quaternion(aos: &[Quat], soa: &mut [SoaQuat]) {
+    for (soa, aos) in soa.iter_mut().zip(aos.chunks(4)) {
+        *soa = SoaQuat::IDENTITY;
+        for (lane, value) in aos.iter().enumerate() {
+            soa.set_quat(lane, *value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod transpose_tests {
+    use wasm_bindgen_test::*;
+
+    use super::*;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_transpose_float3() {
+        for count in [1usize, 4, 7, 16] {
+            let aos: ::alloc::vec::Vec<Vec3> = (0..count)
+                .map(|idx| Vec3::new(idx as f32, -(idx as f32), idx as f32 * 0.5))
+                .collect();
+            let mut soa = ::alloc::vec![SoaVec3::splat(9.0); count.div_ceil(4)];
+            aos_to_soa_float3(&aos, &mut soa);
+            for idx in count..soa.len() * 4 {
+                assert_eq!(soa[idx / 4].vec3(idx % 4), Vec3::ZERO);
+            }
+
+            let mut output = ::alloc::vec![Vec3::splat(9.0); count + 1];
+            soa_to_aos_float3(&soa, &mut output[..count]);
+            assert_eq!(&output[..count], &aos[..]);
+            assert_eq!(output[count], Vec3::splat(9.0));
+        }
+
+        // shorter soa
+        let mut output = [Vec3::splat(9.0); 6];
+        soa_to_aos_float3(&[SoaVec3::splat(1.0)], &mut output);
+        assert_eq!(output[..4], [Vec3::ONE; 4]);
+        assert_eq!(output[4..], [Vec3::splat(9.0); 2]);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_transpose_quaternion() {
+        for count in [1usize, 4, 7, 16] {
+            let aos: ::alloc::vec::Vec<Quat> = (0..count)
+                .map(|idx| Quat::from_rotation_x(idx as f32 * 0.1) * Quat::from_rotation_z(idx as f32 * 0.2))
+                .collect();
+            let mut soa = ::alloc::vec![SoaQuat::splat(0.5); count.div_ceil(4)];
+            aos_to_soa_quaternion(&aos, &mut soa);
+            for idx in count..soa.len() * 4 {
+                assert_eq!(soa[idx / 4].quat(idx % 4), Quat::IDENTITY);
+            }
+
+            let mut output = ::alloc::vec![Quat::IDENTITY; count];
+            soa_to_aos_quaternion(&soa, &mut output);
+            assert_eq!(output, aos);
+        }
+    }
+}
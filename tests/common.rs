@@ -62,6 +62,80 @@ pub fn compare_with_cpp(folder: &str, name: &str, data: &[Mat4], diff: f32) -> R
     Ok(())
 }
 
+/// A frame of a reference dump, model-space matrices outputted by C++ ozz at a time ratio.
+#[derive(Debug, Clone)]
+pub struct ReferenceFrame {
+    pub ratio: f32,
+    pub models: Vec<Mat4>,
+}
+
+/// Reads a reference dump (`./expected/{folder}/{name}_cpp.ref`), frames of C++ ozz outputs.
+///
+/// No dump is checked in yet, they have to be exported from the C++ ozz samples.
+///
+/// Little-endian layout: `b"OZRF"` magic, `u32` frames count, `u32` matrices count per frame, then for each
+/// frame a `f32` time ratio followed by column-major matrices.
+pub fn load_reference(folder: &str, name: &str) -> Result<Vec<ReferenceFrame>, Box<dyn Error>> {
+    let path = format!("./expected/{0}/{1}_cpp.ref", folder, name);
+    #[cfg(not(feature = "wasm"))]
+    let buf = fs::read(&path)?;
+    #[cfg(all(feature = "wasm", feature = "nodejs"))]
+    let buf = nodejs::read_file(&path).map_err(|e| String::from(e.to_string()))?;
+
+    let mut words = buf
+        .get(4..)
+        .filter(|_| buf.starts_with(b"OZRF"))
+        .ok_or(format!("load_reference({}) magic", path))?
+        .chunks_exact(4)
+        .map(|w| [w[0], w[1], w[2], w[3]]);
+    let mut next = || words.next().ok_or(format!("load_reference({}) truncated", path));
+    let num_frames = u32::from_le_bytes(next()?) as usize;
+    let num_models = u32::from_le_bytes(next()?) as usize;
+
+    let mut frames = Vec::with_capacity(num_frames);
+    for _ in 0..num_frames {
+        let ratio = f32::from_le_bytes(next()?);
+        let mut models = Vec::with_capacity(num_models);
+        for _ in 0..num_models {
+            let mut cols = [0.0; 16];
+            for value in cols.iter_mut() {
+                *value = f32::from_le_bytes(next()?);
+            }
+            models.push(Mat4::from_cols_array(&cols));
+        }
+        frames.push(ReferenceFrame { ratio, models });
+    }
+    if next().is_ok() {
+        return Err(format!("load_reference({}) trailing data", path).into());
+    }
+    Ok(frames)
+}
+
+/// Compares `compute(ratio)` outputs with all frames of a reference dump, see `load_reference()`.
+pub fn compare_with_reference<F>(folder: &str, name: &str, diff: f32, mut compute: F) -> Result<(), Box<dyn Error>>
+where
+    F: FnMut(f32) -> Vec<Mat4>,
+{
+    let frames = load_reference(folder, name)?;
+    if frames.is_empty() {
+        return Err(format!("compare_with_reference({}) no frame", name).into());
+    }
+    for frame in frames.iter() {
+        let models = compute(frame.ratio);
+        if models.len() != frame.models.len() {
+            return Err(format!("compare_with_reference() ratio:{} size:{}", frame.ratio, models.len()).into());
+        }
+        for (idx, (actual, expected)) in models.iter().zip(frame.models.iter()).enumerate() {
+            if !actual.abs_diff_eq(*expected, diff) {
+                println!("actual: {:?}", actual);
+                println!("expected: {:?}", expected);
+                return Err(format!("compare_with_reference() ratio:{} idx:{}", frame.ratio, idx).into());
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(feature = "rkyv")]
 #[cfg(not(feature = "wasm"))]
 pub fn compare_with_rkyv<T>(folder: &str, name: &str, data: &T) -> Result<(), Box<dyn Error>>